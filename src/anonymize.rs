@@ -0,0 +1,225 @@
+use crate::git::{AuthorMerge, Contribution, AUTOMATION_EMAIL};
+use std::collections::HashMap;
+
+/// A replacement identity for one real author.
+#[derive(Debug, Clone)]
+pub struct Pseudonym {
+    pub name: String,
+    pub email: String,
+}
+
+/// FNV-1a, used instead of `DefaultHasher` because its output must not change
+/// between Rust releases: the same email has to get the same pseudonym in
+/// every report.
+pub(crate) fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Spreadsheet-style label: 0 → "A", 25 → "Z", 26 → "AA".
+fn letter_label(mut index: usize) -> String {
+    let mut label = Vec::new();
+    loop {
+        label.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    label.reverse();
+    String::from_utf8(label).unwrap_or_default()
+}
+
+/// Assigns `Contributor A`, `Contributor B`, ... to every email found in the
+/// given analyses, ordered by a deterministic hash of the email so the mapping
+/// is identical across repositories, summaries and runs over the same authors.
+pub fn build_pseudonyms(
+    contribution_maps: &[&HashMap<String, Vec<Contribution>>],
+) -> HashMap<String, Pseudonym> {
+    let mut emails: Vec<&str> = contribution_maps
+        .iter()
+        .flat_map(|map| map.values().flatten())
+        .map(|contrib| contrib.email.as_str())
+        // The `--merge-bots` row stands for no one in particular.
+        .filter(|email| *email != AUTOMATION_EMAIL)
+        .collect();
+    emails.sort_by(|a, b| stable_hash(a).cmp(&stable_hash(b)).then(a.cmp(b)));
+    emails.dedup();
+
+    emails
+        .into_iter()
+        .enumerate()
+        .map(|(index, email)| {
+            let label = letter_label(index);
+            (
+                email.to_string(),
+                Pseudonym {
+                    name: format!("Contributor {}", label),
+                    email: format!("contributor-{}@anonymized.invalid", label.to_lowercase()),
+                },
+            )
+        })
+        .collect()
+}
+
+/// The pseudonym of a `Name <email>` identity, formatted the same way.
+fn pseudonymous_identity(
+    identity: &str,
+    pseudonyms: &HashMap<String, Pseudonym>,
+) -> Option<String> {
+    identity
+        .rsplit_once('<')
+        .and_then(|(_, email)| pseudonyms.get(email.trim().trim_end_matches('>')))
+        .map(|pseudonym| format!("{} <{}>", pseudonym.name, pseudonym.email))
+}
+
+/// Replaces every author name and email in `contributions_map` with its
+/// pseudonym. Recorded committers become theirs too, or are dropped when they
+/// never authored anything and so have none. Trailer values naming an author
+/// (`Co-authored-by: Name <email>`) get that author's pseudonym; any other
+/// value may name someone too, so it is hidden. Bodies are dropped, as
+/// free text that often mentions people by name or email.
+pub fn anonymize_contributions(
+    contributions_map: &mut HashMap<String, Vec<Contribution>>,
+    pseudonyms: &HashMap<String, Pseudonym>,
+) {
+    for contrib in contributions_map.values_mut().flatten() {
+        if let Some(pseudonym) = pseudonyms.get(&contrib.email) {
+            contrib.author = pseudonym.name.clone();
+            contrib.email = pseudonym.email.clone();
+        }
+        for record in &mut contrib.commit_records {
+            record.committer = record
+                .committer
+                .as_deref()
+                .and_then(|committer| pseudonymous_identity(committer, pseudonyms));
+            record.body = None;
+            for value in record
+                .trailers
+                .iter_mut()
+                .flat_map(|t| t.values_mut().flatten())
+            {
+                *value = pseudonymous_identity(value, pseudonyms)
+                    .unwrap_or_else(|| String::from("(anonymized)"));
+            }
+        }
+    }
+}
+
+/// Replaces the emails of imported review counts with their authors'
+/// pseudonyms, matching case-insensitively as `apply_reviews` does. Reviewers
+/// with no commits get `reviewer-a@anonymized.invalid`, ... in hash order.
+pub fn anonymize_reviews(
+    reviews: &mut HashMap<String, u32>,
+    pseudonyms: &HashMap<String, Pseudonym>,
+) {
+    let by_email: HashMap<String, &Pseudonym> = pseudonyms
+        .iter()
+        .map(|(email, pseudonym)| (email.to_lowercase(), pseudonym))
+        .collect();
+
+    let mut reviewers: Vec<(String, u32)> = reviews.drain().collect();
+    reviewers.sort_by(|a, b| {
+        stable_hash(&a.0)
+            .cmp(&stable_hash(&b.0))
+            .then(a.0.cmp(&b.0))
+    });
+
+    let mut unmatched = 0;
+    for (email, count) in reviewers {
+        let email = match by_email.get(&email) {
+            Some(pseudonym) => pseudonym.email.clone(),
+            None => {
+                unmatched += 1;
+                format!(
+                    "reviewer-{}@anonymized.invalid",
+                    letter_label(unmatched - 1).to_lowercase()
+                )
+            }
+        };
+        *reviews.entry(email).or_insert(0) += count;
+    }
+}
+
+/// Shows `--fuzzy-merge` results under the merged author's pseudonym. The
+/// variants were merged away before pseudonyms were assigned, so they are
+/// hidden rather than given names of their own.
+pub fn anonymize_merges(merges: &mut [AuthorMerge], pseudonyms: &HashMap<String, Pseudonym>) {
+    for merge in merges {
+        if let Some(pseudonym) = pseudonyms.get(&merge.email) {
+            merge.author = pseudonym.name.clone();
+            merge.email = pseudonym.email.clone();
+        }
+        for variant in &mut merge.variants {
+            *variant = String::from("(anonymized)");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::CommitRecord;
+    use std::collections::BTreeMap;
+
+    fn anonymized(record: CommitRecord) -> CommitRecord {
+        let mut contributions_map = HashMap::from([(
+            "api".to_string(),
+            vec![Contribution {
+                author: "Jane".to_string(),
+                email: "jane@acme.com".to_string(),
+                commit_records: vec![record],
+                ..Default::default()
+            }],
+        )]);
+        let pseudonyms = build_pseudonyms(&[&contributions_map]);
+        anonymize_contributions(&mut contributions_map, &pseudonyms);
+        contributions_map.remove("api").unwrap()[0].commit_records[0].clone()
+    }
+
+    #[test]
+    fn trailers_are_anonymized() {
+        let record = anonymized(CommitRecord {
+            hash: "abc".to_string(),
+            trailers: Some(BTreeMap::from([
+                (
+                    "co-authored-by".to_string(),
+                    vec![
+                        "Jane Doe <jane@acme.com>".to_string(),
+                        "Bob Real <bob@real.com>".to_string(),
+                    ],
+                ),
+                ("reported-by".to_string(), vec!["Bob Real".to_string()]),
+            ])),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            record.trailers.unwrap(),
+            BTreeMap::from([
+                (
+                    "co-authored-by".to_string(),
+                    vec![
+                        "Contributor A <contributor-a@anonymized.invalid>".to_string(),
+                        "(anonymized)".to_string(),
+                    ],
+                ),
+                ("reported-by".to_string(), vec!["(anonymized)".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn bodies_are_dropped() {
+        let record = anonymized(CommitRecord {
+            hash: "abc".to_string(),
+            subject: Some("Fix login".to_string()),
+            body: Some("Thanks to Bob Real <bob@real.com> for the report.".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(record.subject.as_deref(), Some("Fix login"));
+        assert_eq!(record.body, None);
+    }
+}
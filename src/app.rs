@@ -1,35 +1,565 @@
-use crate::git::Contribution;
-use std::collections::HashMap;
+use crate::export::{ExportFormat, TimelineGranularity, YearRange, DEFAULT_TIMELINE_AUTHORS};
+use crate::git::{
+    apply_org_map, calculate_summaries, format_timestamp, repository_health, AtRiskFile,
+    AuthorComparison, AuthorMerge, CollaboratorPair, CommitReconciliation, Contribution, GroupBy,
+    HealthThresholds, LineWeights, OrphanedDirectory, OwnershipBasis, RepositoryHealth,
+    RepositoryInfo, SummaryBasis, Trend, DEFAULT_INACTIVE_DAYS, DEFAULT_ORPHAN_THRESHOLD,
+    DEFAULT_PERCENT_PRECISION,
+};
+use crate::score::{apply_summary_score, ScoreExpression};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum AppState {
     Loading,
     Main,
 }
 
+#[derive(Clone)]
 pub struct App {
     pub state: AppState,
     pub repositories: Vec<String>,
+    /// Where each analyzed repository lives on disk, by name.
+    pub repository_paths: HashMap<String, PathBuf>,
+    /// Directory each repository sits in (see `repository_group`), by name;
+    /// empty when they all share one or with `--no-grouping`.
+    pub repository_groups: HashMap<String, String>,
     pub contributions: HashMap<String, Vec<Contribution>>,
+    pub repository_info: HashMap<String, RepositoryInfo>,
     pub author_summaries: Vec<AuthorSummary>,
+    pub comparison: Option<ComparisonView>,
+    pub show_comparison: bool,
+    /// Author pairs who changed the same files, strongest first.
+    pub collaborators: Vec<CollaboratorPair>,
+    pub show_collaborators: bool,
+    /// Directories with no contributor above `orphan_threshold`, most diffuse first.
+    pub orphaned: Vec<OrphanedDirectory>,
+    pub show_orphaned: bool,
+    /// Share a contributor must exceed for a directory not to be orphaned.
+    pub orphan_threshold: f64,
+    /// Files mostly owned by inactive authors, riskiest first; empty without blame.
+    pub at_risk_files: Vec<AtRiskFile>,
+    pub show_at_risk: bool,
+    /// Days without a commit after which an author counts as inactive.
+    pub inactive_days: u32,
+    /// Authors folded together by `--merge-bots` or `--fuzzy-merge`, listed
+    /// with the errors.
+    pub author_merges: Vec<AuthorMerge>,
+    /// Repositories that failed to analyze, collected instead of being printed
+    /// over the TUI; empty with `--quiet`.
+    pub error_log: Vec<String>,
+    pub show_errors: bool,
+    pub coverage: CoverageStats,
+    /// Analyzed commit totals checked against `git rev-list` (`--validate`).
+    pub reconciliation: Vec<CommitReconciliation>,
+    /// Per-repository report files, as (repository, file name), that a
+    /// `--split-output` summary links to instead of including their tables.
+    pub repository_reports: Vec<(String, String)>,
+    pub display_metric: DisplayMetric,
+    /// Lines added/deleted columns show shares of the total instead of counts.
+    pub show_line_shares: bool,
+    /// Decorative colors: striped rows and per-author colors. Off when
+    /// `NO_COLOR` is set or with `--accessible`.
+    pub use_color: bool,
+    /// High-contrast text, bold underlined headers, a marked bold selection
+    /// and symbols next to colored badges (`--accessible`).
+    pub accessible: bool,
+    /// Merge commits were counted (`--include-merges`), so show them.
+    pub show_merges: bool,
+    /// Surviving lines were computed (`--blame`), so show them.
+    pub show_surviving: bool,
+    /// Commit signatures were checked (`--signatures`), so show the signed share.
+    pub show_signatures: bool,
+    /// Review counts were imported (`--reviews-csv`), so show them.
+    pub show_reviews: bool,
+    /// Organization names per email domain (`--org-map`); the summary shows
+    /// an Org column while set.
+    pub org_map: Option<HashMap<String, String>>,
+    /// Share of a directory's lines a contributor must exceed to be suggested
+    /// as its owner in the CODEOWNERS export.
+    pub codeowners_threshold: f64,
+    /// Whether ownership-based features weigh lines or commits.
+    pub ownership_basis: OwnershipBasis,
+    /// Whether summary rows are authors or email domains.
+    pub group_by: GroupBy,
+    /// What summary rows' overall percentages are measured against.
+    pub summary_basis: SummaryBasis,
+    /// Weights the percentages were computed with (`--add-weight`/`--delete-weight`).
+    pub line_weights: LineWeights,
+    /// Expression the percentages and ranks were scored with (`--score`).
+    pub score: Option<ScoreExpression>,
+    /// Decimal places shown for percentages (`--percent-precision`).
+    pub percent_precision: usize,
+    /// Years the HTML report's activity calendars cover; by default the
+    /// first to the last year with commits (`--calendar-years`).
+    pub calendar_years: Option<YearRange>,
+    /// Period the HTML report's commits-over-time chart steps by.
+    pub timeline_granularity: TimelineGranularity,
+    /// Authors with the most commits the chart draws; 0 leaves it out.
+    pub timeline_authors: usize,
+    /// Show dates in the TUI as "3 months ago" instead of "2024-03-11";
+    /// exports always use absolute dates.
+    pub relative_dates: bool,
     pub current_tab: usize,
+    /// Highlighted row of each tab. Selections are kept per tab, so switching
+    /// away and back returns to the same row; Esc clears the current one.
     pub selected_in_tab: Vec<Option<usize>>,
+    /// Tabs visited, for Backspace / `[` and `]` to step through.
+    pub navigation: NavigationHistory,
     pub loading_message: String,
-    pub loading_progress: u8,
+    /// Real analysis progress in percent; `None` while the current phase has no
+    /// measurable progress (e.g. repository discovery).
+    pub loading_progress: Option<u8>,
+    /// Animation counter advanced by the UI tick, independent of progress.
+    pub loading_tick: u8,
     pub show_help: bool,
+    /// Borderless tables, one-line tabs and no footer unless it has news, to
+    /// fit more rows on small terminals (`--compact`, toggled with `d`).
+    pub compact: bool,
+    /// The summary only lists authors with commits in every repository
+    /// (`--ubiquitous-only`, toggled with `u`).
+    pub ubiquitous_only: bool,
+    pub show_author_detail: bool,
+    /// First line shown of the detail popup's commit list, while it is open
+    /// (`--keep-commits`, Enter in the popup).
+    pub commit_list: Option<usize>,
+    pub picker: Option<RepoPicker>,
+    /// Column the summary and repository tables are sorted by (`:sort`).
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    /// Per tab, like `selected_in_tab`: only rows whose author or email
+    /// contain this, ignoring case (`:filter`). Each tab keeps its own, so
+    /// switching tabs does not lose it.
+    pub filters: Vec<String>,
+    /// Text typed into the command bar after `:`, while it has focus.
+    pub command_line: Option<String>,
+    /// Highlighted entry of the export menu overlay, while it is open.
+    pub export_menu: Option<usize>,
+    /// Outcome of the last action, shown in the footer until the next key.
+    pub status_message: Option<String>,
+    /// Something visible changed since the last frame; the event loop only
+    /// redraws when this is set.
+    pub dirty: bool,
     pub quit: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct AuthorSummary {
     pub author: String,
     pub email: String,
     pub total_commits: u32,
+    pub total_merges: u32,
     pub total_lines_added: u32,
     pub total_lines_deleted: u32,
     pub overall_contribution_percent: f64,
     pub preferred_repo: String,
     pub preferred_repo_percent: f64,
+    /// Share of the author's own changed lines in each repository, largest
+    /// first; adds up to 100% unless they changed no lines at all.
+    pub repo_distribution: Vec<(String, f64)>,
+    /// Repositories the author has any commits in.
+    pub repositories: usize,
+    pub terms: HashMap<String, u32>,
+    /// Distinct issues referenced, counted per repository since issue numbers
+    /// are only unique within one.
+    pub issues_touched: u32,
+    /// Earliest commit across all repositories, ignoring any date window.
+    pub first_commit_at: Option<i64>,
+    /// Latest commit across all repositories, ignoring any date window.
+    pub last_commit_at: Option<i64>,
+    /// Position by overall contribution, starting at 1; ties share a rank.
+    pub rank: u32,
+    /// Lines at `HEAD` attributed by `git blame`, across all repositories.
+    pub total_surviving_lines: u32,
+    /// Signed commits across all repositories; zero unless signatures are checked.
+    pub total_signed_commits: u32,
+    /// Commits inside the `--trend-window`, across all repositories.
+    pub recent_commits: u32,
+    /// Recent versus overall commit rate; `None` without a trend window.
+    pub trend: Option<Trend>,
+    /// Code reviews imported with `--reviews-csv`; zero without one.
+    pub reviews: u32,
+    /// Organization of the email's domain from `--org-map`, or the raw
+    /// domain when the map has none; `None` without a map.
+    pub organization: Option<String>,
+}
+
+/// Whether an author's first-ever commit falls inside the analyzed window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContributorStatus {
+    New,
+    Returning,
+}
+
+/// Classifies an author by their first-ever commit relative to the start of
+/// the analyzed window. A first commit exactly at the window start counts as new.
+pub fn contributor_status(first_commit_at: Option<i64>, window_start: i64) -> ContributorStatus {
+    match first_commit_at {
+        Some(first) if first < window_start => ContributorStatus::Returning,
+        _ => ContributorStatus::New,
+    }
+}
+
+/// Contribution metric shown in the last column of the tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMetric {
+    /// Share of lines added plus deleted.
+    #[default]
+    Lines,
+    /// Share of commits.
+    Commits,
+    /// Lines added minus lines deleted.
+    NetLines,
+}
+
+impl DisplayMetric {
+    pub fn next(self) -> DisplayMetric {
+        match self {
+            DisplayMetric::Lines => DisplayMetric::Commits,
+            DisplayMetric::Commits => DisplayMetric::NetLines,
+            DisplayMetric::NetLines => DisplayMetric::Lines,
+        }
+    }
+}
+
+/// Column the tables can be sorted by from the command bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortColumn {
+    #[default]
+    Rank,
+    Author,
+    Email,
+    Commits,
+    Added,
+    Deleted,
+    /// Contribution percentage (overall percentage on the summary).
+    Contribution,
+    /// Date of the most recent commit.
+    Last,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 8] = [
+        SortColumn::Rank,
+        SortColumn::Author,
+        SortColumn::Email,
+        SortColumn::Commits,
+        SortColumn::Added,
+        SortColumn::Deleted,
+        SortColumn::Contribution,
+        SortColumn::Last,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortColumn::Rank => "rank",
+            SortColumn::Author => "author",
+            SortColumn::Email => "email",
+            SortColumn::Commits => "commits",
+            SortColumn::Added => "added",
+            SortColumn::Deleted => "deleted",
+            SortColumn::Contribution => "contribution",
+            SortColumn::Last => "last",
+        }
+    }
+
+    /// Looks a column up by its name or an unambiguous prefix of it.
+    pub fn parse(name: &str) -> Result<SortColumn, String> {
+        let name = name.to_lowercase();
+        let matches: Vec<SortColumn> = SortColumn::ALL
+            .into_iter()
+            .filter(|column| column.name().starts_with(&name))
+            .collect();
+        match matches[..] {
+            [column] => Ok(column),
+            [] => Err(format!(
+                "Unknown sort column '{}', expected one of: {}",
+                name,
+                SortColumn::ALL.map(SortColumn::name).join(", ")
+            )),
+            _ => Err(format!(
+                "Ambiguous sort column '{}', could be: {}",
+                name,
+                matches
+                    .iter()
+                    .map(|column| column.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Names read best A to Z and ranks from #1, while counts and dates are
+    /// most useful largest first.
+    pub fn descending_by_default(self) -> bool {
+        !matches!(
+            self,
+            SortColumn::Rank | SortColumn::Author | SortColumn::Email
+        )
+    }
+
+    fn compare(self, a: &impl TableRow, b: &impl TableRow) -> Ordering {
+        match self {
+            SortColumn::Rank => a.rank().cmp(&b.rank()),
+            SortColumn::Author => a.author().to_lowercase().cmp(&b.author().to_lowercase()),
+            SortColumn::Email => a.email().to_lowercase().cmp(&b.email().to_lowercase()),
+            SortColumn::Commits => a.commits().cmp(&b.commits()),
+            SortColumn::Added => a.lines().0.cmp(&b.lines().0),
+            SortColumn::Deleted => a.lines().1.cmp(&b.lines().1),
+            SortColumn::Contribution => a.percent().total_cmp(&b.percent()),
+            SortColumn::Last => a.last_commit_at().cmp(&b.last_commit_at()),
+        }
+    }
+}
+
+/// What sorting and filtering look at in a summary or repository table row.
+trait TableRow {
+    fn author(&self) -> &str;
+    fn email(&self) -> &str;
+    fn rank(&self) -> u32;
+    fn commits(&self) -> u32;
+    /// Lines added and deleted.
+    fn lines(&self) -> (u32, u32);
+    fn percent(&self) -> f64;
+    fn last_commit_at(&self) -> Option<i64>;
+}
+
+impl TableRow for AuthorSummary {
+    fn author(&self) -> &str {
+        &self.author
+    }
+    fn email(&self) -> &str {
+        &self.email
+    }
+    fn rank(&self) -> u32 {
+        self.rank
+    }
+    fn commits(&self) -> u32 {
+        self.total_commits
+    }
+    fn lines(&self) -> (u32, u32) {
+        (self.total_lines_added, self.total_lines_deleted)
+    }
+    fn percent(&self) -> f64 {
+        self.overall_contribution_percent
+    }
+    fn last_commit_at(&self) -> Option<i64> {
+        self.last_commit_at
+    }
+}
+
+impl TableRow for Contribution {
+    fn author(&self) -> &str {
+        &self.author
+    }
+    fn email(&self) -> &str {
+        &self.email
+    }
+    fn rank(&self) -> u32 {
+        self.rank
+    }
+    fn commits(&self) -> u32 {
+        self.commits
+    }
+    fn lines(&self) -> (u32, u32) {
+        (self.lines_added, self.lines_deleted)
+    }
+    fn percent(&self) -> f64 {
+        self.contribution_percent
+    }
+    fn last_commit_at(&self) -> Option<i64> {
+        self.last_commit_at
+    }
+}
+
+/// Headline totals across every analyzed repository.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OverviewStats {
+    pub repositories: usize,
+    pub authors: usize,
+    pub commits: u32,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+}
+
+impl OverviewStats {
+    pub fn line_churn(&self) -> u64 {
+        self.lines_added as u64 + self.lines_deleted as u64
+    }
+}
+
+/// How complete a run was: what discovery found and what analysis made of it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverageStats {
+    pub repositories_found: usize,
+    pub repositories_analyzed: usize,
+    pub repositories_failed: usize,
+    /// Analyzed but dropped by `--min-repo-commits`.
+    pub repositories_hidden: usize,
+    /// Never analyzed because the analysis was cancelled first.
+    pub repositories_skipped: usize,
+    /// Commits (and merges, when counted) across every analyzed repository,
+    /// including hidden ones.
+    pub commits_processed: u32,
+}
+
+impl CoverageStats {
+    pub fn is_complete(&self) -> bool {
+        self.repositories_failed == 0 && self.repositories_skipped == 0
+    }
+
+    /// One-line description, e.g. "Coverage: 4 found | 3 analyzed | 1 failed | 812 commits processed".
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Coverage: {} found | {} analyzed | {} failed",
+            self.repositories_found, self.repositories_analyzed, self.repositories_failed
+        );
+        if self.repositories_hidden > 0 {
+            summary.push_str(&format!(" | {} hidden", self.repositories_hidden));
+        }
+        if self.repositories_skipped > 0 {
+            summary.push_str(&format!(" | {} skipped", self.repositories_skipped));
+        }
+        summary.push_str(&format!(" | {} commits processed", self.commits_processed));
+        summary
+    }
+}
+
+/// A tab and the row selected in it, as remembered by `NavigationHistory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavigationEntry {
+    pub tab: usize,
+    pub selected: Option<usize>,
+}
+
+/// Most entries kept for going back; older ones are forgotten.
+pub const NAVIGATION_HISTORY_LIMIT: usize = 50;
+
+/// Tabs visited before and after the current one, as in a browser: moving to
+/// a new tab clears everything that could be gone forward to.
+#[derive(Debug, Clone, Default)]
+pub struct NavigationHistory {
+    back: VecDeque<NavigationEntry>,
+    forward: Vec<NavigationEntry>,
+}
+
+impl NavigationHistory {
+    /// Remembers the view being left for a new one.
+    pub fn record(&mut self, left: NavigationEntry) {
+        self.back.push_back(left);
+        if self.back.len() > NAVIGATION_HISTORY_LIMIT {
+            self.back.pop_front();
+        }
+        self.forward.clear();
+    }
+
+    /// The previous view, keeping `current` to go forward to again.
+    pub fn back(&mut self, current: NavigationEntry) -> Option<NavigationEntry> {
+        let entry = self.back.pop_back()?;
+        self.forward.push(current);
+        Some(entry)
+    }
+
+    /// The view last gone back from, keeping `current` to go back to again.
+    pub fn forward(&mut self, current: NavigationEntry) -> Option<NavigationEntry> {
+        let entry = self.forward.pop()?;
+        self.back.push_back(current);
+        Some(entry)
+    }
+}
+
+/// State of the Ctrl+P repository picker overlay.
+#[derive(Debug, Clone, Default)]
+pub struct RepoPicker {
+    pub query: String,
+    /// Tab indices matching `query`, best match first.
+    pub matches: Vec<usize>,
+    /// Position within `matches` of the highlighted entry.
+    pub selected: usize,
+}
+
+/// Scores `candidate` against `query` case-insensitively: substring matches rank
+/// above scattered subsequence matches, and earlier, tighter matches rank higher.
+/// Returns `None` if the query characters do not all appear in order.
+pub fn fuzzy_score(candidate: &str, query: &str) -> Option<i64> {
+    let candidate = candidate.to_lowercase();
+    let query = query.to_lowercase();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    if let Some(position) = candidate.find(&query) {
+        return Some(1000 - position as i64);
+    }
+
+    let mut gaps = 0;
+    let mut last_match: Option<usize> = None;
+    let mut candidate_chars = candidate.chars().enumerate();
+
+    for q in query.chars() {
+        let (index, _) = candidate_chars.find(|(_, c)| *c == q)?;
+        if let Some(last) = last_match {
+            gaps += (index - last - 1) as i64;
+        }
+        last_match = Some(index);
+    }
+
+    Some(-gaps)
+}
+
+/// Per-author deltas between two analyzed time windows (`--compare`).
+#[derive(Debug, Clone)]
+pub struct ComparisonView {
+    pub baseline_label: String,
+    pub current_label: String,
+    pub authors: Vec<AuthorComparison>,
+}
+
+/// Everything the loading thread produces, handed to the UI in one piece
+/// when analysis finishes or is cancelled.
+#[derive(Debug, Default)]
+pub struct AnalysisReport {
+    pub repositories: Vec<String>,
+    pub repository_paths: HashMap<String, PathBuf>,
+    pub repository_groups: HashMap<String, String>,
+    pub contributions: HashMap<String, Vec<Contribution>>,
+    pub repository_info: HashMap<String, RepositoryInfo>,
+    pub author_summaries: Vec<AuthorSummary>,
+    pub comparison: Option<ComparisonView>,
+    pub collaborators: Vec<CollaboratorPair>,
+    pub orphaned: Vec<OrphanedDirectory>,
+    pub at_risk_files: Vec<AtRiskFile>,
+    pub author_merges: Vec<AuthorMerge>,
+    pub coverage: CoverageStats,
+    pub reconciliation: Vec<CommitReconciliation>,
+    /// Discovery and analysis errors, for the errors view; empty with `--quiet`.
+    pub errors: Vec<String>,
+}
+
+/// What the loading thread reports over its channel, in the order it
+/// happens. The UI drains these every tick instead of sharing the `App`.
+#[derive(Debug)]
+pub enum ProgressEvent {
+    /// Discovery finished with this many repositories to analyze.
+    Found(usize),
+    /// Repository `index` (from 0) of `total` is being analyzed.
+    Analyzing {
+        index: usize,
+        total: usize,
+        name: String,
+    },
+    /// Analysis is over; nothing is sent after this.
+    Done(Box<AnalysisReport>),
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
@@ -37,92 +567,1207 @@ impl App {
         App {
             state: AppState::Loading,
             repositories: Vec::new(),
+            repository_paths: HashMap::new(),
+            repository_groups: HashMap::new(),
             contributions: HashMap::new(),
+            repository_info: HashMap::new(),
             author_summaries: Vec::new(),
+            comparison: None,
+            show_comparison: false,
+            collaborators: Vec::new(),
+            show_collaborators: false,
+            orphaned: Vec::new(),
+            show_orphaned: false,
+            orphan_threshold: DEFAULT_ORPHAN_THRESHOLD,
+            at_risk_files: Vec::new(),
+            show_at_risk: false,
+            inactive_days: DEFAULT_INACTIVE_DAYS,
+            author_merges: Vec::new(),
+            error_log: Vec::new(),
+            show_errors: false,
+            coverage: CoverageStats::default(),
+            reconciliation: Vec::new(),
+            repository_reports: Vec::new(),
+            percent_precision: DEFAULT_PERCENT_PRECISION,
+            calendar_years: None,
+            timeline_granularity: TimelineGranularity::default(),
+            timeline_authors: DEFAULT_TIMELINE_AUTHORS,
+            relative_dates: false,
+            display_metric: DisplayMetric::default(),
+            show_line_shares: false,
+            use_color: true,
+            accessible: false,
+            show_merges: false,
+            show_surviving: false,
+            show_signatures: false,
+            show_reviews: false,
+            org_map: None,
+            codeowners_threshold: 50.0,
+            ownership_basis: OwnershipBasis::default(),
+            group_by: GroupBy::default(),
+            summary_basis: SummaryBasis::default(),
+            line_weights: LineWeights::default(),
+            score: None,
             current_tab: 0,
             selected_in_tab: Vec::new(),
+            navigation: NavigationHistory::default(),
             loading_message: String::from("Initializing..."),
-            loading_progress: 0,
+            loading_progress: None,
+            loading_tick: 0,
             show_help: false,
+            compact: false,
+            ubiquitous_only: false,
+            show_author_detail: false,
+            commit_list: None,
+            picker: None,
+            sort_column: SortColumn::Rank,
+            sort_descending: false,
+            filters: Vec::new(),
+            command_line: None,
+            export_menu: None,
+            status_message: None,
+            dirty: true,
             quit: false,
         }
     }
 
+    /// Appended to the summary title while `ubiquitous_only` filters it.
+    pub fn summary_filter_note(&self) -> &'static str {
+        if self.ubiquitous_only {
+            " (in every repository)"
+        } else {
+            ""
+        }
+    }
+
+    /// Summary rows as listed on the summary tab: every author, or with
+    /// `ubiquitous_only` just those with commits in every repository, after
+    /// the command bar's filter and sort.
+    pub fn summary_rows(&self) -> Vec<&AuthorSummary> {
+        self.arrange_rows(
+            self.author_summaries
+                .iter()
+                .filter(|s| !self.ubiquitous_only || s.repositories >= self.repositories.len()),
+            self.tab_filter(self.repositories.len()),
+        )
+    }
+
+    /// A repository's rows as listed in its tab, after that tab's filter and
+    /// the command bar's sort.
+    pub fn repository_rows(&self, repo_name: &str) -> Vec<&Contribution> {
+        let tab = self.repositories.iter().position(|name| name == repo_name);
+        self.arrange_rows(
+            self.contributions.get(repo_name).into_iter().flatten(),
+            tab.map_or("", |tab| self.tab_filter(tab)),
+        )
+    }
+
+    /// The `:filter` text of a tab; empty when it has none.
+    pub fn tab_filter(&self, tab: usize) -> &str {
+        self.filters.get(tab).map_or("", String::as_str)
+    }
+
+    /// Drops rows not matching `filter` and orders the rest by the sort
+    /// column; ties keep their rank order.
+    fn arrange_rows<'a, T: TableRow + 'a>(
+        &self,
+        rows: impl Iterator<Item = &'a T>,
+        filter: &str,
+    ) -> Vec<&'a T> {
+        let filter = filter.to_lowercase();
+        let mut rows: Vec<&T> = rows
+            .filter(|row| {
+                row.author().to_lowercase().contains(&filter)
+                    || row.email().to_lowercase().contains(&filter)
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            let ordering = self.sort_column.compare(*a, *b);
+            if self.sort_descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        rows
+    }
+
+    /// Sort and the current tab's filter as shown in the command bar, e.g.
+    /// `sort: commits ↓`.
+    pub fn sort_filter_status(&self) -> String {
+        let mut status = format!(
+            "sort: {} {}",
+            self.sort_column.name(),
+            if self.sort_descending { "↓" } else { "↑" }
+        );
+        let filter = self.tab_filter(self.current_tab);
+        if !filter.is_empty() {
+            status.push_str(&format!(" | filter: {}", filter));
+        }
+        status
+    }
+
+    /// Whether the current tab's table differs from plain rank order.
+    pub fn is_sorted_or_filtered(&self) -> bool {
+        self.sort_column != SortColumn::Rank
+            || self.sort_descending
+            || !self.tab_filter(self.current_tab).is_empty()
+    }
+
+    /// Switches the summary between all authors and those in every
+    /// repository; its selection is cleared since the rows change.
+    pub fn toggle_ubiquitous_only(&mut self) {
+        self.ubiquitous_only = !self.ubiquitous_only;
+        if let Some(selected) = self.selected_in_tab.get_mut(self.repositories.len()) {
+            *selected = None;
+        }
+        if self.current_tab >= self.repositories.len() {
+            self.show_author_detail = false;
+        }
+    }
+
     pub fn next(&mut self) {
+        if let Some(offset) = self.commit_list {
+            let commits = self
+                .selected_contribution()
+                .map_or(0, |c| c.kept_commits.len());
+            self.commit_list = Some((offset + 1).min(commits.saturating_sub(1)));
+            return;
+        }
         if self.current_tab >= self.repositories.len() {
+            let summary_rows = self.summary_rows().len();
             if let Some(i) = self.selected_in_tab[self.current_tab] {
-                if i >= self.author_summaries.len() - 1 {
+                if i + 1 >= summary_rows {
                     self.selected_in_tab[self.current_tab] = Some(0);
                 } else {
                     self.selected_in_tab[self.current_tab] = Some(i + 1);
                 }
-            } else if !self.author_summaries.is_empty() {
+            } else if summary_rows > 0 {
                 self.selected_in_tab[self.current_tab] = Some(0);
             }
         } else {
+            let repo_rows = self
+                .repository_rows(&self.repositories[self.current_tab])
+                .len();
             if let Some(i) = self.selected_in_tab[self.current_tab] {
-                let repo_name = &self.repositories[self.current_tab];
-                if let Some(repo_contribs) = self.contributions.get(repo_name) {
-                    if i >= repo_contribs.len() - 1 {
-                        self.selected_in_tab[self.current_tab] = Some(0);
-                    } else {
-                        self.selected_in_tab[self.current_tab] = Some(i + 1);
-                    }
-                }
-            } else {
-                let repo_name = &self.repositories[self.current_tab];
-                if let Some(repo_contribs) = self.contributions.get(repo_name) {
-                    if !repo_contribs.is_empty() {
-                        self.selected_in_tab[self.current_tab] = Some(0);
-                    }
+                if i + 1 >= repo_rows {
+                    self.selected_in_tab[self.current_tab] = Some(0);
+                } else {
+                    self.selected_in_tab[self.current_tab] = Some(i + 1);
                 }
+            } else if repo_rows > 0 {
+                self.selected_in_tab[self.current_tab] = Some(0);
             }
         }
     }
 
     pub fn previous(&mut self) {
+        if let Some(offset) = self.commit_list {
+            self.commit_list = Some(offset.saturating_sub(1));
+            return;
+        }
         if self.current_tab >= self.repositories.len() {
+            let summary_rows = self.summary_rows().len();
             if let Some(i) = self.selected_in_tab[self.current_tab] {
                 if i == 0 {
-                    self.selected_in_tab[self.current_tab] = Some(self.author_summaries.len() - 1);
+                    self.selected_in_tab[self.current_tab] = Some(summary_rows.saturating_sub(1));
                 } else {
                     self.selected_in_tab[self.current_tab] = Some(i - 1);
                 }
-            } else if !self.author_summaries.is_empty() {
-                self.selected_in_tab[self.current_tab] = Some(self.author_summaries.len() - 1);
+            } else if summary_rows > 0 {
+                self.selected_in_tab[self.current_tab] = Some(summary_rows - 1);
             }
         } else {
+            let repo_rows = self
+                .repository_rows(&self.repositories[self.current_tab])
+                .len();
             if let Some(i) = self.selected_in_tab[self.current_tab] {
-                let repo_name = &self.repositories[self.current_tab];
-                if let Some(repo_contribs) = self.contributions.get(repo_name) {
-                    if i == 0 {
-                        self.selected_in_tab[self.current_tab] = Some(repo_contribs.len() - 1);
-                    } else {
-                        self.selected_in_tab[self.current_tab] = Some(i - 1);
-                    }
-                }
-            } else {
-                let repo_name = &self.repositories[self.current_tab];
-                if let Some(repo_contribs) = self.contributions.get(repo_name) {
-                    if !repo_contribs.is_empty() {
-                        self.selected_in_tab[self.current_tab] = Some(repo_contribs.len() - 1);
-                    }
+                if i == 0 {
+                    self.selected_in_tab[self.current_tab] = Some(repo_rows.saturating_sub(1));
+                } else {
+                    self.selected_in_tab[self.current_tab] = Some(i - 1);
                 }
+            } else if repo_rows > 0 {
+                self.selected_in_tab[self.current_tab] = Some(repo_rows - 1);
             }
         }
     }
 
     pub fn next_tab(&mut self) {
         let tab_count = self.repositories.len() + 1;
-        self.current_tab = (self.current_tab + 1) % tab_count;
+        self.switch_tab((self.current_tab + 1) % tab_count);
     }
 
     pub fn previous_tab(&mut self) {
         let tab_count = self.repositories.len() + 1;
-        self.current_tab = (self.current_tab + tab_count - 1) % tab_count;
+        self.switch_tab((self.current_tab + tab_count - 1) % tab_count);
+    }
+
+    /// Moves to `tab`, remembering the current view in the navigation history.
+    fn switch_tab(&mut self, tab: usize) {
+        if tab != self.current_tab {
+            self.navigation.record(self.current_view());
+            self.current_tab = tab;
+        }
+        self.show_author_detail = false;
+    }
+
+    /// The current tab and its selected row.
+    pub fn current_view(&self) -> NavigationEntry {
+        NavigationEntry {
+            tab: self.current_tab,
+            selected: self
+                .selected_in_tab
+                .get(self.current_tab)
+                .copied()
+                .flatten(),
+        }
+    }
+
+    /// Returns to the tab and row shown before the last tab switch.
+    pub fn go_back(&mut self) {
+        if let Some(entry) = self.navigation.back(self.current_view()) {
+            self.restore_view(entry);
+        }
+    }
+
+    /// Undoes the last `go_back`.
+    pub fn go_forward(&mut self) {
+        if let Some(entry) = self.navigation.forward(self.current_view()) {
+            self.restore_view(entry);
+        }
+    }
+
+    fn restore_view(&mut self, entry: NavigationEntry) {
+        if let Some(selected) = self.selected_in_tab.get_mut(entry.tab) {
+            self.current_tab = entry.tab;
+            *selected = entry.selected;
+        }
+        self.show_author_detail = false;
+    }
+
+    /// Records real progress. Progress never moves backwards, so a late or
+    /// out-of-order update cannot shrink a nearly complete bar.
+    pub fn set_loading_progress(&mut self, percent: u8) {
+        let percent = percent.min(100);
+        self.loading_progress = Some(self.loading_progress.map_or(percent, |p| p.max(percent)));
+    }
+
+    /// Updates the loading screen from one event of the loading thread, and
+    /// switches to the main view with its report once it is done.
+    pub fn apply_progress(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Found(0) => {
+                self.loading_message = String::from("No Git repositories found!");
+            }
+            ProgressEvent::Found(count) => {
+                self.loading_message = format!("Found {} repositories", count);
+                self.set_loading_progress(0);
+            }
+            ProgressEvent::Analyzing { index, total, name } => {
+                self.loading_message =
+                    format!("Analyzing repository {}/{}: {}", index + 1, total, name);
+                self.set_loading_progress(((index as f32 / total as f32) * 100.0) as u8);
+            }
+            ProgressEvent::Done(report) => self.finish_loading(*report),
+        }
+        self.dirty = true;
+    }
+
+    fn finish_loading(&mut self, report: AnalysisReport) {
+        self.repositories = report.repositories;
+        self.repository_paths = report.repository_paths;
+        self.repository_groups = report.repository_groups;
+        self.contributions = report.contributions;
+        self.repository_info = report.repository_info;
+        self.author_summaries = report.author_summaries;
+        self.comparison = report.comparison;
+        self.collaborators = report.collaborators;
+        self.orphaned = report.orphaned;
+        self.at_risk_files = report.at_risk_files;
+        self.coverage = report.coverage;
+        self.reconciliation = report.reconciliation;
+        self.error_log.extend(report.errors);
+        if !report.author_merges.is_empty() {
+            self.status_message = Some(format!(
+                "Merged {} group(s) of authors; press 'x' to check them",
+                report.author_merges.len()
+            ));
+        }
+        self.author_merges = report.author_merges;
+        self.set_loading_progress(100);
+        self.selected_in_tab = vec![None; self.repositories.len() + 1];
+        self.filters = vec![String::new(); self.repositories.len() + 1];
+        self.current_tab = self.current_tab.min(self.repositories.len());
+        self.state = AppState::Main;
+    }
+
+    /// Advances the loading animation. Only the indicator moves; real progress
+    /// is left untouched, and nothing happens once loading has finished.
+    pub fn tick(&mut self) {
+        if self.state == AppState::Loading {
+            self.loading_tick = self.loading_tick.wrapping_add(1);
+            self.dirty = true;
+        }
+    }
+
+    pub fn overview(&self) -> OverviewStats {
+        OverviewStats {
+            repositories: self.contributions.len(),
+            // Review-only rows from `--reviews-csv` are in no repository.
+            authors: self
+                .author_summaries
+                .iter()
+                .filter(|s| s.repositories > 0)
+                .count(),
+            commits: self.author_summaries.iter().map(|s| s.total_commits).sum(),
+            lines_added: self
+                .author_summaries
+                .iter()
+                .map(|s| s.total_lines_added)
+                .sum(),
+            lines_deleted: self
+                .author_summaries
+                .iter()
+                .map(|s| s.total_lines_deleted)
+                .sum(),
+        }
+    }
+
+    /// Start of the analyzed window (`--since`), if one was given.
+    pub fn window_start(&self) -> Option<i64> {
+        self.repository_info
+            .values()
+            .filter_map(|info| info.window_start)
+            .min()
+    }
+
+    /// Counts of (new, returning) authors in the analyzed window, or `None`
+    /// when no window start was given.
+    pub fn new_and_returning_counts(&self) -> Option<(usize, usize)> {
+        let window_start = self.window_start()?;
+        let new = self
+            .author_summaries
+            .iter()
+            .filter(|s| {
+                contributor_status(s.first_commit_at, window_start) == ContributorStatus::New
+            })
+            .count();
+
+        Some((new, self.author_summaries.len() - new))
+    }
+
+    /// Health of one repository as of now; see `RepositoryHealth`.
+    pub fn repository_health(&self, repo_name: &str) -> Option<RepositoryHealth> {
+        let contributions = self.contributions.get(repo_name)?;
+        repository_health(
+            contributions,
+            self.ownership_basis,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// Repositories missing a health threshold, as "<repository>: <what it
+    /// missed>"; those without line data have no health to check.
+    pub fn threshold_violations(&self, thresholds: &HealthThresholds) -> Vec<String> {
+        self.repositories
+            .iter()
+            .filter_map(|name| Some((name, self.repository_health(name)?)))
+            .flat_map(|(name, health)| {
+                thresholds
+                    .violations(&health)
+                    .into_iter()
+                    .map(move |violation| format!("{}: {}", name, violation))
+            })
+            .collect()
+    }
+
+    /// Average health score over the repositories that have one, with the
+    /// lowest-scoring repository and its score; `None` if none has a score.
+    pub fn health_overview(&self) -> Option<(u32, &str, u32)> {
+        let scores: Vec<(&str, u32)> = self
+            .repositories
+            .iter()
+            .filter_map(|name| Some((name.as_str(), self.repository_health(name)?.score)))
+            .collect();
+        let (lowest, lowest_score) = scores.iter().min_by_key(|(_, score)| *score)?;
+        let average =
+            scores.iter().map(|(_, score)| score).sum::<u32>() as f64 / scores.len() as f64;
+
+        Some((average.round() as u32, lowest, *lowest_score))
+    }
+
+    /// A copy of the results limited to one repository, with its own summary,
+    /// for writing that repository's report on its own.
+    pub fn repository_view(&self, repo_name: &str) -> App {
+        let contributions: HashMap<String, Vec<Contribution>> = self
+            .contributions
+            .get(repo_name)
+            .map(|contributions| (repo_name.to_string(), contributions.clone()))
+            .into_iter()
+            .collect();
+        let mut author_summaries = calculate_summaries(
+            &contributions,
+            self.group_by,
+            self.summary_basis,
+            self.line_weights,
+        );
+        if let (Some(score), SummaryBasis::Global) = (&self.score, self.summary_basis) {
+            apply_summary_score(&mut author_summaries, score);
+        }
+        if let Some(org_map) = &self.org_map {
+            apply_org_map(&mut author_summaries, org_map);
+        }
+
+        App {
+            repositories: vec![repo_name.to_string()],
+            // Reviews are only known across all repositories.
+            show_reviews: false,
+            author_summaries,
+            contributions,
+            repository_info: self
+                .repository_info
+                .iter()
+                .filter(|(name, _)| name.as_str() == repo_name)
+                .map(|(name, info)| (name.clone(), info.clone()))
+                .collect(),
+            repository_paths: self
+                .repository_paths
+                .iter()
+                .filter(|(name, _)| name.as_str() == repo_name)
+                .map(|(name, path)| (name.clone(), path.clone()))
+                .collect(),
+            orphaned: self
+                .orphaned
+                .iter()
+                .filter(|orphan| orphan.repository == repo_name)
+                .cloned()
+                .collect(),
+            at_risk_files: self
+                .at_risk_files
+                .iter()
+                .filter(|file| file.repository == repo_name)
+                .cloned()
+                .collect(),
+            error_log: Vec::new(),
+            repository_reports: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// A copy of the results whose reports hold only the summary, linking to
+    /// each repository's own report in `repository_reports` instead.
+    pub fn summary_view(&self, repository_reports: Vec<(String, String)>) -> App {
+        App {
+            repositories: Vec::new(),
+            repository_reports,
+            ..self.clone()
+        }
+    }
+
+    /// Group label to show before the tab at `index`: set on the first
+    /// repository of each group only.
+    pub fn group_label(&self, index: usize) -> Option<&str> {
+        let group = self.repository_groups.get(self.repositories.get(index)?)?;
+        let previous = index
+            .checked_sub(1)
+            .and_then(|previous| self.repositories.get(previous))
+            .and_then(|name| self.repository_groups.get(name));
+        (previous != Some(group)).then_some(group.as_str())
+    }
+
+    pub fn is_shallow(&self, repo_name: &str) -> bool {
+        self.repository_info
+            .get(repo_name)
+            .map(|info| info.shallow)
+            .unwrap_or(false)
+    }
+
+    /// How the repository's history was limited, for its heading: the
+    /// revision range and/or "last N commits", or `None` when it was not.
+    pub fn history_limits(&self, repo_name: &str) -> Option<String> {
+        let info = self.repository_info.get(repo_name)?;
+        let limits: Vec<String> = info
+            .revision_range
+            .clone()
+            .into_iter()
+            .chain(
+                info.last_commits
+                    .map(|last| format!("last {} commits", last)),
+            )
+            .collect();
+        (!limits.is_empty()).then(|| limits.join(", "))
+    }
+
+    /// On disk location of the repository in the current tab; `None` on the summary.
+    pub fn current_repository_path(&self) -> Option<&Path> {
+        let name = self.repositories.get(self.current_tab)?;
+        self.repository_paths.get(name).map(PathBuf::as_path)
+    }
+
+    /// Closes the detail popup if it is open, otherwise clears the current
+    /// tab's selection.
+    pub fn clear_selection(&mut self) {
+        if self.commit_list.is_some() {
+            self.commit_list = None;
+        } else if self.show_author_detail {
+            self.show_author_detail = false;
+        } else if let Some(selected) = self.selected_in_tab.get_mut(self.current_tab) {
+            *selected = None;
+        }
+    }
+
+    /// First and last commit dates as shown in the TUI, honoring `relative_dates`.
+    pub fn active_dates(&self, first: Option<i64>, last: Option<i64>) -> String {
+        let now = chrono::Utc::now().timestamp();
+        match (first, last) {
+            (Some(first), Some(last)) => format!(
+                "First commit: {} | Last commit: {}",
+                format_timestamp(first, now, self.relative_dates),
+                format_timestamp(last, now, self.relative_dates)
+            ),
+            _ => String::from("First commit: - | Last commit: -"),
+        }
+    }
+
+    /// The selected row of the current repository tab; `None` on the summary.
+    pub fn selected_contribution(&self) -> Option<&Contribution> {
+        let repo_name = self.repositories.get(self.current_tab)?;
+        let selected = self.selected_in_tab.get(self.current_tab).copied()??;
+        self.repository_rows(repo_name).get(selected).copied()
+    }
+
+    /// Opens the detail popup for the selected row. In an open popup, lists
+    /// the row's commits if they were kept, and otherwise closes it.
+    pub fn toggle_author_detail(&mut self) {
+        if self.show_author_detail {
+            let has_commits = self
+                .selected_contribution()
+                .is_some_and(|c| !c.kept_commits.is_empty());
+            if self.commit_list.is_none() && has_commits {
+                self.commit_list = Some(0);
+            } else {
+                self.show_author_detail = false;
+                self.commit_list = None;
+            }
+        } else if self
+            .selected_in_tab
+            .get(self.current_tab)
+            .copied()
+            .flatten()
+            .is_some()
+        {
+            self.show_author_detail = true;
+        }
+    }
+
+    pub fn cycle_display_metric(&mut self) {
+        self.display_metric = self.display_metric.next();
+    }
+
+    pub fn toggle_line_shares(&mut self) {
+        self.show_line_shares = !self.show_line_shares;
+    }
+
+    pub fn toggle_comparison(&mut self) {
+        if self.comparison.is_some() {
+            self.show_comparison = !self.show_comparison;
+            self.show_collaborators = false;
+            self.show_orphaned = false;
+            self.show_at_risk = false;
+            self.show_errors = false;
+        }
+    }
+
+    pub fn toggle_collaborators(&mut self) {
+        self.show_collaborators = !self.show_collaborators;
+        self.show_comparison = false;
+        self.show_orphaned = false;
+        self.show_at_risk = false;
+        self.show_errors = false;
+    }
+
+    pub fn toggle_orphaned(&mut self) {
+        self.show_orphaned = !self.show_orphaned;
+        self.show_comparison = false;
+        self.show_collaborators = false;
+        self.show_at_risk = false;
+        self.show_errors = false;
+    }
+
+    pub fn toggle_at_risk(&mut self) {
+        self.show_at_risk = !self.show_at_risk;
+        self.show_comparison = false;
+        self.show_collaborators = false;
+        self.show_orphaned = false;
+        self.show_errors = false;
+    }
+
+    pub fn toggle_errors(&mut self) {
+        self.show_errors = !self.show_errors;
+        self.show_comparison = false;
+        self.show_collaborators = false;
+        self.show_orphaned = false;
+        self.show_at_risk = false;
+    }
+
+    /// Name shown for a tab in the picker: a repository or the summary.
+    pub fn tab_name(&self, tab: usize) -> &str {
+        self.repositories
+            .get(tab)
+            .map(String::as_str)
+            .unwrap_or("Summary")
+    }
+
+    pub fn open_picker(&mut self) {
+        self.picker = Some(RepoPicker::default());
+        self.update_picker_matches();
+    }
+
+    pub fn close_picker(&mut self) {
+        self.picker = None;
+    }
+
+    pub fn picker_push(&mut self, c: char) {
+        if let Some(picker) = &mut self.picker {
+            picker.query.push(c);
+        }
+        self.update_picker_matches();
+    }
+
+    pub fn picker_pop(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            picker.query.pop();
+        }
+        self.update_picker_matches();
+    }
+
+    pub fn picker_next(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            if !picker.matches.is_empty() {
+                picker.selected = (picker.selected + 1) % picker.matches.len();
+            }
+        }
+    }
+
+    pub fn picker_previous(&mut self) {
+        if let Some(picker) = &mut self.picker {
+            if !picker.matches.is_empty() {
+                picker.selected =
+                    (picker.selected + picker.matches.len() - 1) % picker.matches.len();
+            }
+        }
+    }
+
+    /// Jumps to the highlighted tab and closes the picker.
+    pub fn picker_confirm(&mut self) {
+        if let Some(picker) = self.picker.take() {
+            if let Some(&tab) = picker.matches.get(picker.selected) {
+                self.switch_tab(tab);
+            }
+        }
+    }
+
+    fn update_picker_matches(&mut self) {
+        let query = match &self.picker {
+            Some(picker) => picker.query.clone(),
+            None => return,
+        };
+
+        let mut scored: Vec<(i64, usize)> = (0..=self.repositories.len())
+            .filter_map(|tab| fuzzy_score(self.tab_name(tab), &query).map(|score| (score, tab)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        if let Some(picker) = &mut self.picker {
+            picker.matches = scored.into_iter().map(|(_, tab)| tab).collect();
+            picker.selected = 0;
+        }
+    }
+
+    pub fn open_export_menu(&mut self) {
+        self.export_menu = Some(0);
+    }
+
+    pub fn close_export_menu(&mut self) {
+        self.export_menu = None;
+    }
+
+    pub fn export_menu_next(&mut self) {
+        if let Some(selected) = &mut self.export_menu {
+            *selected = (*selected + 1) % ExportFormat::ALL.len();
+        }
+    }
+
+    pub fn export_menu_previous(&mut self) {
+        if let Some(selected) = &mut self.export_menu {
+            *selected = (*selected + ExportFormat::ALL.len() - 1) % ExportFormat::ALL.len();
+        }
+    }
+
+    /// Closes the export menu, returning the format that was highlighted.
+    pub fn export_menu_confirm(&mut self) -> Option<ExportFormat> {
+        self.export_menu
+            .take()
+            .and_then(|selected| ExportFormat::ALL.get(selected).copied())
+    }
+
+    pub fn open_command_line(&mut self) {
+        self.command_line = Some(String::new());
+    }
+
+    pub fn close_command_line(&mut self) {
+        self.command_line = None;
+    }
+
+    pub fn command_line_push(&mut self, c: char) {
+        if let Some(command_line) = &mut self.command_line {
+            command_line.push(c);
+        }
+    }
+
+    /// Deletes the last character, or closes the bar when it is already
+    /// empty, as in vim.
+    pub fn command_line_pop(&mut self) {
+        match &mut self.command_line {
+            Some(command_line) if !command_line.is_empty() => {
+                command_line.pop();
+            }
+            _ => self.command_line = None,
+        }
+    }
+
+    /// Closes the command bar and runs what was typed; a mistake is shown in
+    /// the footer.
+    pub fn command_line_submit(&mut self) {
+        if let Some(command) = self.command_line.take() {
+            if let Err(message) = self.run_command(&command) {
+                self.status_message = Some(message);
+            }
+        }
+    }
+
+    /// Runs a command bar command:
+    ///
+    /// - `sort <column> [asc|desc]`, or `sort` alone for rank order again;
+    ///   applies to every tab
+    /// - `filter <text>`, or `filter` alone to show every row; applies to the
+    ///   current tab only
+    ///
+    /// Commands and columns may be shortened to any unambiguous prefix.
+    pub fn run_command(&mut self, command: &str) -> Result<(), String> {
+        let command = command.trim();
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+        let argument = argument.trim();
+
+        match name {
+            "" => return Ok(()),
+            _ if "sort".starts_with(name) => {
+                let mut words = argument.split_whitespace();
+                let column = match words.next() {
+                    Some(column) => SortColumn::parse(column)?,
+                    None => SortColumn::Rank,
+                };
+                let descending = match words.next() {
+                    None => column.descending_by_default(),
+                    Some(direction) if "ascending".starts_with(direction) => false,
+                    Some(direction) if "descending".starts_with(direction) => true,
+                    Some(direction) => {
+                        return Err(format!(
+                            "Unknown sort direction '{}', expected asc or desc",
+                            direction
+                        ))
+                    }
+                };
+                if let Some(extra) = words.next() {
+                    return Err(format!("Unexpected '{}' after the sort direction", extra));
+                }
+                self.sort_column = column;
+                self.sort_descending = descending;
+
+                // Rows moved, so a kept selection would point at someone else.
+                for selected in &mut self.selected_in_tab {
+                    *selected = None;
+                }
+            }
+            _ if "filter".starts_with(name) => {
+                if let Some(filter) = self.filters.get_mut(self.current_tab) {
+                    *filter = argument.to_string();
+                }
+                if let Some(selected) = self.selected_in_tab.get_mut(self.current_tab) {
+                    *selected = None;
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown command '{}', expected sort <column> [asc|desc] or filter <text>",
+                    name
+                ))
+            }
+        }
+
+        self.show_author_detail = false;
+        Ok(())
     }
 
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
+
+    pub fn toggle_compact(&mut self) {
+        self.compact = !self.compact;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tab: usize) -> NavigationEntry {
+        NavigationEntry {
+            tab,
+            selected: None,
+        }
+    }
+
+    #[test]
+    fn going_back_on_an_empty_history_returns_nothing() {
+        let mut history = NavigationHistory::default();
+        assert_eq!(history.back(entry(0)), None);
+        assert_eq!(history.forward(entry(0)), None);
+    }
+
+    #[test]
+    fn moving_on_after_going_back_drops_the_forward_tabs() {
+        let mut history = NavigationHistory::default();
+        history.record(entry(0));
+        history.record(entry(1));
+        // Now on tab 2.
+        assert_eq!(history.back(entry(2)), Some(entry(1)));
+        assert_eq!(history.forward(entry(1)), Some(entry(2)));
+        assert_eq!(history.back(entry(2)), Some(entry(1)));
+
+        history.record(entry(1));
+        // Now on tab 3; tab 2 can no longer be gone forward to.
+        assert_eq!(history.forward(entry(3)), None);
+        assert_eq!(history.back(entry(3)), Some(entry(1)));
+        assert_eq!(history.back(entry(1)), Some(entry(0)));
+        assert_eq!(history.back(entry(0)), None);
+    }
+
+    #[test]
+    fn history_forgets_the_oldest_tabs_past_the_limit() {
+        let mut history = NavigationHistory::default();
+        for tab in 0..NAVIGATION_HISTORY_LIMIT + 5 {
+            history.record(entry(tab));
+        }
+        let mut current = entry(usize::MAX);
+        let mut steps = 0;
+        while let Some(previous) = history.back(current) {
+            current = previous;
+            steps += 1;
+        }
+        assert_eq!(steps, NAVIGATION_HISTORY_LIMIT);
+        assert_eq!(current, entry(5));
+    }
+
+    #[test]
+    fn moving_through_an_empty_summary_does_not_underflow() {
+        let mut app = App::new();
+        app.selected_in_tab = vec![Some(0)];
+        app.next();
+        assert_eq!(app.selected_in_tab[0], Some(0));
+        app.previous();
+        assert_eq!(app.selected_in_tab[0], Some(0));
+    }
+
+    #[test]
+    fn loading_progress_is_clamped_and_never_goes_back() {
+        let mut app = App::new();
+        assert_eq!(app.loading_progress, None);
+
+        app.set_loading_progress(250);
+        assert_eq!(app.loading_progress, Some(100));
+
+        let mut app = App::new();
+        app.set_loading_progress(90);
+        app.set_loading_progress(10);
+        assert_eq!(app.loading_progress, Some(90));
+    }
+
+    #[test]
+    fn progress_events_move_the_bar_forward_until_done() {
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Found(4));
+        assert_eq!(app.loading_progress, Some(0));
+
+        app.apply_progress(ProgressEvent::Analyzing {
+            index: 3,
+            total: 4,
+            name: "web".to_string(),
+        });
+        assert_eq!(app.loading_progress, Some(75));
+
+        // A late event for an earlier repository does not shrink the bar.
+        app.apply_progress(ProgressEvent::Analyzing {
+            index: 1,
+            total: 4,
+            name: "api".to_string(),
+        });
+        assert_eq!(app.loading_progress, Some(75));
+
+        let tick = app.loading_tick;
+        app.tick();
+        assert_eq!(app.loading_tick, tick.wrapping_add(1));
+        assert_eq!(app.loading_progress, Some(75));
+
+        app.apply_progress(ProgressEvent::Done(Box::default()));
+        assert!(app.state == AppState::Main);
+        assert_eq!(app.loading_progress, Some(100));
+
+        // Ticks after loading finished change nothing.
+        let tick = app.loading_tick;
+        app.tick();
+        assert_eq!(app.loading_tick, tick);
+        assert_eq!(app.loading_progress, Some(100));
+    }
+
+    #[test]
+    fn first_commits_at_the_window_start_are_new() {
+        let start = 1_700_000_000;
+        assert_eq!(
+            contributor_status(Some(start), start),
+            ContributorStatus::New
+        );
+        assert_eq!(
+            contributor_status(Some(start + 1), start),
+            ContributorStatus::New
+        );
+        assert_eq!(
+            contributor_status(Some(start - 1), start),
+            ContributorStatus::Returning
+        );
+    }
+
+    #[test]
+    fn authors_without_a_known_first_commit_are_new() {
+        assert_eq!(contributor_status(None, 0), ContributorStatus::New);
+    }
+
+    #[test]
+    fn new_and_returning_authors_are_counted_from_the_earliest_window() {
+        let start = 1_700_000_000;
+        let mut app = App::new();
+        assert_eq!(app.new_and_returning_counts(), None);
+
+        for (name, window_start) in [("api", start), ("web", start + 100)] {
+            app.repository_info.insert(
+                name.to_string(),
+                RepositoryInfo {
+                    window_start: Some(window_start),
+                    ..Default::default()
+                },
+            );
+        }
+        app.author_summaries = [Some(start - 1), Some(start), Some(start + 50)]
+            .into_iter()
+            .map(|first_commit_at| AuthorSummary {
+                first_commit_at,
+                ..Default::default()
+            })
+            .collect();
+
+        assert_eq!(app.new_and_returning_counts(), Some((2, 1)));
+    }
+
+    fn contribution(author: &str, email: &str, lines: u32) -> Contribution {
+        Contribution {
+            author: author.to_string(),
+            email: email.to_string(),
+            commits: 1,
+            lines_added: lines,
+            ..Default::default()
+        }
+    }
+
+    /// A loaded app with three repositories: Jane commits to all of them, Bob
+    /// to `api` and `web`, and Mia only to `docs`.
+    fn loaded_app() -> App {
+        let contributions = HashMap::from([
+            (
+                "api".to_string(),
+                vec![
+                    contribution("Jane", "jane@acme.com", 10),
+                    contribution("Bob", "bob@acme.com", 5),
+                ],
+            ),
+            (
+                "docs".to_string(),
+                vec![
+                    contribution("Jane", "jane@acme.com", 1),
+                    contribution("Mia", "mia@int.io", 4),
+                ],
+            ),
+            (
+                "web".to_string(),
+                vec![
+                    contribution("Jane", "jane@acme.com", 2),
+                    contribution("Bob", "bob@acme.com", 3),
+                ],
+            ),
+        ]);
+        let author_summaries = calculate_summaries(
+            &contributions,
+            GroupBy::Author,
+            SummaryBasis::Global,
+            LineWeights::default(),
+        );
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            repositories: vec!["api".to_string(), "docs".to_string(), "web".to_string()],
+            contributions,
+            author_summaries,
+            ..Default::default()
+        })));
+        app
+    }
+
+    fn emails<T: TableRow>(rows: &[&T]) -> Vec<String> {
+        let mut emails: Vec<String> = rows.iter().map(|row| row.email().to_string()).collect();
+        emails.sort();
+        emails
+    }
+
+    #[test]
+    fn ubiquitous_only_keeps_authors_in_every_repository() {
+        let mut app = loaded_app();
+        assert_eq!(app.summary_rows().len(), 3);
+        assert_eq!(app.summary_filter_note(), "");
+
+        app.current_tab = 3;
+        app.selected_in_tab[3] = Some(2);
+        app.toggle_ubiquitous_only();
+        assert_eq!(emails(&app.summary_rows()), ["jane@acme.com"]);
+        assert_eq!(app.summary_filter_note(), " (in every repository)");
+        // The old selection would point past the remaining rows.
+        assert_eq!(app.selected_in_tab[3], None);
+
+        app.toggle_ubiquitous_only();
+        assert_eq!(app.summary_rows().len(), 3);
+    }
+
+    #[test]
+    fn ubiquitous_only_leaves_repository_tabs_alone() {
+        let mut app = loaded_app();
+        app.toggle_ubiquitous_only();
+        assert_eq!(app.repository_rows("api").len(), 2);
+    }
+
+    #[test]
+    fn filters_apply_to_the_current_tab_only() {
+        let mut app = loaded_app();
+        app.run_command("filter bob").unwrap();
+        assert_eq!(emails(&app.repository_rows("api")), ["bob@acme.com"]);
+        assert_eq!(app.repository_rows("web").len(), 2);
+        assert_eq!(app.summary_rows().len(), 3);
+
+        app.next_tab();
+        app.next_tab();
+        app.run_command("f JANE").unwrap();
+        assert_eq!(emails(&app.repository_rows("web")), ["jane@acme.com"]);
+        assert_eq!(app.sort_filter_status(), "sort: rank ↑ | filter: JANE");
+        assert!(app.is_sorted_or_filtered());
+
+        // Each tab keeps its own filter across switches.
+        app.previous_tab();
+        app.previous_tab();
+        assert_eq!(app.tab_filter(app.current_tab), "bob");
+        assert_eq!(emails(&app.repository_rows("api")), ["bob@acme.com"]);
+
+        app.run_command("filter").unwrap();
+        assert_eq!(app.repository_rows("api").len(), 2);
+        assert!(!app.is_sorted_or_filtered());
+        assert_eq!(app.tab_filter(2), "JANE");
+    }
+
+    #[test]
+    fn filtering_clears_only_the_current_tabs_selection() {
+        let mut app = loaded_app();
+        app.selected_in_tab = vec![Some(1), Some(1), Some(1), Some(1)];
+        app.run_command("filter mia").unwrap();
+        assert_eq!(app.selected_in_tab, [None, Some(1), Some(1), Some(1)]);
+        assert!(app.repository_rows("api").is_empty());
+    }
+
+    #[test]
+    fn summary_filters_match_names_and_emails() {
+        let mut app = loaded_app();
+        app.current_tab = 3;
+        app.run_command("filter acme").unwrap();
+        assert_eq!(
+            emails(&app.summary_rows()),
+            ["bob@acme.com", "jane@acme.com"]
+        );
+        app.run_command("filter Mia").unwrap();
+        assert_eq!(emails(&app.summary_rows()), ["mia@int.io"]);
+    }
+
+    #[test]
+    fn clearing_closes_popups_before_the_selection() {
+        let mut app = loaded_app();
+        app.selected_in_tab = vec![Some(1), Some(0), None, Some(2)];
+        app.show_author_detail = true;
+        app.commit_list = Some(0);
+
+        app.clear_selection();
+        assert_eq!(app.commit_list, None);
+        assert!(app.show_author_detail);
+
+        app.clear_selection();
+        assert!(!app.show_author_detail);
+        assert_eq!(app.selected_in_tab[0], Some(1));
+
+        app.clear_selection();
+        assert_eq!(app.selected_in_tab, [None, Some(0), None, Some(2)]);
+
+        // Nothing left to clear.
+        app.clear_selection();
+        assert_eq!(app.selected_in_tab, [None, Some(0), None, Some(2)]);
+    }
+
+    #[test]
+    fn selections_persist_across_tab_switches() {
+        let mut app = loaded_app();
+        app.next();
+        app.next_tab();
+        assert_eq!(app.selected_in_tab[1], None);
+        app.next();
+        app.previous_tab();
+        assert_eq!(app.selected_in_tab[..2], [Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn history_limits_name_the_range_and_commit_limit() {
+        let mut app = loaded_app();
+        assert_eq!(app.history_limits("api"), None);
+        assert_eq!(app.history_limits("missing"), None);
+
+        app.repository_info.insert(
+            "api".to_string(),
+            RepositoryInfo {
+                last_commits: Some(50),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            app.history_limits("api").as_deref(),
+            Some("last 50 commits")
+        );
+
+        app.repository_info.get_mut("api").unwrap().revision_range = Some("v1.0..v2.0".to_string());
+        assert_eq!(
+            app.history_limits("api").as_deref(),
+            Some("v1.0..v2.0, last 50 commits")
+        );
+    }
 }
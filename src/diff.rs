@@ -0,0 +1,418 @@
+use crate::export::JSON_SCHEMA_VERSION;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// How `--diff` prints the changes between two JSON reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DiffFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// The parts of a JSON report (`e` then JSON, or `--split-format json`) a
+/// diff compares.
+#[derive(Debug, Deserialize)]
+struct ReportFile {
+    schema_version: u32,
+    generated_at: String,
+    summaries: Vec<SummaryRow>,
+    repositories: Vec<RepositoryRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryRow {
+    author: String,
+    email: String,
+    total_commits: u32,
+    total_lines_added: u32,
+    total_lines_deleted: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryRow {
+    name: String,
+    contributions: Vec<ContributionRow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContributionRow {
+    author: String,
+    email: String,
+    commits: u32,
+    lines_added: u32,
+    lines_deleted: u32,
+}
+
+/// One author's figures in one report, keyed by normalized email.
+#[derive(Debug, Clone, Copy, Default)]
+struct Figures {
+    commits: i64,
+    lines_added: i64,
+    lines_deleted: i64,
+}
+
+impl Figures {
+    fn lines(self) -> i64 {
+        self.lines_added + self.lines_deleted
+    }
+
+    fn minus(self, other: Figures) -> Figures {
+        Figures {
+            commits: self.commits - other.commits,
+            lines_added: self.lines_added - other.lines_added,
+            lines_deleted: self.lines_deleted - other.lines_deleted,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.commits == 0 && self.lines_added == 0 && self.lines_deleted == 0
+    }
+
+    /// "+12 commits, +340 lines (+300 added, +40 deleted)", signed since it
+    /// is a change.
+    fn describe_change(self) -> String {
+        format!(
+            "{:+} commit{}, {:+} line{} ({:+} added, {:+} deleted)",
+            self.commits,
+            if self.commits.abs() == 1 { "" } else { "s" },
+            self.lines(),
+            if self.lines().abs() == 1 { "" } else { "s" },
+            self.lines_added,
+            self.lines_deleted
+        )
+    }
+
+    /// "3 commits, 20 lines (+18 / -2)", for authors in one report only.
+    fn describe_totals(self) -> String {
+        format!(
+            "{} commit{}, {} line{} (+{} / -{})",
+            self.commits,
+            if self.commits == 1 { "" } else { "s" },
+            self.lines(),
+            if self.lines() == 1 { "" } else { "s" },
+            self.lines_added,
+            self.lines_deleted
+        )
+    }
+}
+
+/// Authors of one table: display name and figures per normalized email.
+type AuthorTable = HashMap<String, (String, Figures)>;
+
+fn author_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn add_author(table: &mut AuthorTable, author: &str, email: &str, figures: Figures) {
+    let entry = table
+        .entry(author_key(email))
+        .or_insert_with(|| (format!("{} <{}>", author, email.trim()), Figures::default()));
+    entry.1.commits += figures.commits;
+    entry.1.lines_added += figures.lines_added;
+    entry.1.lines_deleted += figures.lines_deleted;
+}
+
+fn read_report(path: &Path) -> Result<ReportFile, Box<dyn Error + Send>> {
+    let invalid =
+        |message: String| Box::new(std::io::Error::other(message)) as Box<dyn Error + Send>;
+    let json = fs::read_to_string(path)
+        .map_err(|e| invalid(format!("Failed to read report {}: {}", path.display(), e)))?;
+    let report: ReportFile = serde_json::from_str(&json).map_err(|e| {
+        invalid(format!(
+            "{} is not a JSON report of this tool: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if report.schema_version != JSON_SCHEMA_VERSION {
+        return Err(invalid(format!(
+            "{} has schema version {}, expected {}",
+            path.display(),
+            report.schema_version,
+            JSON_SCHEMA_VERSION
+        )));
+    }
+    Ok(report)
+}
+
+/// A titled list of changelog lines.
+struct Section {
+    title: String,
+    lines: Vec<String>,
+}
+
+/// Changed, new and departed authors between two tables, biggest change
+/// first, as sections titled after `scope`. Unchanged authors are left out.
+fn table_sections(scope: &str, old: &AuthorTable, new: &AuthorTable) -> Vec<Section> {
+    let mut changed: Vec<(&String, Figures)> = Vec::new();
+    let mut added: Vec<(&String, Figures)> = Vec::new();
+    let mut departed: Vec<(&String, Figures)> = Vec::new();
+    for (key, (name, figures)) in new {
+        match old.get(key) {
+            Some((_, before)) if !figures.minus(*before).is_zero() => {
+                changed.push((name, figures.minus(*before)))
+            }
+            Some(_) => {}
+            None => added.push((name, *figures)),
+        }
+    }
+    for (key, (name, figures)) in old {
+        if !new.contains_key(key) {
+            departed.push((name, *figures));
+        }
+    }
+    for rows in [&mut changed, &mut added, &mut departed] {
+        rows.sort_by_key(|(name, figures)| (Reverse(figures.lines().abs()), name.to_string()));
+    }
+
+    let lines_of = |rows: Vec<(&String, Figures)>, describe: fn(Figures) -> String| {
+        rows.into_iter()
+            .map(|(name, figures)| format!("{}: {}", name, describe(figures)))
+            .collect::<Vec<_>>()
+    };
+    [
+        (
+            scope.to_string(),
+            lines_of(changed, Figures::describe_change),
+        ),
+        (
+            format!("{}: new authors", scope),
+            lines_of(added, Figures::describe_totals),
+        ),
+        (
+            format!("{}: no longer present", scope),
+            lines_of(departed, Figures::describe_totals),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, lines)| !lines.is_empty())
+    .map(|(title, lines)| Section { title, lines })
+    .collect()
+}
+
+/// Compares two JSON reports author by author, matched by email ignoring
+/// case, for the whole summary and for every repository, and describes what
+/// changed as text or Markdown.
+pub fn diff_reports(
+    old_path: &Path,
+    new_path: &Path,
+    format: DiffFormat,
+) -> Result<String, Box<dyn Error + Send>> {
+    let old = read_report(old_path)?;
+    let new = read_report(new_path)?;
+
+    let summary_table = |report: &ReportFile| {
+        let mut table = AuthorTable::new();
+        for row in &report.summaries {
+            let figures = Figures {
+                commits: row.total_commits as i64,
+                lines_added: row.total_lines_added as i64,
+                lines_deleted: row.total_lines_deleted as i64,
+            };
+            add_author(&mut table, &row.author, &row.email, figures);
+        }
+        table
+    };
+    let repository_tables = |report: &ReportFile| {
+        let mut tables: HashMap<String, AuthorTable> = HashMap::new();
+        for repository in &report.repositories {
+            let table = tables.entry(repository.name.clone()).or_default();
+            for contrib in &repository.contributions {
+                let figures = Figures {
+                    commits: contrib.commits as i64,
+                    lines_added: contrib.lines_added as i64,
+                    lines_deleted: contrib.lines_deleted as i64,
+                };
+                add_author(table, &contrib.author, &contrib.email, figures);
+            }
+        }
+        tables
+    };
+
+    let mut sections = table_sections("Summary", &summary_table(&old), &summary_table(&new));
+    let (old_repositories, new_repositories) = (repository_tables(&old), repository_tables(&new));
+    let names: BTreeSet<&String> = old_repositories
+        .keys()
+        .chain(new_repositories.keys())
+        .collect();
+    let empty = AuthorTable::new();
+    for name in names {
+        let scope = match (
+            old_repositories.contains_key(name),
+            new_repositories.contains_key(name),
+        ) {
+            (false, _) => format!("Repository: {} (new)", name),
+            (_, false) => format!("Repository: {} (removed)", name),
+            _ => format!("Repository: {}", name),
+        };
+        sections.extend(table_sections(
+            &scope,
+            old_repositories.get(name).unwrap_or(&empty),
+            new_repositories.get(name).unwrap_or(&empty),
+        ));
+    }
+
+    let mut output = match format {
+        DiffFormat::Text => format!(
+            "Contribution changes from {} to {}\n",
+            old.generated_at, new.generated_at
+        ),
+        DiffFormat::Markdown => format!(
+            "# Contribution Changes\n\n_From {} to {}_\n",
+            old.generated_at, new.generated_at
+        ),
+    };
+    if sections.is_empty() {
+        output.push_str("\nNo contribution changes.\n");
+    }
+    for section in sections {
+        match format {
+            DiffFormat::Text => {
+                output.push_str(&format!("\n{}\n", section.title));
+                for line in section.lines {
+                    output.push_str(&format!("  {}\n", line));
+                }
+            }
+            DiffFormat::Markdown => {
+                output.push_str(&format!("\n## {}\n\n", section.title));
+                for line in section.lines {
+                    output.push_str(&format!("- {}\n", line));
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A minimal JSON report with one repository, `api`, whose rows are
+    /// (author, email, commits, lines added, lines deleted); the summary
+    /// repeats them.
+    fn write_report(
+        name: &str,
+        generated_at: &str,
+        rows: &[(&str, &str, u32, u32, u32)],
+    ) -> PathBuf {
+        let summaries: Vec<_> = rows
+            .iter()
+            .map(|(author, email, commits, added, deleted)| {
+                serde_json::json!({
+                    "author": author,
+                    "email": email,
+                    "total_commits": commits,
+                    "total_lines_added": added,
+                    "total_lines_deleted": deleted,
+                })
+            })
+            .collect();
+        let contributions: Vec<_> = rows
+            .iter()
+            .map(|(author, email, commits, added, deleted)| {
+                serde_json::json!({
+                    "author": author,
+                    "email": email,
+                    "commits": commits,
+                    "lines_added": added,
+                    "lines_deleted": deleted,
+                })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "generated_at": generated_at,
+            "summaries": summaries,
+            "repositories": [{ "name": "api", "contributions": contributions }],
+        });
+
+        let dir = std::env::temp_dir().join(format!("gca-diff-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, report.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn describes_changed_new_and_departed_authors() {
+        let old = write_report(
+            "old.json",
+            "2024-01-01",
+            &[
+                ("Jane", "jane@acme.com", 3, 10, 2),
+                ("Bob", "bob@other.org", 1, 5, 0),
+            ],
+        );
+        let new = write_report(
+            "new.json",
+            "2024-02-01",
+            &[
+                ("Jane", "Jane@Acme.com", 4, 12, 5),
+                ("Mia", "mia@int.io", 2, 7, 1),
+            ],
+        );
+
+        assert_eq!(
+            diff_reports(&old, &new, DiffFormat::Text).unwrap(),
+            "Contribution changes from 2024-01-01 to 2024-02-01
+
+Summary
+  Jane <Jane@Acme.com>: +1 commit, +5 lines (+2 added, +3 deleted)
+
+Summary: new authors
+  Mia <mia@int.io>: 2 commits, 8 lines (+7 / -1)
+
+Summary: no longer present
+  Bob <bob@other.org>: 1 commit, 5 lines (+5 / -0)
+
+Repository: api
+  Jane <Jane@Acme.com>: +1 commit, +5 lines (+2 added, +3 deleted)
+
+Repository: api: new authors
+  Mia <mia@int.io>: 2 commits, 8 lines (+7 / -1)
+
+Repository: api: no longer present
+  Bob <bob@other.org>: 1 commit, 5 lines (+5 / -0)
+"
+        );
+    }
+
+    #[test]
+    fn identical_reports_have_no_changes() {
+        let rows = [("Jane", "jane@acme.com", 3, 10, 2)];
+        let old = write_report("same-old.json", "2024-01-01", &rows);
+        let new = write_report("same-new.json", "2024-02-01", &rows);
+
+        assert_eq!(
+            diff_reports(&old, &new, DiffFormat::Markdown).unwrap(),
+            "# Contribution Changes\n\n_From 2024-01-01 to 2024-02-01_\n\nNo contribution changes.\n"
+        );
+    }
+
+    #[test]
+    fn other_schema_versions_are_rejected() {
+        let path =
+            std::env::temp_dir().join(format!("gca-diff-test-{}-v0.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"schema_version": 0, "generated_at": "", "summaries": [], "repositories": []}"#,
+        )
+        .unwrap();
+
+        let error = diff_reports(&path, &path, DiffFormat::Text)
+            .unwrap_err()
+            .to_string();
+        assert!(error.ends_with(&format!(
+            "has schema version 0, expected {}",
+            JSON_SCHEMA_VERSION
+        )));
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,5 +1,37 @@
-use crate::app::App;
-use std::{error::Error, fs, path::Path};
+use crate::{
+    app::{App, AuthorSummary, CoverageStats, OverviewStats},
+    git::{
+        directory_owners, format_percent, has_line_data, language_footprint, AtRiskFile,
+        AuthorMerge, Contribution, LanguageFootprint, OrphanedDirectory, OwnershipBasis,
+        RepositoryHealth, RepositoryInfo, NO_LINE_DATA_MESSAGE,
+    },
+};
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// `value` with the characters HTML gives meaning to replaced by entities,
+/// safe inside text and quoted attribute values.
+fn html_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
 
 pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
     let mut html = String::from(
@@ -34,9 +66,23 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
             background-color: #f2f2f2;
             font-weight: bold;
         }
+        .repo-group > summary {
+            font-size: 1.3em;
+            font-weight: bold;
+            color: #2c3e50;
+            cursor: pointer;
+            margin: 20px 0 10px;
+        }
+        tr:nth-child(even) {
+            background-color: #fafafa;
+        }
         tr:hover {
             background-color: #f5f5f5;
         }
+        .coverage {
+            color: #7f8c8d;
+            text-align: center;
+        }
         .report-date {
             color: #7f8c8d;
             font-style: italic;
@@ -52,6 +98,63 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
             padding: 20px;
             border-radius: 5px;
         }
+        .overview {
+            display: flex;
+            gap: 40px;
+            margin-bottom: 30px;
+        }
+        .overview div {
+            font-size: 0.9em;
+            color: #7f8c8d;
+        }
+        .overview strong {
+            display: block;
+            font-size: 1.6em;
+            color: #2c3e50;
+        }
+        .calendar {
+            margin: 10px 0 20px;
+        }
+        .calendar-year {
+            font-size: 0.9em;
+            color: #7f8c8d;
+        }
+        .calendar svg text {
+            font-size: 9px;
+            fill: #7f8c8d;
+        }
+        .calendar-legend {
+            font-size: 0.8em;
+            color: #7f8c8d;
+        }
+        .calendar-legend span {
+            display: inline-block;
+            width: 10px;
+            height: 10px;
+            margin: 0 1px;
+            border-radius: 2px;
+        }
+        .timeline svg text {
+            font-size: 10px;
+            fill: #7f8c8d;
+        }
+        .timeline-legend span {
+            display: inline-block;
+            margin-right: 12px;
+            font-size: 0.8em;
+        }
+        .footprint-bar {
+            height: 10px;
+            min-width: 1px;
+            background-color: #3498db;
+            border-radius: 2px;
+        }
+        .warning {
+            color: #b9770e;
+            background-color: #fef5e7;
+            padding: 8px 12px;
+            border-radius: 3px;
+        }
     </style>
 </head>
 <body>
@@ -64,72 +167,226 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
     use chrono::Local;
     html.push_str(&Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
 
-    html.push_str(
+    let overview = app.overview();
+    html.push_str(&format!(
         r#"</p>
+
+        <div class="overview">
+            <div><strong>{}</strong>Repositories</div>
+            <div><strong>{}</strong>Authors</div>
+            <div><strong>{}</strong>Commits</div>
+            <div><strong>{}</strong>Line churn (+{} / -{})</div>
+        </div>
+        <p class="coverage">{}</p>"#,
+        overview.repositories,
+        overview.authors,
+        overview.commits,
+        overview.line_churn(),
+        overview.lines_added,
+        overview.lines_deleted,
+        app.coverage.summary()
+    ));
+
+    let merges_header = if app.show_merges {
+        r#"
+                        <th>Merges</th>"#
+    } else {
+        ""
+    };
+    let merges_cell = |merges: u32| {
+        if app.show_merges {
+            format!(
+                r#"
+                        <td>{}</td>"#,
+                merges
+            )
+        } else {
+            String::new()
+        }
+    };
+    let org_header = if app.org_map.is_some() {
+        r#"
+                        <th>Org</th>"#
+    } else {
+        ""
+    };
+    let org_cell = |organization: &Option<String>| {
+        if app.org_map.is_some() {
+            format!(
+                r#"
+                        <td>{}</td>"#,
+                html_escape(organization.as_deref().unwrap_or_default())
+            )
+        } else {
+            String::new()
+        }
+    };
+    let reviews_header = if app.show_reviews {
+        r#"
+                        <th>Reviews</th>"#
+    } else {
+        ""
+    };
+    let reviews_cell = |reviews: u32| {
+        if app.show_reviews {
+            format!(
+                r#"
+                        <td>{}</td>"#,
+                reviews
+            )
+        } else {
+            String::new()
+        }
+    };
+
+    html.push_str(&format!(
+        r#"
         
         <div class="repo-section">
-            <h2>Summary Across All Repositories</h2>
+            <h2>{}{}</h2>
             <table>
                 <thead>
                     <tr>
-                        <th>Author</th>
-                        <th>Email</th>
-                        <th>Total Commits</th>
+                        <th>Rank</th>
+                        <th>{}</th>
+                        <th>Email</th>{}
+                        <th>Total Commits</th>{}
                         <th>Lines Added</th>
                         <th>Lines Deleted</th>
                         <th>Overall %</th>
                         <th>Preferred Repo</th>
-                        <th>Preferred %</th>
+                        <th>Preferred %</th>{}
                     </tr>
                 </thead>
                 <tbody>
 "#,
-    );
+        app.group_by.summary_title(),
+        app.summary_basis.title_note(),
+        app.group_by.name_header(),
+        org_header,
+        merges_header,
+        reviews_header
+    ));
 
     for summary in &app.author_summaries {
         html.push_str(&format!(
             r#"
                     <tr>
+                        <td>#{}</td>
                         <td>{}</td>
+                        <td>{}</td>{}
+                        <td>{}</td>{}
                         <td>{}</td>
                         <td>{}</td>
                         <td>{}</td>
                         <td>{}</td>
-                        <td>{:.2}%</td>
-                        <td>{}</td>
-                        <td>{:.2}%</td>
+                        <td>{}</td>{}
                     </tr>
 "#,
-            summary.author,
-            summary.email,
+            summary.rank,
+            html_escape(&summary.author),
+            html_escape(&summary.email),
+            org_cell(&summary.organization),
             summary.total_commits,
+            merges_cell(summary.total_merges),
             summary.total_lines_added,
             summary.total_lines_deleted,
-            summary.overall_contribution_percent,
-            summary.preferred_repo,
-            summary.preferred_repo_percent
+            format_percent(summary.overall_contribution_percent, app.percent_precision),
+            html_escape(&summary.preferred_repo),
+            format_percent(summary.preferred_repo_percent, app.percent_precision),
+            reviews_cell(summary.reviews)
         ));
     }
 
-    html.push_str(
+    let mut all_days = BTreeMap::new();
+    for contributions in app
+        .repositories
+        .iter()
+        .filter_map(|repo_name| app.contributions.get(repo_name))
+    {
+        add_commit_days(&mut all_days, contributions);
+    }
+    html.push_str(&format!(
         r#"
                 </tbody>
-            </table>
+            </table>{}{}
         </div>
 "#,
-    );
+        activity_calendar_html(&all_days, app.calendar_years),
+        contribution_timeline_html(app)
+    ));
+    html.push_str(&language_footprint_html(app));
+
+    if !app.repository_reports.is_empty() {
+        html.push_str(
+            r#"
+        <div class="repo-section">
+            <h2>Repositories</h2>
+            <ul>"#,
+        );
+        for (repo_name, file_name) in &app.repository_reports {
+            html.push_str(&format!(
+                r#"
+                <li><a href="{}">{}</a></li>"#,
+                html_escape(file_name),
+                html_escape(repo_name)
+            ));
+        }
+        html.push_str(
+            r#"
+            </ul>
+        </div>
+"#,
+        );
+    }
+
+    let mut group_open = false;
+    for (index, repo_name) in app.repositories.iter().enumerate() {
+        if let Some(group) = app.group_label(index) {
+            if group_open {
+                html.push_str("        </details>\n");
+            }
+            html.push_str(&format!(
+                r#"
+        <details class="repo-group" open>
+            <summary>{}</summary>
+"#,
+                html_escape(group)
+            ));
+            group_open = true;
+        }
+        let heading = match app.history_limits(repo_name) {
+            Some(range) => format!("{} ({})", repo_name, range),
+            None => repo_name.to_string(),
+        };
+        let mut notes = String::new();
+        if app.is_shallow(repo_name) {
+            notes.push_str(
+                r#"
+            <p class="warning">&#9888; Shallow clone: history is incomplete, so these numbers undercount.</p>"#,
+            );
+        }
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            if !has_line_data(contributions) {
+                notes.push_str(&format!(
+                    r#"
+            <p class="warning">{}</p>"#,
+                    NO_LINE_DATA_MESSAGE
+                ));
+            }
+        }
 
-    for repo_name in &app.repositories {
         html.push_str(&format!(
             r#"
         <div class="repo-section">
-            <h2>Repository: {}</h2>
+            <h2>Repository: {}</h2>{}
             <table>
                 <thead>
                     <tr>
+                        <th>Rank</th>
                         <th>Author</th>
                         <th>Email</th>
-                        <th>Commits</th>
+                        <th>Commits</th>{}
                         <th>Lines Added</th>
                         <th>Lines Deleted</th>
                         <th>Contribution %</th>
@@ -137,7 +394,9 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
                 </thead>
                 <tbody>
 "#,
-            repo_name
+            html_escape(&heading),
+            notes,
+            merges_header
         ));
 
         if let Some(contributions) = app.contributions.get(repo_name) {
@@ -145,24 +404,129 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
                 html.push_str(&format!(
                     r#"
                     <tr>
+                        <td>#{}</td>
                         <td>{}</td>
                         <td>{}</td>
+                        <td>{}</td>{}
                         <td>{}</td>
                         <td>{}</td>
                         <td>{}</td>
-                        <td>{:.2}%</td>
                     </tr>
 "#,
-                    contrib.author,
-                    contrib.email,
+                    contrib.rank,
+                    html_escape(&contrib.display_author()),
+                    html_escape(&contrib.email),
                     contrib.commits,
+                    merges_cell(contrib.merges),
                     contrib.lines_added,
                     contrib.lines_deleted,
-                    contrib.contribution_percent
+                    format_percent(contrib.contribution_percent, app.percent_precision)
                 ));
             }
         }
 
+        let mut repo_days = BTreeMap::new();
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            add_commit_days(&mut repo_days, contributions);
+        }
+        html.push_str(&format!(
+            r#"
+                </tbody>
+            </table>{}
+        </div>
+"#,
+            activity_calendar_html(&repo_days, app.calendar_years)
+        ));
+    }
+    if group_open {
+        html.push_str("        </details>\n");
+    }
+
+    if !app.orphaned.is_empty() {
+        html.push_str(&format!(
+            r#"
+        <div class="repo-section">
+            <h2>Orphaned Directories</h2>
+            <p>No contributor owns more than {} of these directories.</p>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Repository</th>
+                        <th>Directory</th>
+                        <th>Contributors</th>
+                        <th>Largest Shares</th>
+                    </tr>
+                </thead>
+                <tbody>
+"#,
+            format_percent(app.orphan_threshold, 0)
+        ));
+        for orphan in &app.orphaned {
+            html.push_str(&format!(
+                r#"
+                    <tr>
+                        <td>{}</td>
+                        <td>{}/</td>
+                        <td>{}</td>
+                        <td>{}</td>
+                    </tr>
+"#,
+                html_escape(&orphan.repository),
+                html_escape(&orphan.directory),
+                orphan.contributors,
+                orphan.ownership_spread(app.percent_precision)
+            ));
+        }
+        html.push_str(
+            r#"
+                </tbody>
+            </table>
+        </div>
+"#,
+        );
+    }
+
+    if !app.at_risk_files.is_empty() {
+        html.push_str(&format!(
+            r#"
+        <div class="repo-section">
+            <h2>Files Owned by Inactive Authors</h2>
+            <p>Risk is the share of a file's surviving lines whose author has not committed in {} days.</p>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Repository</th>
+                        <th>File</th>
+                        <th>Lines</th>
+                        <th>Risk</th>
+                        <th>Main Inactive Owner</th>
+                        <th>Last Commit</th>
+                    </tr>
+                </thead>
+                <tbody>
+"#,
+            app.inactive_days
+        ));
+        for file in &app.at_risk_files {
+            html.push_str(&format!(
+                r#"
+                    <tr>
+                        <td>{}</td>
+                        <td>{}</td>
+                        <td>{}</td>
+                        <td>{}</td>
+                        <td>{}</td>
+                        <td>{}</td>
+                    </tr>
+"#,
+                html_escape(&file.repository),
+                html_escape(&file.path),
+                file.lines,
+                format_percent(file.risk_percent, app.percent_precision),
+                html_escape(&file.inactive_owner),
+                file.inactivity()
+            ));
+        }
         html.push_str(
             r#"
                 </tbody>
@@ -172,6 +536,26 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
         );
     }
 
+    if !app.error_log.is_empty() {
+        html.push_str(
+            r#"
+        <div class="repo-section">
+            <h2>Errors</h2>"#,
+        );
+        for error in &app.error_log {
+            html.push_str(&format!(
+                r#"
+            <p class="warning">{}</p>"#,
+                html_escape(error)
+            ));
+        }
+        html.push_str(
+            r#"
+        </div>
+"#,
+        );
+    }
+
     html.push_str(
         r#"
     </div>
@@ -184,3 +568,1196 @@ pub fn export_html_report(app: &App, output_path: &Path) -> Result<(), Box<dyn E
 
     Ok(())
 }
+
+/// Years the HTML activity calendars cover, both inclusive (`--calendar-years`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearRange {
+    pub from: i32,
+    pub until: i32,
+}
+
+impl YearRange {
+    /// Parses a single year, `2024`, or a range, `2022..2024`.
+    pub fn parse(range: &str) -> Result<YearRange, Box<dyn Error + Send>> {
+        let invalid = || {
+            Box::new(std::io::Error::other(format!(
+                "Invalid year range '{}', expected <year> or <year>..<year>",
+                range
+            ))) as Box<dyn Error + Send>
+        };
+        let (from, until) = range.split_once("..").unwrap_or((range, range));
+        let from = from.trim().parse().map_err(|_| invalid())?;
+        let until = until.trim().parse().map_err(|_| invalid())?;
+        if from > until {
+            return Err(invalid());
+        }
+        Ok(YearRange { from, until })
+    }
+}
+
+/// Calendar cell colors from no commits to the busiest days.
+const CALENDAR_COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+/// Distance between calendar cells, which are two pixels smaller.
+const CALENDAR_CELL: i64 = 12;
+/// Room left of the grid for weekday names and above it for month names.
+const CALENDAR_LEFT: i64 = 28;
+const CALENDAR_TOP: i64 = 14;
+
+fn add_commit_days(days: &mut BTreeMap<NaiveDate, u32>, contributions: &[Contribution]) {
+    for contribution in contributions {
+        for (day, commits) in &contribution.commit_days {
+            *days.entry(*day).or_insert(0) += commits;
+        }
+    }
+}
+
+/// A GitHub-style activity calendar: one grid per year, newest on top, with
+/// a column per week and a cell per day shaded by its commits relative to
+/// the busiest day shown. Covers `years` if given, otherwise the first to
+/// the last year with commits; empty if no commits fall in those years.
+fn activity_calendar_html(days: &BTreeMap<NaiveDate, u32>, years: Option<YearRange>) -> String {
+    let years = match years {
+        Some(years) => years,
+        None => match (days.keys().next(), days.keys().next_back()) {
+            (Some(first), Some(last)) => YearRange {
+                from: first.year(),
+                until: last.year(),
+            },
+            _ => return String::new(),
+        },
+    };
+    let shown = || {
+        days.iter()
+            .filter(|(day, _)| (years.from..=years.until).contains(&day.year()))
+    };
+    let busiest = match shown().map(|(_, commits)| *commits).max() {
+        Some(busiest) => busiest,
+        None => return String::new(),
+    };
+    let level = |commits: u32| {
+        if commits == 0 {
+            0
+        } else {
+            (commits * 4).div_ceil(busiest).clamp(1, 4) as usize
+        }
+    };
+
+    let mut html = String::from(
+        r#"
+            <h3>Activity</h3>
+            <div class="calendar">"#,
+    );
+    for year in (years.from..=years.until).rev() {
+        let (Some(first_day), Some(next_year)) = (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year + 1, 1, 1),
+        ) else {
+            continue;
+        };
+        // Weeks start on Sunday, so January 1st sits in the first column's
+        // row for its weekday.
+        let offset = first_day.weekday().num_days_from_sunday() as i64;
+        let position = |day: NaiveDate| {
+            let index = day.ordinal0() as i64 + offset;
+            (
+                CALENDAR_LEFT + index / 7 * CALENDAR_CELL,
+                CALENDAR_TOP + index % 7 * CALENDAR_CELL,
+            )
+        };
+        let year_commits: u32 = shown()
+            .filter(|(day, _)| day.year() == year)
+            .map(|(_, commits)| commits)
+            .sum();
+        let weeks = (next_year - first_day).num_days() + offset + 6;
+        let width = CALENDAR_LEFT + weeks / 7 * CALENDAR_CELL;
+        let height = CALENDAR_TOP + 7 * CALENDAR_CELL;
+
+        html.push_str(&format!(
+            r#"
+                <div class="calendar-year">{}: {} commit{}</div>
+                <svg width="{}" height="{}" role="img" aria-label="Commits per day in {}">"#,
+            year,
+            year_commits,
+            if year_commits == 1 { "" } else { "s" },
+            width,
+            height,
+            year
+        ));
+        for month in 1..=12 {
+            if let Some(day) = NaiveDate::from_ymd_opt(year, month, 1) {
+                html.push_str(&format!(
+                    r#"
+                    <text x="{}" y="{}">{}</text>"#,
+                    position(day).0,
+                    CALENDAR_TOP - 4,
+                    day.format("%b")
+                ));
+            }
+        }
+        for (row, name) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+            html.push_str(&format!(
+                r#"
+                    <text x="0" y="{}">{}</text>"#,
+                CALENDAR_TOP + row * CALENDAR_CELL + 9,
+                name
+            ));
+        }
+        for day in first_day.iter_days().take_while(|day| *day < next_year) {
+            let commits = days.get(&day).copied().unwrap_or(0);
+            let (x, y) = position(day);
+            html.push_str(&format!(
+                r#"
+                    <rect x="{}" y="{}" width="{}" height="{}" rx="2" fill="{}"><title>{} commit{} on {}</title></rect>"#,
+                x,
+                y,
+                CALENDAR_CELL - 2,
+                CALENDAR_CELL - 2,
+                CALENDAR_COLORS[level(commits)],
+                commits,
+                if commits == 1 { "" } else { "s" },
+                day.format("%Y-%m-%d")
+            ));
+        }
+        html.push_str(
+            r#"
+                </svg>"#,
+        );
+    }
+
+    html.push_str(
+        r#"
+                <div class="calendar-legend">Less "#,
+    );
+    for color in CALENDAR_COLORS {
+        html.push_str(&format!(
+            r#"<span style="background-color: {}"></span>"#,
+            color
+        ));
+    }
+    html.push_str(&format!(
+        r#" More (busiest day: {} commit{})</div>
+            </div>"#,
+        busiest,
+        if busiest == 1 { "" } else { "s" }
+    ));
+    html
+}
+
+/// Time step of the HTML report's contribution timeline (`--timeline-granularity`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum TimelineGranularity {
+    Week,
+    #[default]
+    Month,
+}
+
+impl TimelineGranularity {
+    /// First day of the week (Monday) or month `day` falls in.
+    fn period_start(self, day: NaiveDate) -> NaiveDate {
+        match self {
+            TimelineGranularity::Week => {
+                day - chrono::Days::new(day.weekday().num_days_from_monday() as u64)
+            }
+            TimelineGranularity::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+
+    /// Start of the period after the one starting on `start`.
+    fn next(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            TimelineGranularity::Week => start.checked_add_days(chrono::Days::new(7)),
+            TimelineGranularity::Month => start.checked_add_months(chrono::Months::new(1)),
+        }
+    }
+
+    fn label(self, start: NaiveDate) -> String {
+        match self {
+            TimelineGranularity::Week => format!("week of {}", start.format("%Y-%m-%d")),
+            TimelineGranularity::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// The lines a language's share is measured by: what is left at `HEAD` when
+/// `--blame` counted it, what was changed otherwise.
+fn footprint_lines(footprint: &LanguageFootprint, surviving: bool) -> u32 {
+    if surviving {
+        footprint.surviving_lines
+    } else {
+        footprint.lines_changed
+    }
+}
+
+/// Each language's share of all lines, largest first, with a bar scaled to
+/// the largest.
+fn language_footprint_html(app: &App) -> String {
+    let footprint = language_footprint(&app.contributions);
+    let total: u32 = footprint
+        .iter()
+        .map(|f| footprint_lines(f, app.show_surviving))
+        .sum();
+    let Some(largest) = footprint
+        .iter()
+        .map(|f| footprint_lines(f, app.show_surviving))
+        .max()
+        .filter(|&largest| largest > 0)
+    else {
+        return String::new();
+    };
+
+    let mut html = format!(
+        r#"
+        <div class="repo-section">
+            <h2>Technology Footprint</h2>
+            <p>Lines per language across all repositories, by file extension{}.</p>
+            <table>
+                <thead>
+                    <tr>
+                        <th>Language</th>
+                        <th>Lines Changed</th>{}
+                        <th>Repositories</th>
+                        <th>Share</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+"#,
+        if app.show_surviving {
+            "; shares are of the lines at HEAD"
+        } else {
+            ""
+        },
+        if app.show_surviving {
+            "\n                        <th>Surviving</th>"
+        } else {
+            ""
+        }
+    );
+    for language in &footprint {
+        let lines = footprint_lines(language, app.show_surviving);
+        html.push_str(&format!(
+            r#"
+                    <tr>
+                        <td>{}</td>
+                        <td>{}</td>{}
+                        <td>{}</td>
+                        <td>{}</td>
+                        <td><div class="footprint-bar" style="width: {:.1}%"></div></td>
+                    </tr>
+"#,
+            html_escape(&language.language),
+            language.lines_changed,
+            if app.show_surviving {
+                format!(
+                    "\n                        <td>{}</td>",
+                    language.surviving_lines
+                )
+            } else {
+                String::new()
+            },
+            language.repositories,
+            format_percent(lines as f64 / total as f64 * 100.0, app.percent_precision),
+            lines as f64 / largest as f64 * 100.0
+        ));
+    }
+    html.push_str(
+        r#"
+                </tbody>
+            </table>
+        </div>
+"#,
+    );
+    html
+}
+
+/// Authors the timeline draws by default (`--timeline-authors`).
+pub const DEFAULT_TIMELINE_AUTHORS: usize = 5;
+
+/// Line colors of the timeline's authors, in order.
+const TIMELINE_COLORS: [&str; 8] = [
+    "#3498db", "#e67e22", "#27ae60", "#9b59b6", "#e74c3c", "#16a085", "#f1c40f", "#34495e",
+];
+const TIMELINE_AXIS_COLOR: &str = "#bdc3c7";
+const TIMELINE_WIDTH: usize = 720;
+const TIMELINE_HEIGHT: usize = 220;
+/// Room left of the plot for the commit axis and below it for dates.
+const TIMELINE_LEFT: usize = 40;
+const TIMELINE_BOTTOM: usize = 20;
+
+/// Cumulative commits over time of the summary rows with the most commits,
+/// one line each, per week or month. An author whose commits all fall in
+/// one period is a single step, and with a single period overall every
+/// author is a dot. Empty without commit dates or with `timeline_authors` 0.
+fn contribution_timeline_html(app: &App) -> String {
+    let mut authors: Vec<&AuthorSummary> = app
+        .author_summaries
+        .iter()
+        .filter(|s| s.total_commits > 0)
+        .collect();
+    authors.sort_by(|a, b| {
+        b.total_commits
+            .cmp(&a.total_commits)
+            .then(a.rank.cmp(&b.rank))
+    });
+    authors.truncate(app.timeline_authors);
+
+    // Commits per period of each author, keyed like the summary rows.
+    let granularity = app.timeline_granularity;
+    let mut periods: Vec<BTreeMap<NaiveDate, u32>> = vec![BTreeMap::new(); authors.len()];
+    for contrib in app.contributions.values().flatten() {
+        let (key, _) = app.group_by.summary_key(&contrib.email, &contrib.author);
+        if let Some(index) = authors.iter().position(|s| s.email == key) {
+            for (day, commits) in &contrib.commit_days {
+                *periods[index]
+                    .entry(granularity.period_start(*day))
+                    .or_insert(0) += commits;
+            }
+        }
+    }
+    let (Some(first), Some(last)) = (
+        periods.iter().filter_map(|p| p.keys().next()).min(),
+        periods.iter().filter_map(|p| p.keys().next_back()).max(),
+    ) else {
+        return String::new();
+    };
+    let mut starts = vec![*first];
+    while let Some(next) = granularity.next(starts[starts.len() - 1]) {
+        if next > *last {
+            break;
+        }
+        starts.push(next);
+    }
+
+    let series: Vec<Vec<u32>> = periods
+        .iter()
+        .map(|commits| {
+            starts
+                .iter()
+                .scan(0, |total, start| {
+                    *total += commits.get(start).copied().unwrap_or(0);
+                    Some(*total)
+                })
+                .collect()
+        })
+        .collect();
+    let most = series.iter().flatten().copied().max().unwrap_or(0).max(1);
+    let plot_width = TIMELINE_WIDTH - TIMELINE_LEFT - 10;
+    let plot_height = TIMELINE_HEIGHT - TIMELINE_BOTTOM - 10;
+    let x = |index: usize| match starts.len() {
+        1 => TIMELINE_LEFT + plot_width / 2,
+        periods => TIMELINE_LEFT + index * plot_width / (periods - 1),
+    };
+    let y = |total: u32| 10 + plot_height - total as usize * plot_height / most as usize;
+
+    let mut html = format!(
+        r#"
+            <h3>Commits over Time</h3>
+            <div class="timeline">
+                <svg width="{width}" height="{height}" role="img" aria-label="Cumulative commits per {unit} of the top {count} authors">
+                    <line x1="{left}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="{axis}"/>
+                    <line x1="{left}" y1="10" x2="{left}" y2="{bottom}" stroke="{axis}"/>
+                    <text x="0" y="14">{most}</text>
+                    <text x="0" y="{bottom}">0</text>
+                    <text x="{left}" y="{labels}">{first}</text>"#,
+        width = TIMELINE_WIDTH,
+        height = TIMELINE_HEIGHT,
+        labels = TIMELINE_HEIGHT - 4,
+        unit = if granularity == TimelineGranularity::Week {
+            "week"
+        } else {
+            "month"
+        },
+        count = authors.len(),
+        axis = TIMELINE_AXIS_COLOR,
+        left = TIMELINE_LEFT,
+        right = TIMELINE_LEFT + plot_width,
+        bottom = 10 + plot_height,
+        most = most,
+        first = granularity.label(starts[0]),
+    );
+    if starts.len() > 1 {
+        html.push_str(&format!(
+            r#"
+                    <text x="{}" y="{}" text-anchor="end">{}</text>"#,
+            TIMELINE_LEFT + plot_width,
+            TIMELINE_HEIGHT - 4,
+            granularity.label(starts[starts.len() - 1])
+        ));
+    }
+    for (index, (author, totals)) in authors.iter().zip(&series).enumerate() {
+        let color = TIMELINE_COLORS[index % TIMELINE_COLORS.len()];
+        let points: Vec<String> = totals
+            .iter()
+            .enumerate()
+            .map(|(period, total)| format!("{},{}", x(period), y(*total)))
+            .collect();
+        if points.len() > 1 {
+            html.push_str(&format!(
+                r#"
+                    <polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                points.join(" "),
+                color
+            ));
+        }
+        let last_period = totals.len() - 1;
+        html.push_str(&format!(
+            r#"
+                    <circle cx="{}" cy="{}" r="3" fill="{}"><title>{}: {} commit{} by {}</title></circle>"#,
+            x(last_period),
+            y(totals[last_period]),
+            color,
+            html_escape(&author.author),
+            totals[last_period],
+            if totals[last_period] == 1 { "" } else { "s" },
+            granularity.label(starts[last_period])
+        ));
+    }
+    html.push_str(
+        r#"
+                </svg>
+                <div class="timeline-legend">"#,
+    );
+    for (index, author) in authors.iter().enumerate() {
+        html.push_str(&format!(
+            r#"<span style="color: {}">&#9632; {}</span>"#,
+            TIMELINE_COLORS[index % TIMELINE_COLORS.len()],
+            html_escape(&author.author)
+        ));
+    }
+    html.push_str(
+        r#"</div>
+            </div>"#,
+    );
+    html
+}
+
+/// Report formats offered by the export menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Html,
+    Csv,
+    Json,
+    JsonLines,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Menu order.
+    pub const ALL: [ExportFormat; 5] = [
+        ExportFormat::Html,
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::JsonLines,
+        ExportFormat::Markdown,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "HTML",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::JsonLines => "JSON Lines",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::JsonLines => "ndjson",
+            ExportFormat::Markdown => "md",
+        }
+    }
+
+    /// File the report is written to in the working directory.
+    pub fn default_path(self) -> PathBuf {
+        PathBuf::from(format!("git_contribution_report.{}", self.extension()))
+    }
+}
+
+pub fn export_report(
+    app: &App,
+    format: ExportFormat,
+    output_path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ExportFormat::Html => export_html_report(app, output_path),
+        ExportFormat::Csv => export_csv_report(app, output_path),
+        ExportFormat::Json => export_json_report(app, output_path),
+        ExportFormat::JsonLines => export_json_lines_report(app, output_path),
+        ExportFormat::Markdown => export_markdown_report(app, output_path),
+    }
+}
+
+/// Replaces characters that are unsafe or awkward in file names with `_`.
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    match sanitized.trim_start_matches('.') {
+        "" => String::from("_"),
+        _ => sanitized,
+    }
+}
+
+/// File names (without extension) for `repositories`, in order: each name
+/// sanitized, with `-2`, `-3`, ... appended where two would otherwise match.
+fn repository_file_stems(repositories: &[String]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    repositories
+        .iter()
+        .map(|repo_name| {
+            let stem = sanitize_file_name(repo_name);
+            let mut unique = stem.clone();
+            let mut suffix = 2;
+            while !taken.insert(unique.to_lowercase()) {
+                unique = format!("{}-{}", stem, suffix);
+                suffix += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+/// Writes one report per repository into `output_dir`, named after the
+/// repository, plus an `index` report with the summary that links to them.
+/// CSV and JSON Lines hold no summary, so they get no index file.
+pub fn export_split_report(
+    app: &App,
+    format: ExportFormat,
+    output_dir: &Path,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(output_dir)?;
+
+    let extension = format.extension();
+    let mut repository_reports = Vec::new();
+    for (repo_name, stem) in app
+        .repositories
+        .iter()
+        .zip(repository_file_stems(&app.repositories))
+    {
+        let file_name = format!("{}.{}", stem, extension);
+        export_report(
+            &app.repository_view(repo_name),
+            format,
+            &output_dir.join(&file_name),
+        )?;
+        repository_reports.push((repo_name.clone(), file_name));
+    }
+
+    if !matches!(format, ExportFormat::Csv | ExportFormat::JsonLines) {
+        export_report(
+            &app.summary_view(repository_reports),
+            format,
+            &output_dir.join(format!("index.{}", extension)),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes one row per author and repository.
+pub fn export_csv_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut csv = String::from(
+        "repository,rank,author,email,commits,merges,lines_added,lines_deleted,contribution_percent\n",
+    );
+
+    for repo_name in &app.repositories {
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            for contrib in contributions {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{:.2}\n",
+                    csv_field(repo_name),
+                    contrib.rank,
+                    csv_field(&contrib.author),
+                    csv_field(&contrib.email),
+                    contrib.commits,
+                    contrib.merges,
+                    contrib.lines_added,
+                    contrib.lines_deleted,
+                    contrib.contribution_percent
+                ));
+            }
+        }
+    }
+
+    fs::write(output_path, csv)?;
+
+    Ok(())
+}
+
+/// Version of the JSON report's shape, written as its `schema_version`. Bump it
+/// and `JSON_SCHEMA` together whenever a field is renamed, removed or changes
+/// type; adding a field does not need a bump.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema describing the JSON report, as printed by `--schema`.
+pub const JSON_SCHEMA: &str = include_str!("report.schema.json");
+
+#[derive(Serialize)]
+struct JsonRepository<'a> {
+    name: &'a str,
+    path: Option<&'a Path>,
+    info: Option<&'a RepositoryInfo>,
+    health: Option<RepositoryHealth>,
+    contributions: &'a [Contribution],
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    generated_at: String,
+    overview: OverviewStats,
+    coverage: &'a CoverageStats,
+    summaries: &'a [AuthorSummary],
+    languages: Vec<LanguageFootprint>,
+    orphaned_directories: &'a [OrphanedDirectory],
+    at_risk_files: &'a [AtRiskFile],
+    author_merges: &'a [AuthorMerge],
+    repositories: Vec<JsonRepository<'a>>,
+}
+
+pub fn export_json_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    let report = JsonReport {
+        schema_version: JSON_SCHEMA_VERSION,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        overview: app.overview(),
+        coverage: &app.coverage,
+        summaries: &app.author_summaries,
+        languages: language_footprint(&app.contributions),
+        orphaned_directories: &app.orphaned,
+        at_risk_files: &app.at_risk_files,
+        author_merges: &app.author_merges,
+        repositories: app
+            .repositories
+            .iter()
+            .map(|name| JsonRepository {
+                name,
+                path: app.repository_paths.get(name).map(PathBuf::as_path),
+                info: app.repository_info.get(name),
+                health: app.repository_health(name),
+                contributions: app.contributions.get(name).map_or(&[], Vec::as_slice),
+            })
+            .collect(),
+    };
+
+    fs::write(output_path, serde_json::to_string_pretty(&report)?)?;
+
+    Ok(())
+}
+
+/// One line of the JSON Lines export: a contribution plus the schema version.
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    contribution: &'a Contribution,
+}
+
+/// Writes one JSON object per contribution per line (NDJSON), each carrying its
+/// repository, so the output can be streamed through tools like `jq`.
+pub fn export_json_lines_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(output_path)?);
+
+    for repo_name in &app.repositories {
+        for contribution in app.contributions.get(repo_name).into_iter().flatten() {
+            let line = JsonLine {
+                schema_version: JSON_SCHEMA_VERSION,
+                contribution,
+            };
+            serde_json::to_writer(&mut writer, &line)?;
+            writer.write_all(b"\n")?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+pub fn export_markdown_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut markdown = format!(
+        "# Git Contribution Analysis Report\n\n_Generated on: {}_\n\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    let overview = app.overview();
+    markdown.push_str(&format!(
+        "**Repositories:** {} | **Authors:** {} | **Commits:** {} | **Line churn:** {} (+{} / -{})\n\n",
+        overview.repositories,
+        overview.authors,
+        overview.commits,
+        overview.line_churn(),
+        overview.lines_added,
+        overview.lines_deleted
+    ));
+    markdown.push_str(&format!("_{}_\n\n", app.coverage.summary()));
+
+    markdown.push_str(&format!(
+        "## {}{}\n\n| Rank | {} | Email |{} Total Commits | Lines Added | Lines Deleted | Overall % | Preferred Repo | Preferred % |{}\n|---|---|---|{}---|---|---|---|---|---|{}\n",
+        app.group_by.summary_title(),
+        app.summary_basis.title_note(),
+        app.group_by.name_header(),
+        if app.org_map.is_some() { " Org |" } else { "" },
+        if app.show_reviews { " Reviews |" } else { "" },
+        if app.org_map.is_some() { "---|" } else { "" },
+        if app.show_reviews { "---|" } else { "" }
+    ));
+    for summary in &app.author_summaries {
+        let organization = if app.org_map.is_some() {
+            format!(
+                " {} |",
+                markdown_cell(summary.organization.as_deref().unwrap_or_default())
+            )
+        } else {
+            String::new()
+        };
+        let reviews = if app.show_reviews {
+            format!(" {} |", summary.reviews)
+        } else {
+            String::new()
+        };
+        markdown.push_str(&format!(
+            "| #{} | {} | {} |{} {} | {} | {} | {} | {} | {} |{}\n",
+            summary.rank,
+            markdown_cell(&summary.author),
+            markdown_cell(&summary.email),
+            organization,
+            summary.total_commits,
+            summary.total_lines_added,
+            summary.total_lines_deleted,
+            format_percent(summary.overall_contribution_percent, app.percent_precision),
+            markdown_cell(&summary.preferred_repo),
+            format_percent(summary.preferred_repo_percent, app.percent_precision),
+            reviews
+        ));
+    }
+
+    let footprint = language_footprint(&app.contributions);
+    if !footprint.is_empty() {
+        let total: u32 = footprint
+            .iter()
+            .map(|f| footprint_lines(f, app.show_surviving))
+            .sum();
+        markdown.push_str(&format!(
+            "\n## Technology Footprint\n\n| Language | Lines Changed |{} Repositories | Share |\n|---|---|{}---|---|\n",
+            if app.show_surviving { " Surviving |" } else { "" },
+            if app.show_surviving { "---|" } else { "" }
+        ));
+        for language in &footprint {
+            let share = if total > 0 {
+                footprint_lines(language, app.show_surviving) as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            };
+            markdown.push_str(&format!(
+                "| {} | {} |{} {} | {} |\n",
+                markdown_cell(&language.language),
+                language.lines_changed,
+                if app.show_surviving {
+                    format!(" {} |", language.surviving_lines)
+                } else {
+                    String::new()
+                },
+                language.repositories,
+                format_percent(share, app.percent_precision)
+            ));
+        }
+    }
+
+    if !app.repository_reports.is_empty() {
+        markdown.push_str("\n## Repositories\n\n");
+        for (repo_name, file_name) in &app.repository_reports {
+            markdown.push_str(&format!("- [{}]({})\n", repo_name, file_name));
+        }
+    }
+
+    for repo_name in &app.repositories {
+        let heading = match app.history_limits(repo_name) {
+            Some(range) => format!("{} ({})", repo_name, range),
+            None => repo_name.to_string(),
+        };
+        markdown.push_str(&format!(
+            "\n## Repository: {}\n\n| Rank | Author | Email | Commits | Lines Added | Lines Deleted | Contribution % |\n|---|---|---|---|---|---|---|\n",
+            markdown_cell(&heading)
+        ));
+
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            for contrib in contributions {
+                markdown.push_str(&format!(
+                    "| #{} | {} | {} | {} | {} | {} | {} |\n",
+                    contrib.rank,
+                    markdown_cell(&contrib.display_author()),
+                    markdown_cell(&contrib.email),
+                    contrib.commits,
+                    contrib.lines_added,
+                    contrib.lines_deleted,
+                    format_percent(contrib.contribution_percent, app.percent_precision)
+                ));
+            }
+        }
+    }
+
+    if !app.orphaned.is_empty() {
+        markdown.push_str(&format!(
+            "\n## Orphaned Directories\n\nNo contributor owns more than {} of these directories.\n\n| Repository | Directory | Contributors | Largest Shares |\n|---|---|---|---|\n",
+            format_percent(app.orphan_threshold, 0)
+        ));
+        for orphan in &app.orphaned {
+            markdown.push_str(&format!(
+                "| {} | {}/ | {} | {} |\n",
+                markdown_cell(&orphan.repository),
+                markdown_cell(&orphan.directory),
+                orphan.contributors,
+                markdown_cell(&orphan.ownership_spread(app.percent_precision))
+            ));
+        }
+    }
+
+    if !app.at_risk_files.is_empty() {
+        markdown.push_str(&format!(
+            "\n## Files Owned by Inactive Authors\n\nRisk is the share of a file's surviving lines whose author has not committed in {} days.\n\n| Repository | File | Lines | Risk | Main Inactive Owner | Last Commit |\n|---|---|---|---|---|---|\n",
+            app.inactive_days
+        ));
+        for file in &app.at_risk_files {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                markdown_cell(&file.repository),
+                markdown_cell(&file.path),
+                file.lines,
+                format_percent(file.risk_percent, app.percent_precision),
+                markdown_cell(&file.inactive_owner),
+                file.inactivity()
+            ));
+        }
+    }
+
+    if !app.error_log.is_empty() {
+        markdown.push_str("\n## Errors\n\n");
+        for error in &app.error_log {
+            markdown.push_str(&format!("- {}\n", error));
+        }
+    }
+
+    fs::write(output_path, markdown)?;
+
+    Ok(())
+}
+
+/// Writes a suggested `<repo>.CODEOWNERS` file per repository into `output_dir`,
+/// assigning each top-level directory to its primary contributor when their
+/// share of the directory (by `App::ownership_basis`) exceeds `threshold_percent`.
+pub fn export_codeowners(
+    app: &App,
+    output_dir: &Path,
+    threshold_percent: f64,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(output_dir)?;
+
+    for (repo_name, stem) in app
+        .repositories
+        .iter()
+        .zip(repository_file_stems(&app.repositories))
+    {
+        let mut codeowners = format!(
+            "# Suggested CODEOWNERS for {}\n# Generated by git-contribution-analyzer: each top-level directory is\n# owned by the contributor with more than {}% of its {}.\n\n",
+            repo_name,
+            threshold_percent,
+            match app.ownership_basis {
+                OwnershipBasis::Lines => "changed lines",
+                OwnershipBasis::Commits => "commits",
+            }
+        );
+
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            for owner in directory_owners(contributions, app.ownership_basis) {
+                if owner.percent > threshold_percent {
+                    codeowners.push_str(&format!("/{}/ {}\n", owner.directory, owner.email));
+                }
+            }
+        }
+
+        fs::write(output_dir.join(format!("{}.CODEOWNERS", stem)), codeowners)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rusqlite")]
+pub fn export_sqlite_report(app: &App, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    use rusqlite::{params, Connection};
+
+    let mut conn = Connection::open(output_path)?;
+
+    conn.execute_batch(
+        r#"
+        PRAGMA foreign_keys = ON;
+
+        CREATE TABLE IF NOT EXISTS repositories (
+            name TEXT PRIMARY KEY
+        );
+
+        CREATE TABLE IF NOT EXISTS contributions (
+            repository TEXT NOT NULL REFERENCES repositories(name),
+            email TEXT NOT NULL,
+            author TEXT NOT NULL,
+            commits INTEGER NOT NULL,
+            lines_added INTEGER NOT NULL,
+            lines_deleted INTEGER NOT NULL,
+            contribution_percent REAL NOT NULL,
+            PRIMARY KEY (repository, email)
+        );
+
+        CREATE TABLE IF NOT EXISTS summaries (
+            email TEXT PRIMARY KEY,
+            author TEXT NOT NULL,
+            total_commits INTEGER NOT NULL,
+            total_lines_added INTEGER NOT NULL,
+            total_lines_deleted INTEGER NOT NULL,
+            overall_contribution_percent REAL NOT NULL,
+            preferred_repo TEXT NOT NULL,
+            preferred_repo_percent REAL NOT NULL
+        );
+        "#,
+    )?;
+
+    let tx = conn.transaction()?;
+
+    for repo_name in &app.repositories {
+        tx.execute(
+            "INSERT INTO repositories (name) VALUES (?1) ON CONFLICT(name) DO NOTHING",
+            params![repo_name],
+        )?;
+
+        if let Some(contributions) = app.contributions.get(repo_name) {
+            for contrib in contributions {
+                tx.execute(
+                    r#"INSERT INTO contributions (
+                        repository, email, author, commits,
+                        lines_added, lines_deleted, contribution_percent
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    ON CONFLICT(repository, email) DO UPDATE SET
+                        author = excluded.author,
+                        commits = excluded.commits,
+                        lines_added = excluded.lines_added,
+                        lines_deleted = excluded.lines_deleted,
+                        contribution_percent = excluded.contribution_percent"#,
+                    params![
+                        repo_name,
+                        contrib.email,
+                        contrib.author,
+                        contrib.commits,
+                        contrib.lines_added,
+                        contrib.lines_deleted,
+                        contrib.contribution_percent
+                    ],
+                )?;
+            }
+        }
+    }
+
+    for summary in &app.author_summaries {
+        tx.execute(
+            r#"INSERT INTO summaries (
+                email, author, total_commits, total_lines_added, total_lines_deleted,
+                overall_contribution_percent, preferred_repo, preferred_repo_percent
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(email) DO UPDATE SET
+                author = excluded.author,
+                total_commits = excluded.total_commits,
+                total_lines_added = excluded.total_lines_added,
+                total_lines_deleted = excluded.total_lines_deleted,
+                overall_contribution_percent = excluded.overall_contribution_percent,
+                preferred_repo = excluded.preferred_repo,
+                preferred_repo_percent = excluded.preferred_repo_percent"#,
+            params![
+                summary.email,
+                summary.author,
+                summary.total_commits,
+                summary.total_lines_added,
+                summary.total_lines_deleted,
+                summary.overall_contribution_percent,
+                summary.preferred_repo,
+                summary.preferred_repo_percent
+            ],
+        )?;
+    }
+
+    tx.commit()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{AnalysisReport, ProgressEvent};
+    use crate::git::{calculate_summaries, GroupBy, LineWeights, SummaryBasis};
+    use std::collections::HashMap;
+
+    /// The HTML report of `app`, written to a temporary file named after `name`.
+    fn html_report(app: &App, name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "gca-export-test-{}-{}.html",
+            std::process::id(),
+            name
+        ));
+        export_html_report(app, &path).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        html
+    }
+
+    #[test]
+    fn pipes_in_author_names_are_escaped_in_markdown_tables() {
+        assert_eq!(markdown_cell("Foo | Bar"), "Foo \\| Bar");
+        assert_eq!(csv_field("Foo | Bar"), "Foo | Bar");
+    }
+
+    #[test]
+    fn html_reports_explain_repositories_without_line_data() {
+        let binary_only = Contribution {
+            author: "Jane".to_string(),
+            email: "jane@acme.com".to_string(),
+            commits: 2,
+            ..Default::default()
+        };
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            repositories: vec!["assets".to_string()],
+            contributions: HashMap::from([("assets".to_string(), vec![binary_only])]),
+            ..Default::default()
+        })));
+
+        assert!(html_report(&app, "line-data").contains(NO_LINE_DATA_MESSAGE));
+    }
+
+    #[test]
+    fn timeline_author_names_are_escaped() {
+        let contributions = HashMap::from([(
+            "api".to_string(),
+            vec![Contribution {
+                author: "<b>Jane</b>".to_string(),
+                email: "jane@acme.com".to_string(),
+                commits: 1,
+                commit_days: BTreeMap::from([(NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(), 1)]),
+                ..Default::default()
+            }],
+        )]);
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            repositories: vec!["api".to_string()],
+            author_summaries: calculate_summaries(
+                &contributions,
+                GroupBy::Author,
+                SummaryBasis::default(),
+                LineWeights::default(),
+            ),
+            contributions,
+            ..Default::default()
+        })));
+
+        let timeline = contribution_timeline_html(&app);
+        assert!(timeline.contains("<title>&lt;b&gt;Jane&lt;/b&gt;: 1 commit"));
+        assert!(timeline.contains("&#9632; &lt;b&gt;Jane&lt;/b&gt;</span>"));
+        assert!(!timeline.contains("<b>"));
+    }
+
+    #[test]
+    fn repository_file_names_never_collide() {
+        let repositories = ["team-a/api", "team-b/api", "team-a_api"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            repository_file_stems(&repositories),
+            ["team-a_api", "team-b_api", "team-a_api-2"]
+        );
+
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            repositories: repositories.clone(),
+            ..Default::default()
+        })));
+        let dir = std::env::temp_dir().join(format!("gca-codeowners-test-{}", std::process::id()));
+        export_codeowners(&app, &dir, 50.0).unwrap();
+        let mut written: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        written.sort();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(
+            written,
+            [
+                "team-a_api-2.CODEOWNERS",
+                "team-a_api.CODEOWNERS",
+                "team-b_api.CODEOWNERS"
+            ]
+        );
+    }
+
+    #[test]
+    fn at_risk_files_and_errors_are_escaped() {
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            at_risk_files: vec![AtRiskFile {
+                repository: "api".to_string(),
+                path: "src/<main>.rs".to_string(),
+                lines: 10,
+                risk_percent: 100.0,
+                inactive_owner: "Bob & Co".to_string(),
+                inactive_owner_days: Some(400),
+            }],
+            errors: vec!["Error analyzing repository <web>: failed".to_string()],
+            ..Default::default()
+        })));
+
+        let html = html_report(&app, "at-risk");
+        assert!(html.contains("<td>src/&lt;main&gt;.rs</td>"));
+        assert!(html.contains("<td>Bob &amp; Co</td>"));
+        assert!(html.contains("repository &lt;web&gt;: failed"));
+        assert!(!html.contains("<main>") && !html.contains("<web>"));
+    }
+
+    #[test]
+    fn report_links_and_organizations_are_escaped() {
+        let mut app = App::new();
+        app.apply_progress(ProgressEvent::Done(Box::new(AnalysisReport {
+            author_summaries: vec![AuthorSummary {
+                author: "Jane".to_string(),
+                email: "jane@acme.com".to_string(),
+                organization: Some("R&D <Labs>".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })));
+        app.org_map = Some(HashMap::new());
+        app.repository_reports = vec![("a\"b<c>".to_string(), "a_b_c_.html".to_string())];
+
+        let html = html_report(&app, "links");
+        assert!(html.contains("<td>R&amp;D &lt;Labs&gt;</td>"));
+        assert!(html.contains(r#"<a href="a_b_c_.html">a&quot;b&lt;c&gt;</a>"#));
+    }
+}
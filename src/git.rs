@@ -1,15 +1,28 @@
-use glob::glob;
+use glob::{glob, MatchOptions, Pattern};
 use itertools::Itertools;
+use regex::Regex;
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
     error::Error,
-    path::{Path, PathBuf},
-    process::Command,
+    fs,
+    io::Read,
+    panic,
+    path::{Component, Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 use crate::app::AuthorSummary;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Contribution {
     pub author: String,
     pub email: String,
@@ -18,215 +31,5717 @@ pub struct Contribution {
     pub lines_deleted: u32,
     pub contribution_percent: f64,
     pub repository: String,
+    /// Occurrences of meaningful words in this author's commit subjects.
+    pub terms: HashMap<String, u32>,
+    /// Issue/PR numbers referenced in this author's commit subjects.
+    pub issues: HashSet<u32>,
+    /// Timestamp of this author's first commit in the repository, ignoring any
+    /// date window, so newcomers can be told apart from returning authors.
+    pub first_commit_at: Option<i64>,
+    /// Timestamp of this author's latest commit in the repository, ignoring any
+    /// date window.
+    pub last_commit_at: Option<i64>,
+    /// Merge commits authored; only counted with `AnalysisOptions::include_merges`.
+    pub merges: u32,
+    /// Lines changed per top-level directory; files at the repository root are
+    /// not attributed to any directory.
+    pub directory_lines: HashMap<String, u32>,
+    /// Commits touching each top-level directory.
+    pub directory_commits: HashMap<String, u32>,
+    /// Lines changed per language, told apart by file extension (`language_of`).
+    pub language_lines: HashMap<String, u32>,
+    /// Position by contribution within the repository, starting at 1; authors
+    /// with equal percentages share a rank.
+    pub rank: u32,
+    /// Lines at `HEAD` that `git blame` attributes to this author; only
+    /// computed with `AnalysisOptions::blame`.
+    pub surviving_lines: u32,
+    /// `surviving_lines` per file; feeds the inactive-owner risk scores.
+    #[serde(skip)]
+    pub file_lines: FileLines,
+    /// Commits since `AnalysisOptions::trend_since`; zero without a trend window.
+    pub recent_commits: u32,
+    /// Commits carrying a GPG, SSH or X.509 signature, whether or not it could
+    /// be verified; only counted with `AnalysisOptions::signatures`.
+    pub signed_commits: u32,
+    /// Paths this author changed, at their latest name; feeds collaborator pairs.
+    #[serde(skip)]
+    pub files_touched: HashSet<String>,
+    /// Analyzed commits per local calendar day; feeds the activity calendars.
+    #[serde(skip)]
+    pub commit_days: BTreeMap<chrono::NaiveDate, u32>,
+    /// Includes uncommitted (staged or unstaged) changes, counted as one extra
+    /// commit; only with `AnalysisOptions::include_working_tree`.
+    pub working_tree: bool,
+    /// Every commit with the details in `AnalysisOptions::commit_fields`,
+    /// newest first; empty unless some were asked for.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commit_records: Vec<CommitRecord>,
+    /// Every analyzed commit with its line counts, newest first, so the TUI
+    /// can list what a percentage is made of; only with
+    /// `AnalysisOptions::keep_commits`.
+    #[serde(skip)]
+    pub kept_commits: Vec<KeptCommit>,
 }
 
-pub fn is_git_repository(path: &Path) -> bool {
-    let git_dir = path.join(".git");
-    git_dir.exists() && git_dir.is_dir()
+/// One commit behind a contribution's totals (`--keep-commits`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeptCommit {
+    pub hash: String,
+    /// Author date (committer date with `--identity committer`), as a Unix time.
+    pub timestamp: i64,
+    pub subject: String,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
 }
 
-pub fn find_repositories(
-    parent_path: &Path,
-    pattern: &str,
-) -> Result<Vec<PathBuf>, Box<dyn Error + Send>> {
-    let mut repositories = Vec::new();
-    let pattern_path = parent_path.join(pattern);
-    let pattern_str = pattern_path.to_string_lossy().to_string();
+/// Lines per file path.
+pub type FileLines = HashMap<String, u32>;
+
+impl Contribution {
+    /// Adds another row of the same repository into this one, as when two
+    /// emails turn out to be one author; the name and email stay this row's.
+    pub fn absorb(&mut self, other: Contribution) {
+        self.commits += other.commits;
+        self.lines_added += other.lines_added;
+        self.lines_deleted += other.lines_deleted;
+        self.contribution_percent += other.contribution_percent;
+        for (term, count) in other.terms {
+            *self.terms.entry(term).or_insert(0) += count;
+        }
+        self.issues.extend(other.issues);
+        self.first_commit_at = match (self.first_commit_at, other.first_commit_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_commit_at = self.last_commit_at.max(other.last_commit_at);
+        self.merges += other.merges;
+        for (directory, lines) in other.directory_lines {
+            *self.directory_lines.entry(directory).or_insert(0) += lines;
+        }
+        for (directory, commits) in other.directory_commits {
+            *self.directory_commits.entry(directory).or_insert(0) += commits;
+        }
+        for (language, lines) in other.language_lines {
+            *self.language_lines.entry(language).or_insert(0) += lines;
+        }
+        self.surviving_lines += other.surviving_lines;
+        for (file, lines) in other.file_lines {
+            *self.file_lines.entry(file).or_insert(0) += lines;
+        }
+        self.recent_commits += other.recent_commits;
+        self.signed_commits += other.signed_commits;
+        self.files_touched.extend(other.files_touched);
+        for (day, commits) in other.commit_days {
+            *self.commit_days.entry(day).or_insert(0) += commits;
+        }
+        self.working_tree |= other.working_tree;
+        self.commit_records.extend(other.commit_records);
+        self.kept_commits.extend(other.kept_commits);
+        self.kept_commits
+            .sort_by_key(|commit| std::cmp::Reverse(commit.timestamp));
+    }
+}
+
+/// Optional commit details that can be recorded per commit for the exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CommitField {
+    /// First line of the message.
+    Subject,
+    /// Rest of the message after the subject.
+    Body,
+    /// `%G?` signature status; see `is_signed`.
+    Signature,
+    /// Committer as `name <email>`.
+    Committer,
+    /// `Key: value` trailers at the end of the message; see `parse_trailers`.
+    Trailers,
+}
+
+impl CommitField {
+    /// `git log` placeholder producing this field.
+    fn placeholder(self) -> &'static str {
+        match self {
+            CommitField::Subject => "%s",
+            CommitField::Body => "%b",
+            CommitField::Signature => "%G?",
+            CommitField::Committer => "%cn <%ce>",
+            CommitField::Trailers => "%B",
+        }
+    }
+}
+
+/// A commit's hash with whichever `CommitField`s were asked for.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CommitRecord {
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committer: Option<String>,
+    /// Trailer values by lowercased key, in message order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailers: Option<BTreeMap<String, Vec<String>>>,
+}
+
+impl CommitRecord {
+    /// This record with every field not in `fields` cleared.
+    pub fn retain_fields(mut self, fields: &[CommitField]) -> CommitRecord {
+        let keep =
+            |field: CommitField, value: Option<String>| value.filter(|_| fields.contains(&field));
+        self.subject = keep(CommitField::Subject, self.subject);
+        self.body = keep(CommitField::Body, self.body);
+        self.signature = keep(CommitField::Signature, self.signature);
+        self.committer = keep(CommitField::Committer, self.committer);
+        if !fields.contains(&CommitField::Trailers) {
+            self.trailers = None;
+        }
+        self
+    }
+}
 
-    for entry in glob(&pattern_str).map_err(|e| Box::new(e) as Box<dyn Error + Send>)? {
-        match entry {
-            Ok(path) => {
-                if path.is_dir() && is_git_repository(&path) {
-                    repositories.push(path);
+/// `git log` format writing each commit as a record separator, its hash and
+/// then `fields` in order, all separated by unit separators. Neither control
+/// character can appear in names, emails or subjects, so fields can be added
+/// or removed without the parsing going out of step.
+fn commit_record_format(fields: &[CommitField]) -> String {
+    let mut format = String::from("--format=%x1e%H");
+    for field in fields {
+        format.push_str("%x1f");
+        format.push_str(field.placeholder());
+    }
+    format
+}
+
+/// Parses one record of `commit_record_format(fields)` output, without its
+/// leading record separator; `None` for the empty text before the first one.
+pub fn parse_commit_record(record: &str, fields: &[CommitField]) -> Option<CommitRecord> {
+    let mut values = record.split('\x1f');
+    let hash = values.next()?.trim();
+    if hash.is_empty() {
+        return None;
+    }
+
+    let mut commit = CommitRecord {
+        hash: hash.to_string(),
+        ..Default::default()
+    };
+    for (field, value) in fields.iter().zip(values) {
+        let value = Some(value.trim().to_string());
+        match field {
+            CommitField::Subject => commit.subject = value,
+            CommitField::Body => commit.body = value,
+            CommitField::Signature => commit.signature = value,
+            CommitField::Committer => commit.committer = value,
+            CommitField::Trailers => {
+                commit.trailers =
+                    value.map(|message| parse_trailers(&message).into_iter().collect())
+            }
+        }
+    }
+    Some(commit)
+}
+
+/// Extracts the `Key: value` trailers (`Co-authored-by:`, `Reviewed-by:`, ...)
+/// from a full commit message as `%B` gives it. Like `git interpret-trailers`,
+/// only the last paragraph counts, and only if it is not the subject and every
+/// line in it is a trailer; indented lines continue the previous value. Keys
+/// are lowercased, since git matches them case-insensitively.
+pub fn parse_trailers(body: &str) -> HashMap<String, Vec<String>> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in body.lines().map(|line| line.trim_end_matches('\r')) {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+        } else {
+            paragraph.push(line);
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    if paragraphs.len() < 2 {
+        return HashMap::new();
+    }
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+    for line in paragraphs.pop().unwrap_or_default() {
+        if line.starts_with([' ', '\t']) {
+            match entries.last_mut() {
+                Some((_, value)) => {
+                    if !value.is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(line.trim());
                 }
+                None => return HashMap::new(),
             }
-            Err(e) => eprintln!("Error matching path: {}", e),
+            continue;
+        }
+        // Added by `git cherry-pick -x`; git itself accepts it among trailers.
+        if line.starts_with("(cherry picked from commit ") {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return HashMap::new();
+        };
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return HashMap::new();
         }
+        entries.push((key.to_ascii_lowercase(), value.trim().to_string()));
     }
 
-    Ok(repositories)
+    let mut trailers: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in entries {
+        if !value.is_empty() {
+            trailers.entry(key).or_default().push(value);
+        }
+    }
+    trailers
 }
 
-pub fn analyze_repository(repo_path: &Path) -> Result<(String, Vec<Contribution>), Box<dyn Error>> {
-    let repo_name = repo_path
-        .file_name()
-        .ok_or("Invalid repository path")?
-        .to_string_lossy()
-        .to_string();
+/// What ownership is measured in: who changed the most lines, or who made the
+/// most commits regardless of their size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OwnershipBasis {
+    #[default]
+    Lines,
+    Commits,
+}
 
-    let mut contributions = Vec::new();
+/// Whether a `%G?` signature status means the commit is signed. Every status
+/// but `N` (no signature) does: `G` good, `U` good but of unknown validity,
+/// `X`/`Y` made with an expired signature or key, `R` revoked key, `B` bad,
+/// and `E` not checkable, usually because the key is missing.
+pub fn is_signed(status: &str) -> bool {
+    matches!(status, "G" | "U" | "X" | "Y" | "R" | "B" | "E")
+}
 
-    let total_output = Command::new("git")
-        .args(["log", "--no-merges", "--numstat"])
-        .current_dir(repo_path)
-        .output()?
-        .stdout;
+/// Share of `commits` that are signed, in percent.
+pub fn signed_percent(signed_commits: u32, commits: u32) -> f64 {
+    if commits > 0 {
+        signed_commits as f64 / commits as f64 * 100.0
+    } else {
+        0.0
+    }
+}
 
-    let total_lines = String::from_utf8_lossy(&total_output);
-    let mut total_lines_changed = 0;
+/// Appended to the names of authors whose counts include uncommitted changes.
+pub const WORKING_TREE_MARKER: &str = "✎";
+
+impl Contribution {
+    /// Author name as shown in tables, marked when it includes uncommitted work.
+    pub fn display_author(&self) -> String {
+        if self.working_tree {
+            format!("{} {}", self.author, WORKING_TREE_MARKER)
+        } else {
+            self.author.clone()
+        }
+    }
+
+    /// This author's activity per top-level directory under `basis`.
+    pub fn directory_activity(&self, basis: OwnershipBasis) -> &HashMap<String, u32> {
+        match basis {
+            OwnershipBasis::Lines => &self.directory_lines,
+            OwnershipBasis::Commits => &self.directory_commits,
+        }
+    }
+}
+
+/// Total non-merge commits in a repository's analysis.
+pub fn repository_commit_count(contributions: &[Contribution]) -> u32 {
+    contributions.iter().map(|c| c.commits).sum()
+}
+
+/// Message shown in place of a repository's table when it has commits but no
+/// line-level data to compute contributions from.
+pub const NO_LINE_DATA_MESSAGE: &str =
+    "No line-level contribution data — repository may be binary-only or merge-only";
+
+/// False when a repository has commits but every numstat entry was binary
+/// (`-`), so all line counts and percentages are zero.
+pub fn has_line_data(contributions: &[Contribution]) -> bool {
+    let has_commits = contributions.iter().any(|c| c.commits > 0);
+    let lines_changed: u64 = contributions
+        .iter()
+        .map(|c| c.lines_added as u64 + c.lines_deleted as u64)
+        .sum();
+
+    !has_commits || lines_changed > 0
+}
+
+/// The contributor who changed the most lines in a top-level directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryOwner {
+    pub directory: String,
+    pub author: String,
+    pub email: String,
+    /// Owner's share of the directory's lines or commits, per the ownership basis.
+    pub percent: f64,
+}
+
+/// One `git log --numstat` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumstatLine {
+    /// Lines added and deleted; `None` for binary files, which numstat shows as `-`.
+    pub lines: Option<(u32, u32)>,
+    /// Changed path with `/` separators, in numstat's rename syntax if renamed.
+    pub path: String,
+}
 
-    for line in total_lines.lines() {
-        if let Some((added, deleted, _)) = line.split_whitespace().collect_tuple() {
-            if added != "-" && deleted != "-" {
-                if let (Ok(a), Ok(d)) = (added.parse::<u32>(), deleted.parse::<u32>()) {
-                    total_lines_changed += a + d;
+/// Parses a numstat line, `added<TAB>deleted<TAB>path`. Only the first two tabs
+/// separate fields, so paths keep any spaces; a trailing `\r` from CRLF output
+/// is dropped and Windows `\` separators become `/`. Paths git quoted because
+/// of special characters are unquoted first. Lines that are not numstat
+/// entries (commit headers, blank lines) give `None`.
+pub fn parse_numstat_line(line: &str) -> Option<NumstatLine> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let mut fields = line.splitn(3, '\t');
+    let (added, deleted, path) = (fields.next()?, fields.next()?, fields.next()?);
+    if path.is_empty() {
+        return None;
+    }
+
+    let lines = match (added.parse::<u32>(), deleted.parse::<u32>()) {
+        (Ok(added), Ok(deleted)) => Some((added, deleted)),
+        _ if added == "-" && deleted == "-" => None,
+        _ => return None,
+    };
+
+    let path = match path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    {
+        Some(quoted) => unquote_path(quoted),
+        None => path.replace('\\', "/"),
+    };
+
+    Some(NumstatLine { lines, path })
+}
+
+/// Undoes git's C-style path quoting (the part between the quotes): `\t`,
+/// `\n`, `\"`, `\\` and friends, plus `\NNN` octal bytes for non-ASCII names.
+fn unquote_path(quoted: &str) -> String {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.bytes().peekable();
+
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0c),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'v') => bytes.push(0x0b),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(next @ b'0'..=b'7') => {
+                            value = value * 8 + (next - b'0') as u32;
+                            chars.next();
+                        }
+                        _ => break,
+                    }
                 }
+                bytes.push(value as u8);
             }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
         }
     }
 
-    let authors_output = Command::new("git")
-        .args(["log", "--no-merges", "--format=%ae|%an"])
-        .current_dir(repo_path)
-        .output()?
-        .stdout;
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Returns the first path component of a numstat path, following renames
+/// (`old => new`) to their new location. Root-level files have no directory.
+pub fn top_level_directory(path: &str) -> Option<&str> {
+    let path = match path.split_once(" => ") {
+        Some((_, new_path)) if !path.contains('{') => new_path,
+        _ => path,
+    };
+    let (directory, _) = path.split_once('/')?;
 
-    let authors = String::from_utf8_lossy(&authors_output);
+    (!directory.is_empty() && !directory.starts_with('{')).then_some(directory)
+}
+
+/// Language of files with no known extension.
+pub const OTHER_LANGUAGE: &str = "Other";
+
+/// Languages by lowercase file extension, or by whole file name for files
+/// that usually have none.
+const LANGUAGES: [(&str, &str); 58] = [
+    ("rs", "Rust"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("mts", "TypeScript"),
+    ("cts", "TypeScript"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("py", "Python"),
+    ("pyi", "Python"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("scala", "Scala"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hh", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("swift", "Swift"),
+    ("m", "Objective-C"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("erl", "Erlang"),
+    ("hs", "Haskell"),
+    ("clj", "Clojure"),
+    ("lua", "Lua"),
+    ("dart", "Dart"),
+    ("zig", "Zig"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("css", "CSS"),
+    ("scss", "CSS"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("md", "Markdown"),
+    ("rst", "reStructuredText"),
+    ("json", "JSON"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("toml", "TOML"),
+    ("xml", "XML"),
+    ("proto", "Protocol Buffers"),
+    ("tf", "Terraform"),
+    ("dockerfile", "Dockerfile"),
+    ("makefile", "Makefile"),
+    ("cmake", "CMake"),
+];
+
+/// The language of a numstat path at its current name, from its extension,
+/// or `OTHER_LANGUAGE` when it has none that is known.
+pub fn language_of(path: &str) -> &'static str {
+    let path = current_path(path);
+    let file_name = path.rsplit('/').next().unwrap_or_default().to_lowercase();
+    let key = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension,
+        _ => &file_name,
+    };
+    LANGUAGES
+        .iter()
+        .find(|(extension, _)| *extension == key)
+        .map_or(OTHER_LANGUAGE, |(_, language)| language)
+}
 
-    let mut author_map = HashMap::new();
+/// Lines of one language across every analyzed repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageFootprint {
+    pub language: String,
+    /// Lines added plus lines deleted in this language's files.
+    pub lines_changed: u32,
+    /// Lines of this language's files at `HEAD`; only with `--blame`.
+    pub surviving_lines: u32,
+    /// Repositories with any changed or surviving lines in this language.
+    pub repositories: usize,
+}
 
-    for line in authors.lines() {
-        if let Some((email, name)) = line.split_once('|') {
-            author_map
-                .entry(email.to_string())
-                .or_insert_with(|| name.to_string());
+/// Totals every contribution's lines per language, largest first: by
+/// surviving lines when they were counted, then by lines changed.
+pub fn language_footprint(
+    contributions_map: &HashMap<String, Vec<Contribution>>,
+) -> Vec<LanguageFootprint> {
+    let mut totals: HashMap<&str, (u32, u32, HashSet<&str>)> = HashMap::new();
+    for (repository, contributions) in contributions_map {
+        for contrib in contributions {
+            for (language, lines) in &contrib.language_lines {
+                let total = totals.entry(language).or_default();
+                total.0 += lines;
+                total.2.insert(repository);
+            }
+            for (file, lines) in &contrib.file_lines {
+                let total = totals.entry(language_of(file)).or_default();
+                total.1 += lines;
+                total.2.insert(repository);
+            }
         }
     }
 
-    for (email, name) in author_map {
-        let commits = Command::new("git")
-            .args(["log", "--no-merges", "--author", &email, "--format=%H"])
-            .current_dir(repo_path)
-            .output()?
-            .stdout;
+    totals
+        .into_iter()
+        .map(
+            |(language, (lines_changed, surviving_lines, repositories))| LanguageFootprint {
+                language: language.to_string(),
+                lines_changed,
+                surviving_lines,
+                repositories: repositories.len(),
+            },
+        )
+        .filter(|footprint| footprint.lines_changed > 0 || footprint.surviving_lines > 0)
+        .sorted_by(|a, b| {
+            b.surviving_lines
+                .cmp(&a.surviving_lines)
+                .then(b.lines_changed.cmp(&a.lines_changed))
+                .then_with(|| a.language.cmp(&b.language))
+        })
+        .collect()
+}
 
-        let commit_count = String::from_utf8_lossy(&commits).lines().count() as u32;
+/// Resolves a numstat path to the file's current name, following both rename
+/// forms: `old => new` and `dir/{old => new}/file`.
+pub fn current_path(path: &str) -> String {
+    match (path.find('{'), path.find('}')) {
+        (Some(open), Some(close)) if open < close => {
+            let inner = &path[open + 1..close];
+            let new_part = inner.split_once(" => ").map_or(inner, |(_, new)| new);
+            let joined = format!("{}{}{}", &path[..open], new_part, &path[close + 1..]);
+            joined.replace("//", "/")
+        }
+        _ => path
+            .split_once(" => ")
+            .map_or(path, |(_, new)| new)
+            .to_string(),
+    }
+}
 
-        let stats_output = Command::new("git")
-            .args([
-                "log",
-                "--no-merges",
-                "--author",
-                &email,
-                "--numstat",
-                "--pretty=format:",
-            ])
-            .current_dir(repo_path)
-            .output()?
-            .stdout;
+/// Finds the primary contributor of every top-level directory in a repository,
+/// sorted by directory name.
+pub fn directory_owners(
+    contributions: &[Contribution],
+    basis: OwnershipBasis,
+) -> Vec<DirectoryOwner> {
+    let mut directory_totals: HashMap<&str, u32> = HashMap::new();
+    let mut owners: HashMap<&str, (&Contribution, u32)> = HashMap::new();
 
-        let stats_str = String::from_utf8_lossy(&stats_output);
+    for contrib in contributions {
+        for (directory, lines) in contrib.directory_activity(basis) {
+            *directory_totals.entry(directory).or_insert(0) += lines;
 
-        let mut lines_added = 0;
-        let mut lines_deleted = 0;
+            let owner = owners.entry(directory).or_insert((contrib, *lines));
+            if *lines > owner.1 || (*lines == owner.1 && contrib.email < owner.0.email) {
+                *owner = (contrib, *lines);
+            }
+        }
+    }
 
-        for line in stats_str.lines() {
-            if line.is_empty() {
-                continue;
+    owners
+        .into_iter()
+        .map(|(directory, (contrib, lines))| {
+            let total = directory_totals[directory];
+            DirectoryOwner {
+                directory: directory.to_string(),
+                author: contrib.author.clone(),
+                email: contrib.email.clone(),
+                percent: if total > 0 {
+                    (lines as f64 / total as f64) * 100.0
+                } else {
+                    0.0
+                },
             }
+        })
+        .sorted_by(|a, b| a.directory.cmp(&b.directory))
+        .collect()
+}
 
-            if let Some((added, deleted, _)) = line.split_whitespace().collect_tuple() {
-                if added != "-" && deleted != "-" {
-                    if let (Ok(a), Ok(d)) = (added.parse::<u32>(), deleted.parse::<u32>()) {
-                        lines_added += a;
-                        lines_deleted += d;
-                    }
+/// Default share a single contributor must reach for a directory not to count
+/// as orphaned.
+pub const DEFAULT_ORPHAN_THRESHOLD: f64 = 30.0;
+
+/// Contributors listed in an orphaned directory's ownership spread.
+const ORPHAN_SPREAD_LIMIT: usize = 3;
+
+/// A top-level directory where nobody holds more than the orphan threshold,
+/// so no one clearly owns it.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanedDirectory {
+    pub repository: String,
+    pub directory: String,
+    pub contributors: usize,
+    /// The largest shares of the directory's lines or commits, biggest first,
+    /// as (author, percent).
+    pub top_shares: Vec<(String, f64)>,
+}
+
+impl OrphanedDirectory {
+    /// Largest single share, i.e. how close the directory came to having an owner.
+    pub fn top_percent(&self) -> f64 {
+        self.top_shares.first().map_or(0.0, |(_, percent)| *percent)
+    }
+
+    /// The largest shares as text, e.g. "Ann 28.00%, Bo 25.00%".
+    pub fn ownership_spread(&self, precision: usize) -> String {
+        self.top_shares
+            .iter()
+            .map(|(author, percent)| format!("{} {}", author, format_percent(*percent, precision)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Finds top-level directories in every repository where no contributor's
+/// share under `basis` exceeds `threshold` percent, most diffuse first.
+pub fn orphaned_directories(
+    contributions_map: &HashMap<String, Vec<Contribution>>,
+    basis: OwnershipBasis,
+    threshold: f64,
+) -> Vec<OrphanedDirectory> {
+    let mut orphaned = Vec::new();
+
+    for (repository, contributions) in contributions_map {
+        let mut shares: HashMap<&str, Vec<(&str, u32)>> = HashMap::new();
+        for contrib in contributions {
+            for (directory, activity) in contrib.directory_activity(basis) {
+                if *activity > 0 {
+                    shares
+                        .entry(directory)
+                        .or_default()
+                        .push((&contrib.author, *activity));
                 }
             }
         }
 
-        let lines_changed = lines_added + lines_deleted;
-        let contribution_percent = if total_lines_changed > 0 {
-            (lines_changed as f64 / total_lines_changed as f64) * 100.0
-        } else {
-            0.0
-        };
+        for (directory, mut authors) in shares {
+            let total: u32 = authors.iter().map(|(_, activity)| activity).sum();
+            authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
 
-        contributions.push(Contribution {
-            author: name,
-            email,
-            commits: commit_count,
-            lines_added,
-            lines_deleted,
-            contribution_percent,
-            repository: repo_name.clone(),
-        });
+            let top_shares: Vec<(String, f64)> = authors
+                .iter()
+                .take(ORPHAN_SPREAD_LIMIT)
+                .map(|(author, activity)| {
+                    (author.to_string(), *activity as f64 / total as f64 * 100.0)
+                })
+                .collect();
+
+            if top_shares[0].1 <= threshold {
+                orphaned.push(OrphanedDirectory {
+                    repository: repository.clone(),
+                    directory: directory.to_string(),
+                    contributors: authors.len(),
+                    top_shares,
+                });
+            }
+        }
     }
 
-    contributions.sort_by(|a, b| {
-        b.contribution_percent
-            .partial_cmp(&a.contribution_percent)
-            .unwrap()
+    orphaned.sort_by(|a, b| {
+        a.top_percent()
+            .total_cmp(&b.top_percent())
+            .then_with(|| a.repository.cmp(&b.repository))
+            .then_with(|| a.directory.cmp(&b.directory))
     });
+    orphaned
+}
+
+/// Default days without a commit after which an author counts as inactive
+/// (likely departed) when scoring files by inactive ownership.
+pub const DEFAULT_INACTIVE_DAYS: u32 = 90;
+
+/// Files listed by `inactive_owner_risk`, highest risk first.
+pub const RISK_FILE_LIMIT: usize = 50;
 
-    Ok((repo_name, contributions))
+/// A file at `HEAD` whose surviving lines were mostly written by authors who
+/// have stopped committing, so it may have no active maintainer.
+#[derive(Debug, Clone, Serialize)]
+pub struct AtRiskFile {
+    pub repository: String,
+    pub path: String,
+    /// Surviving lines in the file.
+    pub lines: u32,
+    /// Share of those lines whose author is inactive: the risk score.
+    pub risk_percent: f64,
+    /// Inactive author owning the most lines of the file.
+    pub inactive_owner: String,
+    /// Days since that author's last commit; `None` if they have no commits
+    /// left in the history, e.g. when they were rewritten or excluded.
+    pub inactive_owner_days: Option<u64>,
 }
 
-pub fn calculate_author_summaries(
+impl AtRiskFile {
+    /// When the main inactive owner last committed, e.g. "412 days ago".
+    pub fn inactivity(&self) -> String {
+        match self.inactive_owner_days {
+            Some(days) => format!("{} days ago", days),
+            None => String::from("no commits"),
+        }
+    }
+}
+
+/// A file's surviving lines while `inactive_owner_risk` adds up its owners.
+#[derive(Default)]
+struct FileOwnership<'a> {
+    lines: u32,
+    inactive_lines: u32,
+    /// Inactive author with the most lines so far, and those lines.
+    main_inactive_owner: Option<(&'a Contribution, u32)>,
+}
+
+/// Scores every blamed file by the share of its surviving lines owned by
+/// authors with no commit in the last `inactive_days` days before `now`, and
+/// returns the riskiest (up to `RISK_FILE_LIMIT`), larger files first among
+/// equal scores. Empty unless the analysis ran with blame.
+pub fn inactive_owner_risk(
     contributions_map: &HashMap<String, Vec<Contribution>>,
-) -> Vec<AuthorSummary> {
-    let mut author_data: HashMap<String, (String, String, u32, u32, u32, HashMap<String, f64>)> =
-        HashMap::new();
-    let mut total_lines_changed_all_repos = 0;
+    inactive_days: u32,
+    now: i64,
+) -> Vec<AtRiskFile> {
+    let cutoff = now - i64::from(inactive_days) * SECONDS_PER_DAY as i64;
+    let mut files = Vec::new();
 
-    for (repo_name, contributions) in contributions_map {
+    for (repository, contributions) in contributions_map {
+        let mut ownership: HashMap<&str, FileOwnership> = HashMap::new();
         for contrib in contributions {
-            let email = &contrib.email;
-            let author_name = &contrib.author;
-            let lines_changed = contrib.lines_added + contrib.lines_deleted;
+            let inactive = contrib.last_commit_at.is_none_or(|last| last < cutoff);
+            for (path, lines) in &contrib.file_lines {
+                let entry = ownership.entry(path).or_default();
+                entry.lines += lines;
+                if inactive {
+                    entry.inactive_lines += lines;
+                    if entry
+                        .main_inactive_owner
+                        .is_none_or(|(_, owned)| *lines > owned)
+                    {
+                        entry.main_inactive_owner = Some((contrib, *lines));
+                    }
+                }
+            }
+        }
+
+        for (path, file) in ownership {
+            if let (true, Some((owner, _))) = (file.lines > 0, file.main_inactive_owner) {
+                files.push(AtRiskFile {
+                    repository: repository.clone(),
+                    path: path.to_string(),
+                    lines: file.lines,
+                    risk_percent: file.inactive_lines as f64 / file.lines as f64 * 100.0,
+                    inactive_owner: owner.author.clone(),
+                    inactive_owner_days: owner
+                        .last_commit_at
+                        .map(|last| ((now - last).max(0) as f64 / SECONDS_PER_DAY) as u64),
+                });
+            }
+        }
+    }
+
+    files.sort_by(|a, b| {
+        b.risk_percent
+            .total_cmp(&a.risk_percent)
+            .then_with(|| b.lines.cmp(&a.lines))
+            .then_with(|| a.repository.cmp(&b.repository))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    files.truncate(RISK_FILE_LIMIT);
+    files
+}
+
+/// Words ignored when building commit-subject term counts: English filler plus
+/// the verbs nearly every commit starts with, which say nothing about the work.
+pub const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "in", "into", "is",
+    "it", "its", "of", "on", "or", "so", "that", "the", "this", "to", "was", "were", "when",
+    "with", "without", "not", "no", "all", "some", "more", "less", "now", "also", "only", "via",
+    "add", "added", "adds", "adding", "update", "updated", "updates", "fix", "fixed", "fixes",
+    "remove", "removed", "removes", "change", "changed", "changes", "use", "make", "made", "move",
+    "moved", "bump", "wip", "minor", "misc", "small", "initial", "commit", "merge", "branch",
+];
+
+/// Issue and pull request references recognised in commit subjects: `#1234`
+/// (including `Fixes #1234`, `Closes #1234`, ...) and `GH-1234`. The number is
+/// captured in group 1.
+pub const ISSUE_REFERENCE_PATTERN: &str = r"(?:#|\bGH-)(\d+)\b";
+
+/// Splits a commit subject into lowercase words worth counting, dropping stop
+/// words, numbers and anything shorter than three characters.
+pub fn subject_terms(subject: &str) -> impl Iterator<Item = String> + '_ {
+    subject
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| {
+            word.chars().count() >= 3
+                && !word.chars().all(|c| c.is_numeric())
+                && !STOP_WORDS.contains(&word.as_str())
+        })
+}
+
+/// Returns the `limit` most frequent terms, ties broken alphabetically.
+pub fn top_terms(terms: &HashMap<String, u32>, limit: usize) -> Vec<(&str, u32)> {
+    terms
+        .iter()
+        .map(|(term, count)| (term.as_str(), *count))
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)))
+        .take(limit)
+        .collect()
+}
+
+/// Repository-level facts gathered alongside the per-author contributions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepositoryInfo {
+    /// The repository is a shallow clone, so its history (and every count
+    /// derived from it) is incomplete.
+    pub shallow: bool,
+    /// `--since` resolved to a Unix timestamp, when a window start was given.
+    pub window_start: Option<i64>,
+    /// Revision range the analysis was limited to, for display.
+    pub revision_range: Option<String>,
+    /// `--last` when the repository had more commits than that, so only its
+    /// most recent ones were counted.
+    pub last_commits: Option<u32>,
+    /// `--trend-window` resolved to a Unix timestamp, when one was given.
+    pub trend_start: Option<i64>,
+    /// Median time between consecutive commits in the window; `None` with
+    /// fewer than two commits.
+    pub median_commit_gap: Option<Duration>,
+}
+
+/// Settings that change which history `analyze_repository` looks at.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptions {
+    /// Only count commits more recent than this date (any `git log --since` value).
+    pub since: Option<String>,
+    /// Only count commits older than this date (any `git log --until` value).
+    pub until: Option<String>,
+    /// Also count merge commits per author in a separate pass. Merges never
+    /// contribute to commit or line totals.
+    pub include_merges: bool,
+    /// Which identity on each commit receives the credit.
+    pub identity: Identity,
+    /// Only analyze commits in this tag range, e.g. `v1.0..v2.0` or `v1.0..HEAD`.
+    pub tag_range: Option<TagRange>,
+    /// Only analyze commits on `HEAD` that this ref (usually the branch a
+    /// feature branch will merge into) does not have, i.e. `<base>..HEAD`.
+    pub base: Option<String>,
+    /// Also blame every text file at `HEAD` to count each author's surviving lines.
+    pub blame: bool,
+    /// Also check each commit's signature to count signed commits per author.
+    pub signatures: bool,
+    /// Gitignore-style patterns for paths left out of every count.
+    pub excludes: Vec<String>,
+    /// Only count commits touching this one path, relative to the repository
+    /// root; the totals percentages are measured against shrink to match.
+    pub file: Option<String>,
+    /// Start of the recent window (any `git log --since` value) whose commit
+    /// rate is compared with each author's overall rate.
+    pub trend_since: Option<String>,
+    /// Email or name substrings of people left out of the analysis entirely,
+    /// including the line totals their percentages would be measured against.
+    pub exclude_authors: Vec<String>,
+    /// Also credit uncommitted changes to the configured `user.email`.
+    pub include_working_tree: bool,
+    /// Treat emails differing only in case or surrounding whitespace as one
+    /// author, shown under the form used most often.
+    pub normalize_emails: bool,
+    /// Lowercase commit hashes, full or abbreviated, left out of every count.
+    pub ignore_commits: Vec<String>,
+    /// Only count each repository's most recent this many non-merge commits
+    /// within the window, range and paths, for a quick look at a long history.
+    pub last: Option<u32>,
+    /// Full hashes of the only commits counted; set per repository by
+    /// `limited_to_last`, so leave it `None`.
+    pub only_commits: Option<HashSet<String>>,
+    /// Details to keep for every commit in `Contribution::commit_records`.
+    pub commit_fields: Vec<CommitField>,
+    /// Keep every commit's hash, date, subject and line counts in
+    /// `Contribution::kept_commits`.
+    pub keep_commits: bool,
+    /// How long the git commands for one repository may take altogether
+    /// before they are killed and the repository fails as timed out.
+    pub timeout: Option<Duration>,
+    /// Once set, running git commands are killed and no new ones start, so
+    /// the repository being analyzed fails as cancelled.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// How much added and deleted lines count towards `contribution_percent`.
+    pub line_weights: LineWeights,
+    /// Directory to save each repository's whole `git log --numstat` output
+    /// in, byte for byte; see `raw_dump_path`.
+    pub dump_raw: Option<PathBuf>,
+}
 
-            total_lines_changed_all_repos += lines_changed;
+/// How much an added and a deleted line each count towards contribution
+/// percentages. Both weigh 1.0 by default, so every changed line counts the
+/// same and the percentages are plain shares of lines changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineWeights {
+    pub added: f64,
+    pub deleted: f64,
+}
 
-            let entry = author_data
-                .entry(email.clone())
-                .or_insert_with(|| (author_name.clone(), email.clone(), 0, 0, 0, HashMap::new()));
+impl Default for LineWeights {
+    fn default() -> Self {
+        LineWeights {
+            added: 1.0,
+            deleted: 1.0,
+        }
+    }
+}
 
-            entry.2 += contrib.commits;
-            entry.3 += contrib.lines_added;
-            entry.4 += contrib.lines_deleted;
-            entry
-                .5
-                .insert(repo_name.clone(), contrib.contribution_percent);
+impl LineWeights {
+    /// Checks that neither weight is negative and that they are not both zero,
+    /// which would leave nothing to measure shares against.
+    pub fn new(added: f64, deleted: f64) -> Result<LineWeights, Box<dyn Error + Send>> {
+        let valid = |weight: f64| weight.is_finite() && weight >= 0.0;
+        if !valid(added) || !valid(deleted) || added + deleted == 0.0 {
+            return Err(Box::new(std::io::Error::other(format!(
+                "Invalid line weights {} (added) and {} (deleted): both must be zero or more, and not both zero",
+                added, deleted
+            ))));
         }
+        Ok(LineWeights { added, deleted })
     }
 
-    let mut summaries = Vec::new();
+    /// Weighted size of a change.
+    pub fn weigh(self, added: u32, deleted: u32) -> f64 {
+        added as f64 * self.added + deleted as f64 * self.deleted
+    }
 
-    for (email, (author, _, commits, lines_added, lines_deleted, repo_percentages)) in author_data {
-        let total_lines_changed = lines_added + lines_deleted;
-        let overall_percent = if total_lines_changed_all_repos > 0 {
-            (total_lines_changed as f64 / total_lines_changed_all_repos as f64) * 100.0
+    /// Weighted size of `added`/`deleted` as a percentage of the weighted totals.
+    pub fn percent(self, added: u32, deleted: u32, total_added: u32, total_deleted: u32) -> f64 {
+        let total = self.weigh(total_added, total_deleted);
+        if total > 0.0 {
+            self.weigh(added, deleted) / total * 100.0
         } else {
             0.0
+        }
+    }
+}
+
+/// The `git` pathspec for exactly one path from the repository root, with no
+/// glob characters; a leading `./` or `/` is dropped.
+pub fn file_pathspec(file: &str) -> String {
+    let file = file.trim_start_matches("./").trim_start_matches('/');
+    format!(":(top,literal){}", file)
+}
+
+/// Converts one gitignore-style pattern into `git` exclude pathspecs. As in
+/// `.gitignore`, a pattern with no slash (other than a trailing one) matches at
+/// any depth, a leading `/` anchors it to the repository root, a trailing `/`
+/// matches only directories, and a matched directory excludes everything in it.
+pub fn exclude_pathspecs(pattern: &str) -> Vec<String> {
+    let directory_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+
+    let pattern = if anchored || pattern.starts_with("**/") {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+
+    let mut pathspecs = vec![format!(":(exclude,glob){}/**", pattern)];
+    if !directory_only {
+        pathspecs.push(format!(":(exclude,glob){}", pattern));
+    }
+    pathspecs
+}
+
+/// Reads commit hashes to ignore, one per line. Blank lines and `#` comments
+/// are skipped, and anything after the hash (such as a note on why it is
+/// ignored) is too, so `git blame --ignore-revs-file` files work as they are.
+/// Hashes may be abbreviated to as few as 4 hex digits.
+pub fn read_ignore_commits_file(path: &Path) -> Result<Vec<String>, Box<dyn Error + Send>> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to read ignored commits file {}: {}",
+            path.display(),
+            e
+        ))) as Box<dyn Error + Send>
+    })?;
+
+    let mut hashes = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let hash = match line.split_whitespace().next() {
+            Some(hash) if !hash.starts_with('#') => hash,
+            _ => continue,
         };
+        if hash.len() < 4 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Box::new(std::io::Error::other(format!(
+                "{}:{}: '{}' is not a commit hash",
+                path.display(),
+                index + 1,
+                hash
+            ))));
+        }
+        hashes.push(hash.to_lowercase());
+    }
 
-        let mut preferred_repo = String::new();
-        let mut highest_percent = 0.0;
+    Ok(hashes)
+}
 
-        for (repo, percent) in &repo_percentages {
-            if *percent > highest_percent {
-                highest_percent = *percent;
-                preferred_repo = repo.clone();
-            }
+/// Reads exclusion patterns from a gitignore-syntax file, skipping blank lines
+/// and `#` comments. Negated (`!`) patterns cannot be expressed as exclusions
+/// and are rejected.
+pub fn read_excludes_file(path: &Path) -> Result<Vec<String>, Box<dyn Error + Send>> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to read excludes file {}: {}",
+            path.display(),
+            e
+        ))) as Box<dyn Error + Send>
+    })?;
+
+    let mut patterns = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('!') {
+            return Err(Box::new(std::io::Error::other(format!(
+                "{}:{}: negated pattern '{}' is not supported",
+                path.display(),
+                index + 1,
+                line
+            ))));
+        }
+        // `\#` and `\!` escape a literal leading character.
+        let line = line
+            .strip_prefix('\\')
+            .filter(|rest| rest.starts_with(['#', '!']))
+            .unwrap_or(line);
+        patterns.push(line.to_string());
+    }
+
+    Ok(patterns)
+}
+
+/// Reads review counts from a CSV of `email,review_count` rows, such as one
+/// exported from GitHub or GitLab. Blank lines, `#` comments and a header row
+/// (a first row without an email) are skipped, fields may be quoted and
+/// further columns are ignored. Emails are lowercased and trimmed, and
+/// repeated ones add up.
+pub fn read_reviews_csv(path: &Path) -> Result<HashMap<String, u32>, Box<dyn Error + Send>> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to read reviews file {}: {}",
+            path.display(),
+            e
+        ))) as Box<dyn Error + Send>
+    })?;
+
+    let mut reviews = HashMap::new();
+    let mut first_row = true;
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
+        let fields = csv_fields(line);
+        let header = std::mem::replace(&mut first_row, false);
+        let (email, count) = match fields.as_slice() {
+            [email, count, ..] => (email.trim(), count.trim()),
+            _ => {
+                return Err(Box::new(std::io::Error::other(format!(
+                    "{}:{}: expected email,review_count",
+                    path.display(),
+                    index + 1
+                ))))
+            }
+        };
+        let count = match count.parse::<u32>() {
+            Ok(count) => count,
+            Err(_) if header && !email.contains('@') => continue,
+            Err(_) => {
+                return Err(Box::new(std::io::Error::other(format!(
+                    "{}:{}: '{}' is not a review count",
+                    path.display(),
+                    index + 1,
+                    count
+                ))))
+            }
+        };
+        *reviews.entry(email.to_lowercase()).or_insert(0) += count;
+    }
 
-        summaries.push(AuthorSummary {
-            author,
-            email,
-            total_commits: commits,
-            total_lines_added: lines_added,
-            total_lines_deleted: lines_deleted,
-            overall_contribution_percent: overall_percent,
-            preferred_repo,
-            preferred_repo_percent: highest_percent,
-        });
+    Ok(reviews)
+}
+
+/// Splits one CSV line into fields, unquoting `"..."` fields and their `""`
+/// escapes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
     }
+    fields.push(field);
+    fields
+}
 
-    summaries.sort_by(|a, b| {
-        b.overall_contribution_percent
-            .partial_cmp(&a.overall_contribution_percent)
-            .unwrap()
-    });
+/// A release range between two tags; `until` of `None` means `HEAD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRange {
+    pub from: String,
+    pub until: Option<String>,
+}
 
-    summaries
+impl TagRange {
+    /// Parses `<from>..<until>` as given to `--between`.
+    pub fn parse(range: &str) -> Result<TagRange, Box<dyn Error + Send>> {
+        match range.split_once("..") {
+            Some((from, until)) if !from.is_empty() && !until.is_empty() => Ok(TagRange {
+                from: from.to_string(),
+                until: Some(until.to_string()),
+            }),
+            _ => Err(Box::new(std::io::Error::other(format!(
+                "Invalid tag range '{}', expected <tag>..<tag>",
+                range
+            )))),
+        }
+    }
+
+    /// The `git log` revision range.
+    pub fn revision_range(&self) -> String {
+        format!("{}..{}", self.from, self.until.as_deref().unwrap_or("HEAD"))
+    }
+
+    fn tags(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.from.as_str()).chain(self.until.as_deref())
+    }
+}
+
+/// The identity a commit is attributed to. The author wrote the change; the
+/// committer applied it, which differs after rebases, cherry-picks, or when a
+/// maintainer commits someone else's patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Identity {
+    #[default]
+    Author,
+    Committer,
+}
+
+/// Separates the fields of `Identity` formats. Unlike `|`, which turns up in
+/// display names, git field values cannot contain this control character.
+const FIELD_SEPARATOR: char = '\x1f';
+
+impl Identity {
+    /// `git log` format producing hash, email and name for this identity,
+    /// separated by `FIELD_SEPARATOR`.
+    fn format(self) -> &'static str {
+        match self {
+            Identity::Author => "--format=%H%x1f%ae%x1f%an",
+            Identity::Committer => "--format=%H%x1f%ce%x1f%cn",
+        }
+    }
+
+    /// `git log` format producing hash, email and Unix timestamp for this
+    /// identity, separated by `FIELD_SEPARATOR`.
+    fn timestamp_format(self) -> &'static str {
+        match self {
+            Identity::Author => "--format=%H%x1f%ae%x1f%at",
+            Identity::Committer => "--format=%H%x1f%ce%x1f%ct",
+        }
+    }
+
+    /// `--numstat` header of each commit: a record separator and the hash,
+    /// followed by this identity's timestamp and the subject when commits
+    /// are kept, separated by `FIELD_SEPARATOR`.
+    fn numstat_format(self, keep_commits: bool) -> &'static str {
+        match (keep_commits, self) {
+            (false, _) => "--pretty=format:%x1e%H",
+            (true, Identity::Author) => "--pretty=format:%x1e%H%x1f%at%x1f%s",
+            (true, Identity::Committer) => "--pretty=format:%x1e%H%x1f%ct%x1f%s",
+        }
+    }
+
+    /// `git log` flag restricting history to one person under this identity.
+    fn filter_flag(self) -> &'static str {
+        match self {
+            Identity::Author => "--author",
+            Identity::Committer => "--committer",
+        }
+    }
+
+    /// `git log` format starting each commit with a record separator line
+    /// followed by the fields of `format`.
+    fn record_format(self) -> &'static str {
+        match self {
+            Identity::Author => "--format=%x1e%H%x1f%ae%x1f%an",
+            Identity::Committer => "--format=%x1e%H%x1f%ce%x1f%cn",
+        }
+    }
+
+    /// Header line carrying this identity's email in `git blame --line-porcelain`.
+    fn blame_mail_prefix(self) -> &'static str {
+        match self {
+            Identity::Author => "author-mail <",
+            Identity::Committer => "committer-mail <",
+        }
+    }
+}
+
+impl AnalysisOptions {
+    /// The key an email groups under: lowercased and trimmed with
+    /// `normalize_emails`, otherwise the email itself.
+    pub fn email_key(&self, email: &str) -> String {
+        if self.normalize_emails {
+            email.trim().to_lowercase()
+        } else {
+            email.to_string()
+        }
+    }
+
+    /// `git log` arguments restricting history to the author grouped under `key`.
+    /// The email is matched literally: as a regex, the `[bot]` in GitHub App
+    /// emails would be a character class and match none of their commits.
+    fn author_filter_args(&self, key: &str) -> Vec<String> {
+        let mut args = vec![
+            self.identity.filter_flag().to_string(),
+            key.to_string(),
+            String::from("--fixed-strings"),
+        ];
+        if self.normalize_emails {
+            args.push(String::from("--regexp-ignore-case"));
+        }
+        args
+    }
+
+    /// Whether `--exclude-author` drops this person; matching is a
+    /// case-insensitive substring test against both name and email.
+    pub fn excludes_author(&self, name: &str, email: &str) -> bool {
+        let name = name.to_lowercase();
+        let email = email.to_lowercase();
+        self.exclude_authors.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            name.contains(&pattern) || email.contains(&pattern)
+        })
+    }
+
+    /// Whether `--ignore-commits` lists this commit, by full hash or prefix,
+    /// or it falls outside `only_commits`.
+    pub fn ignores_commit(&self, hash: &str) -> bool {
+        self.ignore_commits
+            .iter()
+            .any(|ignored| hash.starts_with(ignored.as_str()))
+            || self
+                .only_commits
+                .as_ref()
+                .is_some_and(|only| !only.contains(hash))
+    }
+
+    /// These options with `last` resolved for one repository, and whether
+    /// that cut its history short. When it did, the most recent `last`
+    /// commits become `only_commits` and `since` moves up to the oldest of
+    /// their commit dates, so every later `git log` stops walking there
+    /// instead of reading the whole history.
+    pub fn limited_to_last(
+        &self,
+        repo_path: &Path,
+    ) -> Result<(AnalysisOptions, bool), Box<dyn Error>> {
+        let last = match self.last {
+            Some(last) => last as usize,
+            None => return Ok((self.clone(), false)),
+        };
+        let output = run_git(
+            git_command(repo_path)
+                .args(LOG_CONFIG_ARGS)
+                .args(["log", "--no-merges", "--format=%H %ct"])
+                .arg(format!("--max-count={}", last + 1))
+                .args(self.log_args()),
+        )?;
+
+        let commits: Vec<(&str, i64)> = std::str::from_utf8(&output)?
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter_map(|(hash, timestamp)| Some((hash, timestamp.parse().ok()?)))
+            .collect();
+        if commits.len() <= last {
+            return Ok((self.clone(), false));
+        }
+        let commits = &commits[..last];
+        Ok((
+            AnalysisOptions {
+                since: commits
+                    .iter()
+                    .map(|(_, timestamp)| *timestamp)
+                    .min()
+                    .map(|oldest| format!("@{}", oldest))
+                    .or_else(|| self.since.clone()),
+                only_commits: Some(commits.iter().map(|(hash, _)| hash.to_string()).collect()),
+                ..self.clone()
+            },
+            true,
+        ))
+    }
+
+    /// The fields after the hash in a line of an `Identity` format, or
+    /// `None` when that commit is ignored.
+    fn kept_commit<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let (hash, rest) = line.split_once(FIELD_SEPARATOR)?;
+        (!self.ignores_commit(hash)).then_some(rest)
+    }
+
+    /// Extra arguments appended to every `git log` call.
+    fn log_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(since) = &self.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(until) = &self.until {
+            args.push(format!("--until={}", until));
+        }
+        args.extend(self.revision_range());
+        args.extend(self.pathspec_args());
+        args
+    }
+
+    /// The `git log` revision range from the tag range or base ref, if any.
+    pub(crate) fn revision_range(&self) -> Option<String> {
+        match (&self.tag_range, &self.base) {
+            (Some(tag_range), _) => Some(tag_range.revision_range()),
+            (None, Some(base)) => Some(format!("{}..HEAD", base)),
+            (None, None) => None,
+        }
+    }
+
+    /// `-- <pathspec>...` for the file and exclusions, or nothing without any.
+    fn pathspec_args(&self) -> Vec<String> {
+        if self.file.is_none() && self.excludes.is_empty() {
+            return Vec::new();
+        }
+        std::iter::once("--".to_string())
+            .chain(self.file.iter().map(|file| file_pathspec(file)))
+            .chain(self.excludes.iter().flat_map(|p| exclude_pathspecs(p)))
+            .collect()
+    }
+
+    /// Parses a `since..until` window; either side may be left empty.
+    pub fn from_window(window: &str) -> Result<AnalysisOptions, Box<dyn Error + Send>> {
+        let (since, until) = window.split_once("..").ok_or_else(|| {
+            Box::new(std::io::Error::other(format!(
+                "Invalid time window '{}', expected <since>..<until>",
+                window
+            ))) as Box<dyn Error + Send>
+        })?;
+        let bound = |s: &str| (!s.trim().is_empty()).then(|| s.trim().to_string());
+
+        Ok(AnalysisOptions {
+            since: bound(since),
+            until: bound(until),
+            ..Default::default()
+        })
+    }
+}
+
+/// Config passed to every `git log` so non-ASCII paths in `--numstat` output come
+/// through verbatim instead of as quoted octal escapes (`"src/\303\251.rs"`).
+const LOG_CONFIG_ARGS: [&str; 2] = ["-c", "core.quotepath=false"];
+
+/// Attempts made for a git command that keeps failing on a held lock file.
+const GIT_LOCK_ATTEMPTS: u32 = 4;
+/// Wait before the first retry; doubled after every further failure.
+const GIT_LOCK_BACKOFF: Duration = Duration::from_millis(100);
+
+/// How often a running git command checks whether it should be killed.
+const GIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The git binary every command runs, set once by `set_git_executable`.
+static GIT_EXECUTABLE: OnceLock<PathBuf> = OnceLock::new();
+
+/// When the git commands of the current thread have to stop; set from
+/// `AnalysisOptions` by `with_git_limits` and handed on to the blame workers.
+#[derive(Debug, Clone, Default)]
+struct GitLimits {
+    deadline: Option<(Instant, Duration)>,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl GitLimits {
+    fn is_unlimited(&self) -> bool {
+        self.deadline.is_none() && self.cancel.is_none()
+    }
+
+    /// Fails once the analysis is cancelled or past its deadline.
+    fn check(&self) -> Result<(), Box<dyn Error>> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            return Err("analysis cancelled".into());
+        }
+        match self.deadline {
+            Some((deadline, timeout)) if Instant::now() >= deadline => {
+                Err(format!("git timed out after {}s", timeout.as_secs()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+thread_local! {
+    static GIT_LIMITS: RefCell<GitLimits> = RefCell::new(GitLimits::default());
+}
+
+/// Runs `f` with its git commands limited by `options.timeout` and
+/// `options.cancel`, restoring the previous limits afterwards.
+pub(crate) fn with_git_limits<T>(options: &AnalysisOptions, f: impl FnOnce() -> T) -> T {
+    let limits = GitLimits {
+        deadline: options
+            .timeout
+            .map(|timeout| (Instant::now() + timeout, timeout)),
+        cancel: options.cancel.clone(),
+    };
+    let previous = GIT_LIMITS.with(|current| current.replace(limits));
+    let result = f();
+    GIT_LIMITS.with(|current| current.replace(previous));
+    result
+}
+
+/// Picks the git binary: an explicit path (`--git-path`), else the `GIT`
+/// environment variable, else `git` from `PATH`. It is checked by running
+/// `git --version` so a wrong path fails up front rather than per repository.
+/// Only the first call takes effect.
+pub fn set_git_executable(path: Option<PathBuf>) -> Result<(), Box<dyn Error + Send>> {
+    let executable = path
+        .or_else(|| std::env::var_os("GIT").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("git"));
+
+    let works = Command::new(&executable)
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !works {
+        return Err(Box::new(std::io::Error::other(format!(
+            "git executable '{}' not found or not working",
+            executable.display()
+        ))));
+    }
+
+    let _ = GIT_EXECUTABLE.set(executable);
+    Ok(())
+}
+
+/// A git command to run inside `repo_dir`, using the configured git binary.
+pub fn git_command(repo_dir: &Path) -> Command {
+    let mut command = Command::new(
+        GIT_EXECUTABLE
+            .get()
+            .map_or_else(|| Path::new("git"), PathBuf::as_path),
+    );
+    command.current_dir(repo_dir);
+    command
+}
+
+/// Runs a command to completion like `Command::output`, but kills it as soon
+/// as the current `GitLimits` say so. Both pipes are drained on their own
+/// threads, so the child never stalls on a full pipe while this one waits.
+pub(crate) fn git_output(command: &mut Command) -> Result<Output, Box<dyn Error>> {
+    let limits = GIT_LIMITS.with(|current| current.borrow().clone());
+    if limits.is_unlimited() {
+        return Ok(command.output()?);
+    }
+    limits.check()?;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let read_all = |pipe: Option<Box<dyn Read + Send>>| {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    };
+    let stderr_pipe = child
+        .stderr
+        .take()
+        .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>);
+    let stderr = thread::spawn(move || read_all(stderr_pipe));
+    let stdout_pipe = child
+        .stdout
+        .take()
+        .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(read_all(stdout_pipe)));
+
+    let stdout = loop {
+        match receiver.recv_timeout(GIT_POLL_INTERVAL) {
+            Ok(stdout) => break stdout,
+            Err(RecvTimeoutError::Disconnected) => break Vec::new(),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(e) = limits.check() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(e);
+                }
+            }
+        }
+    };
+
+    Ok(Output {
+        status: child.wait()?,
+        stdout,
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Runs a git command and returns its stdout. Git fails fast when another
+/// process holds a lock such as `index.lock`, which is common on machines where
+/// the repository is in active use, so those failures are retried with
+/// exponential backoff before giving up with a clear error. Any other failure
+/// is returned as-is through the (usually empty) stdout, as before.
+fn run_git(command: &mut Command) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut backoff = GIT_LOCK_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        let output = git_output(command)?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if output.status.success() || !stderr.contains(".lock") {
+            return Ok(output.stdout);
+        }
+        if attempt == GIT_LOCK_ATTEMPTS {
+            return Err(format!(
+                "git is still blocked by a lock file after {} attempts: {}",
+                GIT_LOCK_ATTEMPTS,
+                stderr.trim()
+            )
+            .into());
+        }
+
+        thread::sleep(backoff);
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// The directory a repository sits in, relative to the analyzed parent path
+/// and with `/` separators, e.g. `org-a` for `<path>/org-a/api`; `.` for
+/// repositories directly inside the parent path.
+pub fn repository_group(parent_path: &Path, repo_path: &Path) -> String {
+    repo_path
+        .parent()
+        .and_then(|directory| directory.strip_prefix(parent_path).ok())
+        .map(|relative| {
+            relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .join("/")
+        })
+        .filter(|group| !group.is_empty())
+        .unwrap_or_else(|| String::from("."))
+}
+
+/// Names repositories are keyed and shown by: the directory name, or the path
+/// relative to the analyzed parent path (with `/` separators) when another
+/// repository has the same directory name, e.g. `team-a/api` and `team-b/api`.
+pub fn repository_keys(parent_path: &Path, repositories: &[PathBuf]) -> Vec<String> {
+    let file_name = |path: &Path| {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+    let name_counts = repositories.iter().map(|path| file_name(path)).counts();
+    repositories
+        .iter()
+        .map(|path| {
+            let name = file_name(path);
+            if name_counts[&name] < 2 {
+                return name;
+            }
+            match path.strip_prefix(parent_path) {
+                Ok(relative) if !relative.as_os_str().is_empty() => relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .join("/"),
+                _ => path.to_string_lossy().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Order repositories are analyzed in and their tabs are shown in (`--order`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RepositoryOrder {
+    /// Alphabetically by name.
+    #[default]
+    Name,
+    /// Most commits first.
+    Commits,
+    /// Most recently committed to first.
+    Recent,
+    /// Most lines added and deleted first.
+    Size,
+}
+
+impl RepositoryOrder {
+    /// Cheap stand-in for `sort_key` before a repository is analyzed, so the
+    /// most interesting ones are done first if analysis is cancelled. Size is
+    /// estimated by commit count; `None` when the backend cannot tell.
+    fn estimate(self, repo_path: &Path, options: &AnalysisOptions) -> Option<i64> {
+        let backend = crate::vcs::backend_for(repo_path);
+        match self {
+            RepositoryOrder::Name => None,
+            RepositoryOrder::Commits | RepositoryOrder::Size => backend
+                .reference_commit_count(repo_path, options)
+                .ok()
+                .map(i64::from),
+            RepositoryOrder::Recent => backend.latest_commit_at(repo_path, options).ok().flatten(),
+        }
+    }
+
+    /// What an analyzed repository is ranked by, highest first.
+    pub fn sort_key(self, contributions: &[Contribution]) -> i64 {
+        match self {
+            RepositoryOrder::Name => 0,
+            RepositoryOrder::Commits => i64::from(repository_commit_count(contributions)),
+            RepositoryOrder::Recent => contributions
+                .iter()
+                .filter_map(|c| c.last_commit_at)
+                .max()
+                .unwrap_or(i64::MIN),
+            RepositoryOrder::Size => contributions
+                .iter()
+                .map(|c| i64::from(c.lines_added) + i64::from(c.lines_deleted))
+                .sum(),
+        }
+    }
+
+    /// Sorts discovered repositories into analysis order by `estimate`, with
+    /// those it cannot tell about last. Name order keeps discovery order.
+    pub fn arrange(self, repositories: &mut [PathBuf], options: &AnalysisOptions) {
+        if self == RepositoryOrder::Name {
+            return;
+        }
+        let estimates: HashMap<PathBuf, Option<i64>> = repositories
+            .iter()
+            .map(|path| (path.clone(), self.estimate(path, options)))
+            .collect();
+        repositories.sort_by_key(|path| std::cmp::Reverse(estimates[path]));
+    }
+}
+
+/// Commit time of HEAD, for `--order recent` before the repository is
+/// analyzed; `None` for a repository without commits.
+pub fn latest_commit_at(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<Option<i64>, Box<dyn Error>> {
+    let mut command = git_command(repo_path);
+    command.args(["log", "-1", "--format=%ct", "HEAD"]);
+    let output = with_git_limits(options, || run_git(&mut command))?;
+    Ok(String::from_utf8_lossy(&output).trim().parse().ok())
+}
+
+pub fn is_git_repository(path: &Path) -> bool {
+    let git_dir = path.join(".git");
+    git_dir.exists() && git_dir.is_dir()
+}
+
+/// Checks that a repository pattern is a usable glob that stays inside the
+/// parent path. Patterns are always interpreted relative to `--path`, so
+/// absolute patterns (which `join` would let replace the parent entirely) and
+/// `..` components are rejected, as are empty and malformed patterns.
+pub fn validate_repository_pattern(pattern: &str) -> Result<(), Box<dyn Error + Send>> {
+    if pattern.trim().is_empty() {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Invalid pattern '': patterns may not be empty",
+        )));
+    }
+    if let Err(e) = Pattern::new(pattern) {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid pattern '{}': {}", pattern, e),
+        )));
+    }
+
+    let escapes_parent = pattern.starts_with('/')
+        || pattern.starts_with('\\')
+        || Path::new(pattern).components().any(|component| {
+            matches!(
+                component,
+                Component::RootDir | Component::Prefix(_) | Component::ParentDir
+            )
+        });
+
+    if escapes_parent {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid pattern '{}': patterns are relative to --path and may not be absolute or contain '..'",
+                pattern
+            ),
+        )));
+    }
+
+    Ok(())
+}
+
+/// Expands each of `patterns` under `parent_path` and keeps the git
+/// repositories, in path order and once each however many patterns match
+/// them. Paths that could not be read are described in `errors` and skipped.
+pub fn find_repositories(
+    parent_path: &Path,
+    patterns: &[String],
+    errors: &mut Vec<String>,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send>> {
+    let mut repositories = Vec::new();
+    for pattern in patterns {
+        validate_repository_pattern(pattern)?;
+
+        let pattern_path = parent_path.join(pattern);
+        let pattern_str = pattern_path.to_string_lossy().to_string();
+
+        for entry in glob(&pattern_str).map_err(|e| Box::new(e) as Box<dyn Error + Send>)? {
+            match entry {
+                Ok(path) => {
+                    if path.is_dir() && crate::vcs::detect_backend(&path).is_some() {
+                        repositories.push(path);
+                    }
+                }
+                Err(e) => errors.push(format!("Error matching path: {}", e)),
+            }
+        }
+    }
+
+    repositories.sort();
+    repositories.dedup();
+    Ok(repositories)
+}
+
+/// Recursively discovers repositories under `parent_path` whose path relative to
+/// `parent_path` matches `path_pattern` (e.g. `**/service-*` or `team-a/*`).
+///
+/// Unlike `find_repositories`, the pattern is matched against the relative path
+/// rather than expanded by the filesystem, so `**` spans any number of
+/// directories. Discovery does not descend into a repository once one is found.
+/// Directories that could not be read are described in `errors` and skipped.
+pub fn find_repositories_by_path(
+    parent_path: &Path,
+    path_pattern: &str,
+    errors: &mut Vec<String>,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send>> {
+    validate_repository_pattern(path_pattern)?;
+
+    let pattern = Pattern::new(path_pattern).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    let mut repositories = Vec::new();
+    let mut pending = vec![parent_path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                errors.push(format!("Error reading directory {}: {}", dir.display(), e));
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if !is_dir || crate::vcs::is_metadata_dir(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+
+            let path = entry.path();
+            if crate::vcs::detect_backend(&path).is_some() {
+                if let Ok(relative) = path.strip_prefix(parent_path) {
+                    if pattern.matches_path_with(relative, options) {
+                        repositories.push(path);
+                    }
+                }
+            } else {
+                pending.push(path);
+            }
+        }
+    }
+
+    repositories.sort();
+    Ok(repositories)
+}
+
+pub fn is_shallow_repository(repo_path: &Path) -> Result<bool, Box<dyn Error>> {
+    let output = run_git(git_command(repo_path).args(["rev-parse", "--is-shallow-repository"]))?;
+
+    Ok(String::from_utf8_lossy(&output).trim() == "true")
+}
+
+/// Resolves a `--since` value (anything git accepts, e.g. "3 months ago") to a
+/// Unix timestamp using git's own date parser.
+pub fn resolve_since(repo_path: &Path, since: &str) -> Result<Option<i64>, Box<dyn Error>> {
+    let output = run_git(
+        git_command(repo_path)
+            .arg("rev-parse")
+            .arg(format!("--since={}", since)),
+    )?;
+
+    Ok(String::from_utf8_lossy(&output)
+        .trim()
+        .strip_prefix("--max-age=")
+        .and_then(|timestamp| timestamp.parse().ok()))
+}
+
+pub fn tag_exists(repo_path: &Path, tag: &str) -> Result<bool, Box<dyn Error>> {
+    let output = git_output(
+        git_command(repo_path)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/tags/{}", tag)),
+    )?;
+
+    Ok(output.status.success())
+}
+
+/// Whether `rev` names a commit, e.g. a branch, tag or hash.
+pub fn commit_exists(repo_path: &Path, rev: &str) -> Result<bool, Box<dyn Error>> {
+    let output = git_output(
+        git_command(repo_path)
+            .args(["rev-parse", "--verify", "--quiet", "--end-of-options"])
+            .arg(format!("{}^{{commit}}", rev)),
+    )?;
+
+    Ok(output.status.success())
+}
+
+pub fn is_bare_repository(repo_path: &Path) -> Result<bool, Box<dyn Error>> {
+    let output = run_git(git_command(repo_path).args(["rev-parse", "--is-bare-repository"]))?;
+
+    Ok(String::from_utf8_lossy(&output).trim() == "true")
+}
+
+/// Reads a `git config` value, or `None` when it is unset.
+fn git_config(repo_path: &Path, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let output = git_output(git_command(repo_path).args(["config", key]))?;
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((output.status.success() && !value.is_empty()).then_some(value))
+}
+
+/// Counts the commits `analyze_repository` should credit, straight from
+/// `git rev-list` with the same window, range, paths, excluded authors and
+/// ignored commits, as an independent check on the log parsing.
+pub fn reference_commit_count(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<u32, Box<dyn Error>> {
+    let (options, _) = with_git_limits(options, || options.limited_to_last(repo_path))?;
+    let options = &options;
+    let mut command = git_command(repo_path);
+    command
+        .args(LOG_CONFIG_ARGS)
+        .args(["rev-list", "--no-merges", options.identity.format()]);
+    if options.revision_range().is_none() {
+        command.arg("HEAD");
+    }
+    let output = with_git_limits(options, || run_git(command.args(options.log_args())))?;
+
+    // Each commit is a `commit <hash>` header followed by the format line.
+    let count = String::from_utf8_lossy(&output)
+        .lines()
+        .filter(|line| !line.starts_with("commit "))
+        .filter_map(|line| options.kept_commit(line))
+        .filter_map(|line| line.split_once(FIELD_SEPARATOR))
+        .filter(|(email, name)| !options.excludes_author(name, email))
+        .count();
+    Ok(count as u32)
+}
+
+/// A repository's analyzed commit total next to `reference_commit_count`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitReconciliation {
+    pub repository: String,
+    /// `None` when `git rev-list` failed.
+    pub reference_commits: Option<u32>,
+    /// Commits credited by the analysis, not counting uncommitted changes.
+    pub analyzed_commits: u32,
+}
+
+impl CommitReconciliation {
+    pub fn difference(&self) -> Option<i64> {
+        self.reference_commits
+            .map(|reference| self.analyzed_commits as i64 - reference as i64)
+    }
+
+    /// Whether the totals differ by at most `tolerance` percent of the
+    /// reference count.
+    pub fn is_within(&self, tolerance: f64) -> bool {
+        match (self.reference_commits, self.difference()) {
+            (Some(reference), Some(difference)) => {
+                difference.unsigned_abs() as f64 <= reference as f64 * tolerance / 100.0
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Uncommitted changes, staged and unstaged, as numstat entries, or `None` for
+/// a bare repository, which has no working tree.
+pub fn working_tree_changes(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<Option<Vec<NumstatLine>>, Box<dyn Error>> {
+    if is_bare_repository(repo_path)? {
+        return Ok(None);
+    }
+
+    let mut changes = Vec::new();
+    for staged in [false, true] {
+        let mut command = git_command(repo_path);
+        command.args(LOG_CONFIG_ARGS).args(["diff", "--numstat"]);
+        if staged {
+            command.arg("--cached");
+        }
+        let output = run_git(command.args(options.pathspec_args()))?;
+
+        changes.extend(
+            String::from_utf8_lossy(&output)
+                .lines()
+                .filter_map(parse_numstat_line),
+        );
+    }
+
+    Ok(Some(changes))
+}
+
+/// The spelling used most often, ties going to the alphabetically first.
+pub(crate) fn most_frequent_form(forms: &HashMap<&str, u32>) -> String {
+    forms
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(form, _)| form.to_string())
+        .unwrap_or_default()
+}
+
+/// Makes every contribution of an author use the same spelling of their email
+/// across repositories (and across maps, e.g. a comparison baseline): the one
+/// behind the most commits. Without this, repositories that each preferred a
+/// different casing would split the author apart in summaries.
+pub fn unify_email_forms(
+    contribution_maps: &mut [&mut HashMap<String, Vec<Contribution>>],
+    options: &AnalysisOptions,
+) {
+    let mut forms: HashMap<String, HashMap<String, u32>> = HashMap::new();
+    for contrib in contribution_maps
+        .iter()
+        .flat_map(|map| map.values().flatten())
+    {
+        *forms
+            .entry(options.email_key(&contrib.email))
+            .or_default()
+            .entry(contrib.email.clone())
+            .or_insert(0) += contrib.commits.max(1);
+    }
+
+    let preferred: HashMap<String, String> = forms
+        .into_iter()
+        .map(|(key, forms)| {
+            let forms = forms
+                .iter()
+                .map(|(form, count)| (form.as_str(), *count))
+                .collect();
+            (key, most_frequent_form(&forms))
+        })
+        .collect();
+
+    for map in contribution_maps.iter_mut() {
+        for contrib in map.values_mut().flatten() {
+            if let Some(email) = preferred.get(&options.email_key(&contrib.email)) {
+                contrib.email = email.clone();
+            }
+        }
+    }
+}
+
+/// Distinct emails `fuzzy_merge_authors` compares pairwise at most; beyond
+/// this the pass is skipped instead of taking quadratic time.
+pub const FUZZY_MERGE_AUTHOR_LIMIT: usize = 2000;
+
+/// Authors `fuzzy_merge_authors` or `merge_bots` folded into one, for the
+/// user to check.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorMerge {
+    /// Name and email the variants now appear under.
+    pub author: String,
+    pub email: String,
+    /// Emails merged into `email`, sorted.
+    pub variants: Vec<String>,
+}
+
+impl AuthorMerge {
+    /// e.g. "jane@exmaple.com → Jane Doe <jane@example.com>".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} → {} <{}>",
+            self.variants.join(", "),
+            self.author,
+            self.email
+        )
+    }
+}
+
+/// Edit distance between `a` and `b` if it is at most `limit`, giving up as
+/// soon as it cannot be.
+fn bounded_edit_distance(a: &str, b: &str, limit: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > limit {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(a_char != b_char))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        if current
+            .iter()
+            .min()
+            .is_some_and(|&distance| distance > limit)
+        {
+            return None;
+        }
+        previous = current;
+    }
+
+    Some(previous[b.len()]).filter(|&distance| distance <= limit)
+}
+
+/// Whether two authors are likely the same person with a typo: the same
+/// mailbox at domains two edits apart (`jane@exmaple.com`), mailboxes of five
+/// or more characters one edit apart at the same domain, or the same full name
+/// (one edit apart from twelve characters on).
+fn similar_authors(a: (&str, &str), b: (&str, &str)) -> bool {
+    let (a_email, a_name) = a;
+    let (b_email, b_name) = b;
+    if let (Some((a_local, a_domain)), Some((b_local, b_domain))) =
+        (a_email.rsplit_once('@'), b_email.rsplit_once('@'))
+    {
+        if a_local == b_local && a_local.chars().count() >= 3 {
+            if bounded_edit_distance(a_domain, b_domain, 2).is_some() {
+                return true;
+            }
+        } else if a_domain == b_domain
+            && a_local.chars().count().min(b_local.chars().count()) >= 5
+            && bounded_edit_distance(a_local, b_local, 1).is_some()
+        {
+            return true;
+        }
+    }
+    let name_limit = if a_name.chars().count() >= 12 { 1 } else { 0 };
+    a_name.contains(' ') && bounded_edit_distance(a_name, b_name, name_limit).is_some()
+}
+
+/// Root of `i`'s cluster in a union-find `parent` array, halving the path on
+/// the way.
+fn cluster_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Folds authors whose emails or names look like typos of each other (see
+/// `similar_authors`) into the variant with the most commits, across every
+/// map so a comparison baseline stays aligned, merging their rows within each
+/// repository. Returns the merges made, or an error without touching anything
+/// when there are more than `FUZZY_MERGE_AUTHOR_LIMIT` emails to compare.
+pub fn fuzzy_merge_authors(
+    contribution_maps: &mut [&mut HashMap<String, Vec<Contribution>>],
+) -> Result<Vec<AuthorMerge>, String> {
+    // Email -> (name, commits), with the name of the row with most commits.
+    let mut authors: HashMap<&str, (&str, u32)> = HashMap::new();
+    for contrib in contribution_maps
+        .iter()
+        .flat_map(|map| map.values().flatten())
+    {
+        let entry = authors
+            .entry(contrib.email.as_str())
+            .or_insert((contrib.author.as_str(), 0));
+        if contrib.commits > entry.1 {
+            entry.0 = contrib.author.as_str();
+        }
+        entry.1 += contrib.commits;
+    }
+    if authors.len() > FUZZY_MERGE_AUTHOR_LIMIT {
+        return Err(format!(
+            "--fuzzy-merge skipped: {} authors is more than the {} it compares",
+            authors.len(),
+            FUZZY_MERGE_AUTHOR_LIMIT
+        ));
+    }
+
+    let authors: Vec<(&str, &str, u32)> = authors
+        .into_iter()
+        .map(|(email, (name, commits))| (email, name, commits))
+        .sorted()
+        .collect();
+    let keys: Vec<(String, String)> = authors
+        .iter()
+        .map(|(email, name, _)| (email.to_lowercase(), name.trim().to_lowercase()))
+        .collect();
+
+    // Union-find over `authors`, each cluster rooted at its first member.
+    let mut parent: Vec<usize> = (0..authors.len()).collect();
+    for i in 0..authors.len() {
+        for j in i + 1..authors.len() {
+            let a = (keys[i].0.as_str(), keys[i].1.as_str());
+            let b = (keys[j].0.as_str(), keys[j].1.as_str());
+            if similar_authors(a, b) {
+                let (root_i, root_j) = (cluster_root(&mut parent, i), cluster_root(&mut parent, j));
+                parent[root_i.max(root_j)] = root_i.min(root_j);
+            }
+        }
+    }
+
+    let mut clusters: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..authors.len() {
+        let cluster = cluster_root(&mut parent, i);
+        clusters.entry(cluster).or_default().push(i);
+    }
+
+    let mut merges = Vec::new();
+    let mut canonical: HashMap<String, (String, String)> = HashMap::new();
+    for members in clusters.into_values().filter(|members| members.len() > 1) {
+        let Some(&keep) = members.iter().max_by(|&&a, &&b| {
+            authors[a]
+                .2
+                .cmp(&authors[b].2)
+                .then(authors[b].0.cmp(authors[a].0))
+        }) else {
+            continue;
+        };
+        let (email, author, _) = authors[keep];
+        let variants: Vec<String> = members
+            .iter()
+            .filter(|&&i| i != keep)
+            .map(|&i| authors[i].0.to_string())
+            .collect();
+        for variant in &variants {
+            canonical.insert(variant.clone(), (author.to_string(), email.to_string()));
+        }
+        merges.push(AuthorMerge {
+            author: author.to_string(),
+            email: email.to_string(),
+            variants,
+        });
+    }
+
+    reassign_authors(contribution_maps, |contrib| {
+        canonical.get(&contrib.email).cloned()
+    });
+
+    Ok(merges)
+}
+
+/// Renames every row `rename` gives a new (author, email), then folds rows
+/// of one repository that now share an email into one and ranks them again.
+fn reassign_authors(
+    contribution_maps: &mut [&mut HashMap<String, Vec<Contribution>>],
+    rename: impl Fn(&Contribution) -> Option<(String, String)>,
+) {
+    for map in contribution_maps.iter_mut() {
+        for contributions in map.values_mut() {
+            let mut merged: Vec<Contribution> = Vec::with_capacity(contributions.len());
+            for mut contrib in contributions.drain(..) {
+                if let Some((author, email)) = rename(&contrib) {
+                    contrib.author = author;
+                    contrib.email = email;
+                }
+                match merged.iter_mut().find(|row| row.email == contrib.email) {
+                    Some(row) => row.absorb(contrib),
+                    None => merged.push(contrib),
+                }
+            }
+
+            merged.sort_by(|a, b| b.contribution_percent.total_cmp(&a.contribution_percent));
+            let percents: Vec<f64> = merged.iter().map(|c| c.contribution_percent).collect();
+            for (contrib, rank) in merged.iter_mut().zip(competition_ranks(&percents)) {
+                contrib.rank = rank;
+            }
+            *contributions = merged;
+        }
+    }
+}
+
+/// Name and email of the row `merge_bots` folds automation accounts into.
+pub const AUTOMATION_AUTHOR: &str = "Automation";
+pub const AUTOMATION_EMAIL: &str = "automation@bots.invalid";
+
+/// Service accounts recognized by their whole name or email local part.
+const BOT_ACCOUNTS: [&str; 9] = [
+    "dependabot",
+    "renovate",
+    "github-actions",
+    "greenkeeper",
+    "pre-commit-ci",
+    "mergify",
+    "semantic-release-bot",
+    "weblate",
+    "bot",
+];
+
+/// Whether an author looks like an automation account: a GitHub App
+/// (`dependabot[bot]`, including its `123+dependabot[bot]@users.noreply.github.com`
+/// email), a known service account, or a name or email local part ending in
+/// `-bot`, `_bot` or ` bot`. Case is ignored.
+pub fn is_bot(author: &str, email: &str) -> bool {
+    let author = author.trim().to_lowercase();
+    let local = email.split('@').next().unwrap_or_default().to_lowercase();
+    let local = local
+        .split_once('+')
+        .map_or(local.as_str(), |(_, rest)| rest);
+    [author.as_str(), local].iter().any(|name| {
+        BOT_ACCOUNTS.contains(name)
+            || ["[bot]", "-bot", "_bot", " bot"]
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+    })
+}
+
+/// Folds every author `is_bot` matches into one `AUTOMATION_AUTHOR` row per
+/// repository (`--merge-bots`), so automated churn stays visible as a single
+/// author instead of being excluded. Returns the bots folded, if any.
+pub fn merge_bots(
+    contribution_maps: &mut [&mut HashMap<String, Vec<Contribution>>],
+) -> Option<AuthorMerge> {
+    let bots: Vec<String> = contribution_maps
+        .iter()
+        .flat_map(|map| map.values().flatten())
+        .filter(|contrib| is_bot(&contrib.author, &contrib.email))
+        .map(|contrib| contrib.email.clone())
+        .sorted()
+        .dedup()
+        .collect();
+    if bots.is_empty() {
+        return None;
+    }
+
+    reassign_authors(contribution_maps, |contrib| {
+        is_bot(&contrib.author, &contrib.email)
+            .then(|| (AUTOMATION_AUTHOR.to_string(), AUTOMATION_EMAIL.to_string()))
+    });
+
+    Some(AuthorMerge {
+        author: AUTOMATION_AUTHOR.to_string(),
+        email: AUTOMATION_EMAIL.to_string(),
+        variants: bots,
+    })
+}
+
+/// Where `--dump-raw` saves a repository's log: `<dir>/<parent>/<name>.log`,
+/// named after the directory holding the repository too, so repositories
+/// sharing a name under different parents keep their own dumps.
+pub fn raw_dump_path(dir: &Path, repo_path: &Path) -> PathBuf {
+    let name = repo_path.file_name().unwrap_or_default().to_string_lossy();
+    let dir = match repo_path.parent().and_then(Path::file_name) {
+        Some(parent) => dir.join(parent),
+        None => dir.to_path_buf(),
+    };
+    dir.join(format!("{}.log", name))
+}
+
+pub fn analyze_repository(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<(String, Vec<Contribution>, RepositoryInfo), Box<dyn Error>> {
+    let repo_name = repo_path
+        .file_name()
+        .ok_or("Invalid repository path")?
+        .to_string_lossy()
+        .to_string();
+
+    if let Some(tag_range) = &options.tag_range {
+        for tag in tag_range.tags() {
+            if !tag_exists(repo_path, tag)? {
+                return Err(format!("tag '{}' not found, skipping repository", tag).into());
+            }
+        }
+    }
+    if let Some(base) = &options.base {
+        if !commit_exists(repo_path, base)? {
+            return Err(format!("base '{}' not found, skipping repository", base).into());
+        }
+    }
+
+    // The first and last commit dates and merge counts look past `--last`.
+    let unlimited = options;
+    let (options, limited) = unlimited.limited_to_last(repo_path)?;
+    let options = &options;
+
+    let mut contributions = Vec::new();
+    let issue_pattern = Regex::new(ISSUE_REFERENCE_PATTERN)?;
+    let mut info = RepositoryInfo {
+        shallow: is_shallow_repository(repo_path)?,
+        window_start: match &options.since {
+            Some(since) => resolve_since(repo_path, since)?,
+            None => None,
+        },
+        revision_range: options.revision_range(),
+        last_commits: options.last.filter(|_| limited),
+        trend_start: match &options.trend_since {
+            Some(since) => resolve_since(repo_path, since)?,
+            None => None,
+        },
+        ..Default::default()
+    };
+
+    let total_output = run_git(
+        git_command(repo_path)
+            .args(LOG_CONFIG_ARGS)
+            .args([
+                "log",
+                "--no-merges",
+                "--numstat",
+                options.identity.record_format(),
+            ])
+            .args(options.log_args()),
+    )?;
+    if let Some(dir) = &options.dump_raw {
+        let path = raw_dump_path(dir, repo_path);
+        fs::create_dir_all(path.parent().unwrap_or(dir))?;
+        fs::write(path, &total_output)?;
+    }
+
+    let total_lines = String::from_utf8_lossy(&total_output);
+    let mut total_added = 0;
+    let mut total_deleted = 0;
+    let mut excluded_commit = false;
+
+    // Ignored commits and excluded authors' lines leave the denominator too,
+    // so everyone else's percentages still add up to 100.
+    for line in total_lines.lines().map(|line| line.trim_end_matches('\r')) {
+        if let Some(record) = line.strip_prefix('\x1e') {
+            excluded_commit = options.kept_commit(record).is_none_or(|person| {
+                person
+                    .split_once(FIELD_SEPARATOR)
+                    .is_some_and(|(email, name)| options.excludes_author(name, email))
+            });
+            continue;
+        }
+        if excluded_commit {
+            continue;
+        }
+        if let Some((added, deleted)) = parse_numstat_line(line).and_then(|entry| entry.lines) {
+            total_added += added;
+            total_deleted += deleted;
+        }
+    }
+
+    let authors_output = run_git(
+        git_command(repo_path)
+            .args(LOG_CONFIG_ARGS)
+            .args(["log", "--no-merges", options.identity.format()])
+            .args(options.log_args()),
+    )?;
+
+    let authors = String::from_utf8_lossy(&authors_output);
+
+    // Grouping key -> (name, how often each spelling of the email was used).
+    let mut author_map: HashMap<String, (String, HashMap<&str, u32>)> = HashMap::new();
+
+    for line in authors.lines().filter_map(|line| options.kept_commit(line)) {
+        if let Some((email, name)) = line.split_once(FIELD_SEPARATOR) {
+            if options.excludes_author(name, email) {
+                continue;
+            }
+            let (_, forms) = author_map
+                .entry(options.email_key(email))
+                .or_insert_with(|| (name.to_string(), HashMap::new()));
+            *forms.entry(email).or_insert(0) += 1;
+        }
+    }
+
+    // Subjects feed the terms and issues. Checking a signature runs gpg or
+    // ssh-keygen per signed commit, so its status is only asked for if needed.
+    let mut commit_fields = vec![CommitField::Subject];
+    if options.signatures {
+        commit_fields.push(CommitField::Signature);
+    }
+    for field in &options.commit_fields {
+        if !commit_fields.contains(field) {
+            commit_fields.push(*field);
+        }
+    }
+    let commit_format = commit_record_format(&commit_fields);
+    // Without an allowed signers file git reports SSH-signed commits as
+    // unsigned; an empty one still checks them, as of unknown validity.
+    let mut signature_args = Vec::new();
+    if commit_fields.contains(&CommitField::Signature)
+        && git_config(repo_path, "gpg.ssh.allowedSignersFile")?.is_none()
+    {
+        signature_args.extend(["-c", "gpg.ssh.allowedSignersFile=/dev/null"]);
+    }
+
+    for (key, (name, forms)) in author_map {
+        let email = most_frequent_form(&forms);
+        let commits = run_git(
+            git_command(repo_path)
+                .args(LOG_CONFIG_ARGS)
+                .args(&signature_args)
+                .args(["log", "--no-merges"])
+                .args(options.author_filter_args(&key))
+                .arg(&commit_format)
+                .args(options.log_args()),
+        )?;
+
+        let commits = String::from_utf8_lossy(&commits);
+        let mut commit_count = 0;
+        let mut signed_commits = 0;
+        let mut terms = HashMap::new();
+        let mut issues = HashSet::new();
+        let mut commit_records = Vec::new();
+
+        for record in commits.split('\x1e') {
+            let commit = match parse_commit_record(record, &commit_fields) {
+                Some(commit) if !options.ignores_commit(&commit.hash) => commit,
+                _ => continue,
+            };
+            commit_count += 1;
+            if commit.signature.as_deref().is_some_and(is_signed) {
+                signed_commits += 1;
+            }
+            if let Some(subject) = &commit.subject {
+                for term in subject_terms(subject) {
+                    *terms.entry(term).or_insert(0) += 1;
+                }
+                for reference in issue_pattern.captures_iter(subject) {
+                    if let Ok(issue) = reference[1].parse::<u32>() {
+                        issues.insert(issue);
+                    }
+                }
+            }
+            if !options.commit_fields.is_empty() {
+                commit_records.push(commit.retain_fields(&options.commit_fields));
+            }
+        }
+
+        let stats_output = run_git(
+            git_command(repo_path)
+                .args(LOG_CONFIG_ARGS)
+                .args(["log", "--no-merges"])
+                .args(options.author_filter_args(&key))
+                .arg("--numstat")
+                .arg(options.identity.numstat_format(options.keep_commits))
+                .args(options.log_args()),
+        )?;
+
+        let stats_str = String::from_utf8_lossy(&stats_output);
+        let mut ignored_commit = false;
+        let mut kept_commit: Option<KeptCommit> = None;
+        let mut kept_commits = Vec::new();
+
+        let mut lines_added = 0;
+        let mut lines_deleted = 0;
+        let mut directory_lines = HashMap::new();
+        let mut directory_commits = HashMap::new();
+        let mut language_lines = HashMap::new();
+        let mut commit_directories = HashSet::new();
+        let mut files_touched = HashSet::new();
+
+        // Every commit starts with a record separator line and its hash, so
+        // directories can be counted once per commit as well as per changed line.
+        for line in stats_str
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .chain(std::iter::once("\x1e"))
+        {
+            if let Some(header) = line.strip_prefix('\x1e') {
+                for directory in commit_directories.drain() {
+                    *directory_commits.entry(directory).or_insert(0) += 1;
+                }
+                kept_commits.extend(kept_commit.take());
+                let mut fields = header.split(FIELD_SEPARATOR);
+                let hash = fields.next().unwrap_or_default();
+                ignored_commit = options.ignores_commit(hash);
+                if options.keep_commits && !ignored_commit && !hash.is_empty() {
+                    kept_commit = Some(KeptCommit {
+                        hash: hash.to_string(),
+                        timestamp: fields.next().and_then(|t| t.parse().ok()).unwrap_or(0),
+                        subject: fields.next().unwrap_or_default().to_string(),
+                        ..Default::default()
+                    });
+                }
+                continue;
+            }
+            if line.is_empty() || ignored_commit {
+                continue;
+            }
+
+            if let Some(entry) = parse_numstat_line(line) {
+                files_touched.insert(current_path(&entry.path));
+                if let Some((a, d)) = entry.lines {
+                    lines_added += a;
+                    lines_deleted += d;
+                    if let Some(commit) = &mut kept_commit {
+                        commit.lines_added += a;
+                        commit.lines_deleted += d;
+                    }
+                    *language_lines
+                        .entry(language_of(&entry.path).to_string())
+                        .or_insert(0) += a + d;
+                    if let Some(directory) = top_level_directory(&entry.path) {
+                        *directory_lines.entry(directory.to_string()).or_insert(0) += a + d;
+                        commit_directories.insert(directory.to_string());
+                    }
+                }
+            }
+        }
+
+        let contribution_percent =
+            options
+                .line_weights
+                .percent(lines_added, lines_deleted, total_added, total_deleted);
+
+        contributions.push(Contribution {
+            author: name,
+            email,
+            commits: commit_count,
+            signed_commits,
+            lines_added,
+            lines_deleted,
+            contribution_percent,
+            repository: repo_name.clone(),
+            terms,
+            issues,
+            directory_lines,
+            directory_commits,
+            language_lines,
+            files_touched,
+            commit_records,
+            kept_commits,
+            ..Default::default()
+        });
+    }
+
+    if options.include_working_tree {
+        if let (Some(changes), Some(email)) = (
+            working_tree_changes(repo_path, options)?,
+            git_config(repo_path, "user.email")?,
+        ) {
+            let name = git_config(repo_path, "user.name")?.unwrap_or_else(|| email.clone());
+            if !changes.is_empty() && !options.excludes_author(&name, &email) {
+                let key = options.email_key(&email);
+                let index = match contributions
+                    .iter()
+                    .position(|c| options.email_key(&c.email) == key)
+                {
+                    Some(index) => index,
+                    None => {
+                        contributions.push(Contribution {
+                            author: name,
+                            email,
+                            repository: repo_name.clone(),
+                            ..Default::default()
+                        });
+                        contributions.len() - 1
+                    }
+                };
+
+                // All uncommitted work counts as a single pseudo-commit.
+                let contrib = &mut contributions[index];
+                contrib.commits += 1;
+                contrib.working_tree = true;
+                let mut directories = HashSet::new();
+                for change in changes {
+                    contrib.files_touched.insert(current_path(&change.path));
+                    if let Some((added, deleted)) = change.lines {
+                        contrib.lines_added += added;
+                        contrib.lines_deleted += deleted;
+                        total_added += added;
+                        total_deleted += deleted;
+                        *contrib
+                            .language_lines
+                            .entry(language_of(&change.path).to_string())
+                            .or_insert(0) += added + deleted;
+                        if let Some(directory) = top_level_directory(&change.path) {
+                            *contrib
+                                .directory_lines
+                                .entry(directory.to_string())
+                                .or_insert(0) += added + deleted;
+                            directories.insert(directory.to_string());
+                        }
+                    }
+                }
+                for directory in directories {
+                    *contrib.directory_commits.entry(directory).or_insert(0) += 1;
+                }
+
+                // The totals grew, so every share has to be measured again.
+                for contrib in &mut contributions {
+                    contrib.contribution_percent = options.line_weights.percent(
+                        contrib.lines_added,
+                        contrib.lines_deleted,
+                        total_added,
+                        total_deleted,
+                    );
+                }
+            }
+        }
+    }
+
+    let timestamps_output = run_git(git_command(repo_path).args(LOG_CONFIG_ARGS).args([
+        "log",
+        "--no-merges",
+        options.identity.timestamp_format(),
+    ]))?;
+
+    let mut commit_spans: HashMap<String, (i64, i64)> = HashMap::new();
+
+    for line in String::from_utf8_lossy(&timestamps_output)
+        .lines()
+        .filter_map(|line| unlimited.kept_commit(line))
+    {
+        if let Some((email, timestamp)) = line.split_once(FIELD_SEPARATOR) {
+            if let Ok(timestamp) = timestamp.parse::<i64>() {
+                let span = commit_spans
+                    .entry(options.email_key(email))
+                    .or_insert((timestamp, timestamp));
+                span.0 = span.0.min(timestamp);
+                span.1 = span.1.max(timestamp);
+            }
+        }
+    }
+
+    for contrib in &mut contributions {
+        if let Some((first, last)) = commit_spans.get(&options.email_key(&contrib.email)) {
+            contrib.first_commit_at = Some(*first);
+            contrib.last_commit_at = Some(*last);
+        }
+    }
+
+    if options.include_merges {
+        let merges_output = run_git(
+            git_command(repo_path)
+                .args(LOG_CONFIG_ARGS)
+                .args(["log", "--merges", options.identity.format()])
+                .args(options.log_args()),
+        )?;
+
+        // Grouping key -> (name, email as first seen, merges).
+        let mut merge_counts: HashMap<String, (String, String, u32)> = HashMap::new();
+
+        // `--last` counts non-merge commits only, so every merge since the
+        // oldest of them counts.
+        for line in String::from_utf8_lossy(&merges_output)
+            .lines()
+            .filter_map(|line| unlimited.kept_commit(line))
+        {
+            if let Some((email, name)) = line.split_once(FIELD_SEPARATOR) {
+                merge_counts
+                    .entry(options.email_key(email))
+                    .or_insert_with(|| (name.to_string(), email.to_string(), 0))
+                    .2 += 1;
+            }
+        }
+
+        for contrib in &mut contributions {
+            if let Some((_, _, merges)) = merge_counts.remove(&options.email_key(&contrib.email)) {
+                contrib.merges = merges;
+            }
+        }
+
+        // Integrators who only ever merged still get a row.
+        for (name, email, merges) in merge_counts.into_values() {
+            contributions.push(Contribution {
+                author: name,
+                email,
+                repository: repo_name.clone(),
+                merges,
+                ..Default::default()
+            });
+        }
+    }
+
+    if let Some(trend_since) = &options.trend_since {
+        let recent_options = AnalysisOptions {
+            since: Some(trend_since.clone()),
+            ..options.clone()
+        };
+        let recent_output = run_git(
+            git_command(repo_path)
+                .args(LOG_CONFIG_ARGS)
+                .args(["log", "--no-merges", options.identity.format()])
+                .args(recent_options.log_args()),
+        )?;
+
+        let mut recent_counts: HashMap<String, u32> = HashMap::new();
+        let recent = String::from_utf8_lossy(&recent_output);
+        for line in recent.lines().filter_map(|line| options.kept_commit(line)) {
+            if let Some((email, _)) = line.split_once(FIELD_SEPARATOR) {
+                *recent_counts.entry(options.email_key(email)).or_insert(0) += 1;
+            }
+        }
+
+        for contrib in &mut contributions {
+            contrib.recent_commits = recent_counts
+                .get(&options.email_key(&contrib.email))
+                .copied()
+                .unwrap_or(0);
+        }
+    }
+
+    // Unlike the first and last commit dates, calendar days and the median
+    // gap come from the same window, range and paths as the commit counts.
+    let activity_output = run_git(
+        git_command(repo_path)
+            .args(LOG_CONFIG_ARGS)
+            .args(["log", "--no-merges", options.identity.timestamp_format()])
+            .args(options.log_args()),
+    )?;
+
+    // Excluded authors have no row, so only counted authors' commits are gaps.
+    let counted: HashSet<String> = contributions
+        .iter()
+        .map(|contrib| options.email_key(&contrib.email))
+        .collect();
+    let mut commit_times = Vec::new();
+    let mut commit_days: HashMap<String, BTreeMap<chrono::NaiveDate, u32>> = HashMap::new();
+    for line in String::from_utf8_lossy(&activity_output)
+        .lines()
+        .filter_map(|line| options.kept_commit(line))
+    {
+        if let Some((email, timestamp)) = line.split_once(FIELD_SEPARATOR) {
+            let timestamp = timestamp.parse::<i64>().ok();
+            if counted.contains(&options.email_key(email)) {
+                commit_times.extend(timestamp);
+            }
+            if let Some(date) =
+                timestamp.and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            {
+                let day = date.with_timezone(&chrono::Local).date_naive();
+                *commit_days
+                    .entry(options.email_key(email))
+                    .or_default()
+                    .entry(day)
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+
+    for contrib in &mut contributions {
+        if let Some(days) = commit_days.remove(&options.email_key(&contrib.email)) {
+            contrib.commit_days = days;
+        }
+    }
+    info.median_commit_gap = median_duration(commit_gaps(&mut commit_times));
+
+    if options.blame {
+        // Grouping key -> (email as first seen, lines per file).
+        let mut surviving: HashMap<String, (String, FileLines)> = HashMap::new();
+        for (email, files) in surviving_lines(repo_path, options)? {
+            let entry = surviving
+                .entry(options.email_key(&email))
+                .or_insert_with(|| (email, HashMap::new()));
+            for (file, lines) in files {
+                *entry.1.entry(file).or_insert(0) += lines;
+            }
+        }
+
+        for contrib in &mut contributions {
+            if let Some((_, files)) = surviving.remove(&options.email_key(&contrib.email)) {
+                contrib.surviving_lines = files.values().sum();
+                contrib.file_lines = files;
+            }
+        }
+
+        // Authors of surviving code may have no commits in the analyzed window.
+        for (email, files) in surviving.into_values() {
+            let span = commit_spans.get(&options.email_key(&email));
+            contributions.push(Contribution {
+                author: email.clone(),
+                repository: repo_name.clone(),
+                first_commit_at: span.map(|span| span.0),
+                last_commit_at: span.map(|span| span.1),
+                surviving_lines: files.values().sum(),
+                file_lines: files,
+                email,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Merge-only and blame-only rows are added without going through the
+    // author list above.
+    contributions.retain(|c| !options.excludes_author(&c.author, &c.email));
+
+    contributions.sort_by(|a, b| b.contribution_percent.total_cmp(&a.contribution_percent));
+
+    let percents: Vec<f64> = contributions
+        .iter()
+        .map(|c| c.contribution_percent)
+        .collect();
+    for (contrib, rank) in contributions.iter_mut().zip(competition_ranks(&percents)) {
+        contrib.rank = rank;
+    }
+
+    Ok((repo_name, contributions, info))
+}
+
+/// Lists the text files at `HEAD`; binary files have no meaningful lines to blame.
+fn blameable_files(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let output = run_git(
+        git_command(repo_path)
+            .args(["grep", "-I", "-z", "--name-only", "-e", "", "HEAD"])
+            .args(options.pathspec_args()),
+    )?;
+
+    Ok(output
+        .split(|&b| b == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            entry.strip_prefix("HEAD:").unwrap_or(&entry).to_string()
+        })
+        .collect())
+}
+
+/// Conventional list of reformatting commits at a repository's root that
+/// `git blame` should look through (`--ignore-revs-file`).
+pub const BLAME_IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
+
+/// Counts the lines of each file at `HEAD` that `git blame` attributes to each
+/// email, blaming files on several threads since every file is a separate
+/// process. Commits in `BLAME_IGNORE_REVS_FILE`, when there is one, pass their
+/// lines on to whoever wrote them before.
+pub fn surviving_lines(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<HashMap<String, FileLines>, Box<dyn Error>> {
+    let files = blameable_files(repo_path, options)?;
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+    let chunk_size = files.len().div_ceil(workers).max(1);
+    let prefix = options.identity.blame_mail_prefix();
+    let limits = GIT_LIMITS.with(|current| current.borrow().clone());
+    let ignore_revs = Some(repo_path.join(BLAME_IGNORE_REVS_FILE)).filter(|path| path.is_file());
+    let ignore_revs = ignore_revs.as_deref();
+
+    let results: Vec<Result<HashMap<String, FileLines>, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let limits = limits.clone();
+                scope.spawn(move || {
+                    GIT_LIMITS.with(|current| current.replace(limits));
+                    let mut counts: HashMap<String, FileLines> = HashMap::new();
+                    for file in chunk {
+                        let mut command = git_command(repo_path);
+                        command.args(["blame", "--line-porcelain"]);
+                        if let Some(ignore_revs) = ignore_revs {
+                            command.arg("--ignore-revs-file").arg(ignore_revs);
+                        }
+                        let output = run_git(command.args(["HEAD", "--"]).arg(file))
+                            .map_err(|e| e.to_string())?;
+
+                        for line in String::from_utf8_lossy(&output).lines() {
+                            if let Some(email) = line
+                                .strip_prefix(prefix)
+                                .and_then(|rest| rest.strip_suffix('>'))
+                            {
+                                *counts
+                                    .entry(email.to_string())
+                                    .or_default()
+                                    .entry(file.clone())
+                                    .or_insert(0) += 1;
+                            }
+                        }
+                    }
+                    Ok(counts)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err("blame worker panicked".to_string()))
+            })
+            .collect()
+    });
+
+    let mut totals: HashMap<String, FileLines> = HashMap::new();
+    for result in results {
+        for (email, files) in result? {
+            let entry = totals.entry(email).or_default();
+            for (file, lines) in files {
+                *entry.entry(file).or_insert(0) += lines;
+            }
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Analyzes the repository with its version control backend, turning a panic
+/// into an error so one bad repository is reported and skipped instead of
+/// taking down the caller.
+pub fn analyze_repository_guarded(
+    repo_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<(String, Vec<Contribution>, RepositoryInfo), Box<dyn Error>> {
+    match with_git_limits(options, || {
+        panic::catch_unwind(|| crate::vcs::backend_for(repo_path).analyze(repo_path, options))
+    }) {
+        Ok(result) => result,
+        Err(payload) => Err(format!("analysis panicked: {}", panic_message(&*payload)).into()),
+    }
+}
+
+/// The message a panic was raised with, for `panic!` with a literal or a
+/// formatted string.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// The outcome of analyzing one repository, as yielded by `RepositoryAnalyses`.
+pub struct RepositoryAnalysis {
+    pub path: PathBuf,
+    pub result: Result<(String, Vec<Contribution>, RepositoryInfo), Box<dyn Error>>,
+}
+
+/// Analyzes repositories lazily, one per `next()`, so callers can stream each
+/// repository's contributions somewhere (a database, a file) without holding
+/// every repository in memory at once.
+pub struct RepositoryAnalyses {
+    repositories: std::vec::IntoIter<PathBuf>,
+    options: AnalysisOptions,
+}
+
+impl Iterator for RepositoryAnalyses {
+    type Item = RepositoryAnalysis;
+
+    fn next(&mut self) -> Option<RepositoryAnalysis> {
+        let path = self.repositories.next()?;
+        let result = analyze_repository_guarded(&path, &self.options);
+        Some(RepositoryAnalysis { path, result })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.repositories.size_hint()
+    }
+}
+
+/// Returns an iterator that runs `analyze_repository` on each path as it is consumed.
+pub fn analyze_repositories(
+    repositories: Vec<PathBuf>,
+    options: AnalysisOptions,
+) -> RepositoryAnalyses {
+    RepositoryAnalyses {
+        repositories: repositories.into_iter(),
+        options,
+    }
+}
+
+/// Analyzes each repository in turn and hands its contributions to `on_repository`,
+/// stopping at the first error the callback returns. Repositories that fail to
+/// analyze are passed to `on_error` and skipped.
+pub fn for_each_repository<F, E>(
+    repositories: Vec<PathBuf>,
+    options: AnalysisOptions,
+    mut on_repository: F,
+    mut on_error: E,
+) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&str, Vec<Contribution>, RepositoryInfo) -> Result<(), Box<dyn Error>>,
+    E: FnMut(&Path, Box<dyn Error>),
+{
+    for analysis in analyze_repositories(repositories, options) {
+        match analysis.result {
+            Ok((name, contributions, info)) => on_repository(&name, contributions, info)?,
+            Err(e) => on_error(&analysis.path, e),
+        }
+    }
+
+    Ok(())
+}
+
+/// How the summary tab buckets contributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum GroupBy {
+    /// One row per author, keyed on email.
+    #[default]
+    Author,
+    /// One row per email domain, e.g. everyone `@acme.com`.
+    Domain,
+}
+
+impl GroupBy {
+    /// Heading for the cross-repository summary, which names the grouping.
+    pub fn summary_title(self) -> &'static str {
+        match self {
+            GroupBy::Author => "Summary Across All Repositories",
+            GroupBy::Domain => "Summary by Email Domain",
+        }
+    }
+
+    /// Header of the summary's name column.
+    pub fn name_header(self) -> &'static str {
+        match self {
+            GroupBy::Author => "Author",
+            GroupBy::Domain => "Domain",
+        }
+    }
+
+    /// The key and name of the summary row an author's email rolls up into.
+    pub fn summary_key(self, email: &str, author: &str) -> (String, String) {
+        match self {
+            GroupBy::Author => (email.to_string(), author.to_string()),
+            GroupBy::Domain => {
+                let domain = email_domain(email);
+                let key = if domain == UNKNOWN_DOMAIN {
+                    domain.clone()
+                } else {
+                    format!("@{}", domain)
+                };
+                (key, domain)
+            }
+        }
+    }
+}
+
+/// What a summary row's overall percentage is measured against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SummaryBasis {
+    /// Share of all lines changed across every repository, so large
+    /// repositories weigh the most.
+    #[default]
+    Global,
+    /// Average of the per-repository contribution percentages, so every
+    /// repository with line data counts equally regardless of size.
+    PerRepoAverage,
+}
+
+impl SummaryBasis {
+    /// Note appended to summary titles when the percentages are not global.
+    pub fn title_note(self) -> &'static str {
+        match self {
+            SummaryBasis::Global => "",
+            SummaryBasis::PerRepoAverage => " (overall % averaged per repository)",
+        }
+    }
+}
+
+/// Bucket for emails that have no domain to group by.
+pub const UNKNOWN_DOMAIN: &str = "(unknown)";
+
+/// The lowercased domain of an email, or `UNKNOWN_DOMAIN` without an `@`.
+pub fn email_domain(email: &str) -> String {
+    match email.rsplit_once('@') {
+        Some((_, domain)) if !domain.trim().is_empty() => domain.trim().to_lowercase(),
+        _ => UNKNOWN_DOMAIN.to_string(),
+    }
+}
+
+/// Reads organization names per email domain from `--org-map`: one
+/// `domain=Organization Name` per line, with blank lines and `#` comments
+/// skipped. A leading `@` on the domain is optional and domains are
+/// lowercased.
+pub fn read_org_map(path: &Path) -> Result<HashMap<String, String>, Box<dyn Error + Send>> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        Box::new(std::io::Error::other(format!(
+            "Failed to read organization map {}: {}",
+            path.display(),
+            e
+        ))) as Box<dyn Error + Send>
+    })?;
+
+    let mut org_map = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((domain, org)) if !domain.trim().is_empty() && !org.trim().is_empty() => {
+                let domain = domain.trim().trim_start_matches('@').to_lowercase();
+                org_map.insert(domain, org.trim().to_string());
+            }
+            _ => {
+                return Err(Box::new(std::io::Error::other(format!(
+                    "{}:{}: expected domain=Organization Name",
+                    path.display(),
+                    index + 1
+                ))))
+            }
+        }
+    }
+
+    Ok(org_map)
+}
+
+/// The organization an email belongs to: the `--org-map` name of its domain,
+/// or of the closest parent domain listed (so `eng.acme.com` falls under
+/// `acme.com`), else the domain itself.
+pub fn organization_of(email: &str, org_map: &HashMap<String, String>) -> String {
+    let domain = email_domain(email);
+    std::iter::successors(Some(domain.as_str()), |domain| {
+        domain.split_once('.').map(|(_, parent)| parent)
+    })
+    .find_map(|domain| org_map.get(domain))
+    .cloned()
+    .unwrap_or(domain)
+}
+
+/// Sets `AuthorSummary::organization` for every summary from its email (or,
+/// grouped by domain, its domain); see `organization_of`.
+pub fn apply_org_map(summaries: &mut [AuthorSummary], org_map: &HashMap<String, String>) {
+    for summary in summaries {
+        summary.organization = Some(organization_of(&summary.email, org_map));
+    }
+}
+
+#[derive(Default)]
+struct AuthorTotals {
+    author: String,
+    commits: u32,
+    merges: u32,
+    lines_added: u32,
+    lines_deleted: u32,
+    repo_percentages: HashMap<String, f64>,
+    repo_lines: HashMap<String, u32>,
+    terms: HashMap<String, u32>,
+    issues_touched: u32,
+    first_commit_at: Option<i64>,
+    last_commit_at: Option<i64>,
+    surviving_lines: u32,
+    recent_commits: u32,
+    signed_commits: u32,
+}
+
+pub fn calculate_author_summaries(
+    contributions_map: &HashMap<String, Vec<Contribution>>,
+) -> Vec<AuthorSummary> {
+    calculate_summaries(
+        contributions_map,
+        GroupBy::Author,
+        SummaryBasis::Global,
+        LineWeights::default(),
+    )
+}
+
+/// Rolls contributions up per author or per email domain. Domain rows use the
+/// domain as the name and `@domain` as the email, so they stay unique. `basis`
+/// decides what the overall percentage is measured against, and `weights` how
+/// added and deleted lines count towards it, as in `analyze_repository`.
+pub fn calculate_summaries(
+    contributions_map: &HashMap<String, Vec<Contribution>>,
+    group_by: GroupBy,
+    basis: SummaryBasis,
+    weights: LineWeights,
+) -> Vec<AuthorSummary> {
+    let mut author_data: HashMap<String, AuthorTotals> = HashMap::new();
+    let mut total_added_all_repos = 0;
+    let mut total_deleted_all_repos = 0;
+    let repos_with_lines = contributions_map
+        .values()
+        .filter(|contributions| {
+            contributions
+                .iter()
+                .any(|c| c.lines_added + c.lines_deleted > 0)
+        })
+        .count();
+
+    for (repo_name, contributions) in contributions_map {
+        for contrib in contributions {
+            let (key, name) = group_by.summary_key(&contrib.email, &contrib.author);
+            let lines_changed = contrib.lines_added + contrib.lines_deleted;
+
+            total_added_all_repos += contrib.lines_added;
+            total_deleted_all_repos += contrib.lines_deleted;
+
+            let entry = author_data.entry(key).or_insert_with(|| AuthorTotals {
+                author: name,
+                ..Default::default()
+            });
+
+            entry.commits += contrib.commits;
+            entry.merges += contrib.merges;
+            entry.surviving_lines += contrib.surviving_lines;
+            entry.recent_commits += contrib.recent_commits;
+            entry.signed_commits += contrib.signed_commits;
+            entry.lines_added += contrib.lines_added;
+            entry.lines_deleted += contrib.lines_deleted;
+            *entry
+                .repo_percentages
+                .entry(repo_name.clone())
+                .or_insert(0.0) += contrib.contribution_percent;
+            *entry.repo_lines.entry(repo_name.clone()).or_insert(0) += lines_changed;
+            entry.issues_touched += contrib.issues.len() as u32;
+            entry.first_commit_at = match (entry.first_commit_at, contrib.first_commit_at) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            entry.last_commit_at = entry.last_commit_at.max(contrib.last_commit_at);
+            for (term, count) in &contrib.terms {
+                *entry.terms.entry(term.clone()).or_insert(0) += count;
+            }
+        }
+    }
+
+    let mut summaries = Vec::new();
+
+    for (email, totals) in author_data {
+        let AuthorTotals {
+            author,
+            commits,
+            merges,
+            lines_added,
+            lines_deleted,
+            repo_percentages,
+            repo_lines,
+            terms,
+            issues_touched,
+            first_commit_at,
+            last_commit_at,
+            surviving_lines,
+            recent_commits,
+            signed_commits,
+        } = totals;
+        let total_lines_changed = lines_added + lines_deleted;
+        let overall_percent = match basis {
+            SummaryBasis::Global => weights.percent(
+                lines_added,
+                lines_deleted,
+                total_added_all_repos,
+                total_deleted_all_repos,
+            ),
+            SummaryBasis::PerRepoAverage if repos_with_lines > 0 => {
+                repo_percentages.values().sum::<f64>() / repos_with_lines as f64
+            }
+            _ => 0.0,
+        };
+
+        let mut preferred_repo = String::new();
+        let mut highest_percent = 0.0;
+
+        for (repo, percent) in &repo_percentages {
+            if *percent > highest_percent {
+                highest_percent = *percent;
+                preferred_repo = repo.clone();
+            }
+        }
+
+        let mut repo_distribution: Vec<(String, f64)> = repo_lines
+            .into_iter()
+            .filter(|(_, lines)| *lines > 0)
+            .map(|(repo, lines)| (repo, lines as f64 / total_lines_changed as f64 * 100.0))
+            .collect();
+        repo_distribution.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        summaries.push(AuthorSummary {
+            author,
+            email,
+            total_commits: commits,
+            total_merges: merges,
+            total_lines_added: lines_added,
+            total_lines_deleted: lines_deleted,
+            overall_contribution_percent: overall_percent,
+            preferred_repo,
+            preferred_repo_percent: highest_percent,
+            repo_distribution,
+            repositories: repo_percentages.len(),
+            terms,
+            issues_touched,
+            first_commit_at,
+            last_commit_at,
+            rank: 0,
+            total_surviving_lines: surviving_lines,
+            recent_commits,
+            total_signed_commits: signed_commits,
+            trend: None,
+            reviews: 0,
+            organization: None,
+        });
+    }
+
+    summaries.sort_by(|a, b| {
+        b.overall_contribution_percent
+            .total_cmp(&a.overall_contribution_percent)
+    });
+
+    let percents: Vec<f64> = summaries
+        .iter()
+        .map(|s| s.overall_contribution_percent)
+        .collect();
+    for (summary, rank) in summaries.iter_mut().zip(competition_ranks(&percents)) {
+        summary.rank = rank;
+    }
+
+    summaries
+}
+
+/// How an author's recent commit rate compares with their overall rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Trend {
+    Up,
+    Flat,
+    Down,
+}
+
+impl Trend {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Trend::Up => "↑",
+            Trend::Flat => "→",
+            Trend::Down => "↓",
+        }
+    }
+}
+
+/// How far the recent commit rate must move from the overall rate, as a
+/// fraction of it, to count as trending up or down.
+pub const TREND_THRESHOLD: f64 = 0.25;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Compares an author's commits per day since `trend_start` with their
+/// commits per day over the whole analyzed period, which starts at their first
+/// commit or at `history_start` (the `--since` bound), whichever is later, but
+/// never after `trend_start`.
+pub fn commit_trend(
+    total_commits: u32,
+    recent_commits: u32,
+    first_commit_at: Option<i64>,
+    history_start: Option<i64>,
+    trend_start: i64,
+    now: i64,
+) -> Trend {
+    if total_commits == 0 {
+        return Trend::Flat;
+    }
+
+    // The overall period always spans the trend window, so an author whose
+    // whole history is recent reads as flat rather than slowing down.
+    let period_start = first_commit_at
+        .unwrap_or(trend_start)
+        .max(history_start.unwrap_or(i64::MIN))
+        .min(trend_start);
+    let days_since = |start: i64| ((now - start) as f64 / SECONDS_PER_DAY).max(1.0);
+
+    let overall_rate = total_commits as f64 / days_since(period_start);
+    let recent_rate = recent_commits as f64 / days_since(trend_start);
+
+    if recent_rate > overall_rate * (1.0 + TREND_THRESHOLD) {
+        Trend::Up
+    } else if recent_rate < overall_rate * (1.0 - TREND_THRESHOLD) {
+        Trend::Down
+    } else {
+        Trend::Flat
+    }
+}
+
+/// Adds review counts from `read_reviews_csv` to the summary rows whose email
+/// matches ignoring case (or, grouped by domain, whose domain does). Emails
+/// with reviews but no commits get review-only rows, ranked after everyone
+/// with a contribution.
+pub fn apply_reviews(
+    summaries: &mut Vec<AuthorSummary>,
+    reviews: &HashMap<String, u32>,
+    group_by: GroupBy,
+) {
+    let mut review_rows: HashMap<String, (String, u32)> = HashMap::new();
+    for (email, count) in reviews {
+        let (key, name) = group_by.summary_key(email, email);
+        match summaries
+            .iter_mut()
+            .find(|summary| summary.email.to_lowercase() == key)
+        {
+            Some(summary) => summary.reviews += count,
+            None => review_rows.entry(key).or_insert((name, 0)).1 += count,
+        }
+    }
+
+    let rank = 1 + summaries
+        .iter()
+        .filter(|summary| summary.overall_contribution_percent > 0.0)
+        .count() as u32;
+    summaries.extend(
+        review_rows
+            .into_iter()
+            .sorted_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)))
+            .map(|(email, (author, reviews))| AuthorSummary {
+                author,
+                email,
+                rank,
+                reviews,
+                ..Default::default()
+            }),
+    );
+}
+
+/// Sets `AuthorSummary::trend` for every summary; see `commit_trend`.
+pub fn apply_trends(
+    summaries: &mut [AuthorSummary],
+    history_start: Option<i64>,
+    trend_start: i64,
+    now: i64,
+) {
+    for summary in summaries {
+        summary.trend = Some(commit_trend(
+            summary.total_commits,
+            summary.recent_commits,
+            summary.first_commit_at,
+            history_start,
+            trend_start,
+            now,
+        ));
+    }
+}
+
+/// Ranks values sorted in descending order, "1224" style: equal values share
+/// the rank of the first of them and the next distinct value skips ahead.
+pub fn competition_ranks(sorted_values: &[f64]) -> Vec<u32> {
+    let mut ranks = Vec::with_capacity(sorted_values.len());
+
+    for (index, value) in sorted_values.iter().enumerate() {
+        let rank = match index.checked_sub(1) {
+            Some(previous) if sorted_values[previous] == *value => ranks[previous],
+            _ => index as u32 + 1,
+        };
+        ranks.push(rank);
+    }
+
+    ranks
+}
+
+/// Smallest number of authors who together made more than half of the
+/// changed lines or commits, per `basis`; zero without any.
+pub fn bus_factor(contributions: &[Contribution], basis: OwnershipBasis) -> u32 {
+    let mut lines: Vec<u64> = contributions
+        .iter()
+        .map(|c| match basis {
+            OwnershipBasis::Lines => c.lines_added as u64 + c.lines_deleted as u64,
+            OwnershipBasis::Commits => c.commits as u64,
+        })
+        .collect();
+    lines.sort_unstable_by(|a, b| b.cmp(a));
+    let total: u64 = lines.iter().sum();
+
+    let mut covered = 0;
+    for (index, author_lines) in lines.iter().enumerate() {
+        covered += author_lines;
+        if covered * 2 > total {
+            return index as u32 + 1;
+        }
+    }
+    0
+}
+
+/// Gini coefficient of `values`: 0 when all are equal, approaching 1 as one
+/// value holds everything. Zero for fewer than two values or an all-zero set.
+pub fn gini_coefficient(values: &[u64]) -> f64 {
+    let total: u64 = values.iter().sum();
+    if values.len() < 2 || total == 0 {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as f64;
+    let weighted: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (index as f64 + 1.0) * *value as f64)
+        .sum();
+    (2.0 * weighted) / (n * total as f64) - (n + 1.0) / n
+}
+
+/// Points each component contributes to `RepositoryHealth::score`.
+const HEALTH_BUS_FACTOR_WEIGHT: f64 = 30.0;
+const HEALTH_SPREAD_WEIGHT: f64 = 25.0;
+const HEALTH_CONTRIBUTORS_WEIGHT: f64 = 20.0;
+const HEALTH_RECENCY_WEIGHT: f64 = 25.0;
+
+/// Bus factor, contributor count and days of inactivity at which their
+/// components reach full (or, for inactivity, no) credit.
+const HEALTHY_BUS_FACTOR: u32 = 3;
+const HEALTHY_CONTRIBUTORS: u32 = 10;
+const FRESH_DAYS: f64 = 30.0;
+const STALE_DAYS: f64 = 365.0;
+
+/// A 0-100 rollup of how well a repository's knowledge is spread and how
+/// alive it is, with the metrics it was computed from.
+///
+/// The score is the sum of four weighted components, each between 0 and 1:
+/// - bus factor: `min(bus_factor, 3) / 3`, by the ownership basis, worth 30
+///   points;
+/// - spread: `1 - gini` over lines changed per author, worth 25;
+/// - contributors: `min(contributors, 10) / 10`, worth 20;
+/// - recency: 1 for a last commit within 30 days, falling linearly to 0 at a
+///   year, worth 25.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryHealth {
+    pub score: u32,
+    pub bus_factor: u32,
+    pub gini: f64,
+    pub contributors: u32,
+    /// Whole days since the latest commit, ignoring any date window.
+    pub days_since_last_commit: Option<u64>,
+}
+
+impl RepositoryHealth {
+    /// The components behind the score, e.g. "bus factor 2, Gini 0.41,
+    /// 4 contributors, last commit 3 days ago".
+    pub fn breakdown(&self) -> String {
+        let last_commit = match self.days_since_last_commit {
+            Some(0) => String::from("last commit today"),
+            Some(1) => String::from("last commit 1 day ago"),
+            Some(days) => format!("last commit {} days ago", days),
+            None => String::from("no commits"),
+        };
+        format!(
+            "bus factor {}, Gini {:.2}, {} contributor{}, {}",
+            self.bus_factor,
+            self.gini,
+            self.contributors,
+            if self.contributors == 1 { "" } else { "s" },
+            last_commit
+        )
+    }
+}
+
+/// Health limits every repository has to meet in `--plain` mode, for gating
+/// CI on contribution norms.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HealthThresholds {
+    /// Smallest acceptable bus factor; a repository below it fails.
+    pub min_bus_factor: Option<u32>,
+    /// Largest acceptable Gini coefficient; a repository above it fails.
+    pub max_gini: Option<f64>,
+}
+
+impl HealthThresholds {
+    /// Checks that a Gini limit lies between 0 and 1, the coefficient's range.
+    pub fn new(
+        min_bus_factor: Option<u32>,
+        max_gini: Option<f64>,
+    ) -> Result<HealthThresholds, Box<dyn Error + Send>> {
+        if let Some(gini) = max_gini.filter(|gini| !(0.0..=1.0).contains(gini)) {
+            return Err(Box::new(std::io::Error::other(format!(
+                "Invalid Gini threshold {}: it must be between 0 and 1",
+                gini
+            ))));
+        }
+        Ok(HealthThresholds {
+            min_bus_factor,
+            max_gini,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_bus_factor.is_none() && self.max_gini.is_none()
+    }
+
+    /// Every threshold `health` misses, e.g. "bus factor 1 is below 2".
+    pub fn violations(&self, health: &RepositoryHealth) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(min) = self.min_bus_factor.filter(|min| health.bus_factor < *min) {
+            violations.push(format!("bus factor {} is below {}", health.bus_factor, min));
+        }
+        if let Some(max) = self.max_gini.filter(|max| health.gini > *max) {
+            violations.push(format!("Gini {:.2} is above {}", health.gini, max));
+        }
+        violations
+    }
+}
+
+/// Scores a repository's health as of `now`, with the bus factor measured by
+/// `basis`; `None` without any line changes (an empty or binary-only
+/// repository), as there is nothing to measure the spread of. See
+/// `RepositoryHealth`.
+pub fn repository_health(
+    contributions: &[Contribution],
+    basis: OwnershipBasis,
+    now: i64,
+) -> Option<RepositoryHealth> {
+    let lines: Vec<u64> = contributions
+        .iter()
+        .filter(|c| c.commits > 0)
+        .map(|c| c.lines_added as u64 + c.lines_deleted as u64)
+        .collect();
+    if lines.iter().sum::<u64>() == 0 {
+        return None;
+    }
+
+    let bus_factor = bus_factor(contributions, basis);
+    let gini = gini_coefficient(&lines);
+    let contributors = lines.len() as u32;
+    let days_since_last_commit = contributions
+        .iter()
+        .filter_map(|c| c.last_commit_at)
+        .max()
+        .map(|last| (now - last).max(0) as u64 / SECONDS_PER_DAY as u64);
+
+    let recency = match days_since_last_commit {
+        Some(days) => ((STALE_DAYS - days as f64) / (STALE_DAYS - FRESH_DAYS)).clamp(0.0, 1.0),
+        None => 0.0,
+    };
+    let score = HEALTH_BUS_FACTOR_WEIGHT * bus_factor.min(HEALTHY_BUS_FACTOR) as f64
+        / HEALTHY_BUS_FACTOR as f64
+        + HEALTH_SPREAD_WEIGHT * (1.0 - gini)
+        + HEALTH_CONTRIBUTORS_WEIGHT * contributors.min(HEALTHY_CONTRIBUTORS) as f64
+            / HEALTHY_CONTRIBUTORS as f64
+        + HEALTH_RECENCY_WEIGHT * recency;
+
+    Some(RepositoryHealth {
+        score: score.round() as u32,
+        bus_factor,
+        gini,
+        contributors,
+        days_since_last_commit,
+    })
+}
+
+/// The "top N%" bracket a rank falls in among `count` authors.
+pub fn top_percentile(rank: u32, count: usize) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    rank as f64 / count as f64 * 100.0
+}
+
+/// Time between each pair of consecutive commits, given their Unix timestamps
+/// in any order.
+pub fn commit_gaps(timestamps: &mut [i64]) -> Vec<Duration> {
+    timestamps.sort_unstable();
+    timestamps
+        .windows(2)
+        .map(|pair| Duration::from_secs(pair[1].abs_diff(pair[0])))
+        .collect()
+}
+
+/// Middle value of `durations`, averaging the two middle values of an even
+/// count; `None` when empty.
+pub fn median_duration(mut durations: Vec<Duration>) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort_unstable();
+
+    let middle = durations.len() / 2;
+    if durations.len().is_multiple_of(2) {
+        Some((durations[middle - 1] + durations[middle]) / 2)
+    } else {
+        Some(durations[middle])
+    }
+}
+
+/// Rough English length of a duration at its largest whole unit, e.g.
+/// "45 seconds", "1 day" or "3 months" (months are 30 days, years 365).
+pub fn humanize_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (count, unit) = match seconds {
+        s if s < 60 => (s, "second"),
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3600, "hour"),
+        s if s < 30 * 86_400 => (s / 86_400, "day"),
+        s if s < 365 * 86_400 => (s / (30 * 86_400), "month"),
+        s => (s / (365 * 86_400), "year"),
+    };
+
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+/// How long before (or, with clock skew, after) `now` a Unix timestamp lies,
+/// e.g. "3 months ago", "in 2 hours" or "just now".
+pub fn relative_time(timestamp: i64, now: i64) -> String {
+    let distance = Duration::from_secs(now.abs_diff(timestamp));
+    if distance.as_secs() < 60 {
+        String::from("just now")
+    } else if timestamp <= now {
+        format!("{} ago", humanize_duration(distance))
+    } else {
+        format!("in {}", humanize_duration(distance))
+    }
+}
+
+/// A Unix timestamp as a local date ("2024-03-11"), or relative to `now` when
+/// `relative` is set.
+pub fn format_timestamp(timestamp: i64, now: i64, relative: bool) -> String {
+    if relative {
+        return relative_time(timestamp, now);
+    }
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| {
+            date.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Decimal places `--percent-precision` is clamped to.
+pub const MAX_PERCENT_PRECISION: usize = 6;
+
+/// Decimal places percentages are shown with unless `--percent-precision` says otherwise.
+pub const DEFAULT_PERCENT_PRECISION: usize = 2;
+
+/// Formats a percentage with `precision` decimal places (clamped to
+/// `MAX_PERCENT_PRECISION`), e.g. "12.50%".
+pub fn format_percent(percent: f64, precision: usize) -> String {
+    format!("{:.*}%", precision.min(MAX_PERCENT_PRECISION), percent)
+}
+
+/// Compact human form of a gap between commits, e.g. "40m", "2.3h" or "5.1d".
+pub fn format_gap(gap: Duration) -> String {
+    let hours = gap.as_secs_f64() / 3600.0;
+    if gap.as_secs() < 60 {
+        format!("{}s", gap.as_secs())
+    } else if hours < 1.0 {
+        format!("{}m", gap.as_secs() / 60)
+    } else if hours < 48.0 {
+        format!("{:.1}h", hours)
+    } else {
+        format!("{:.1}d", hours / 24.0)
+    }
+}
+
+/// Authors considered for collaborator pairs, by lines changed; bounds the
+/// pairwise comparison on repositories with many contributors.
+pub const COLLABORATION_AUTHOR_LIMIT: usize = 25;
+
+/// Two authors who changed the same files.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollaboratorPair {
+    pub first_author: String,
+    pub first_email: String,
+    pub second_author: String,
+    pub second_email: String,
+    /// Files, across all repositories, changed by both authors.
+    pub shared_files: u32,
+    /// Shared files as a share of all files either author changed (Jaccard
+    /// index), from 0 to 1.
+    pub affinity: f64,
+}
+
+/// Files one author changed across all repositories, keyed by repository.
+struct AuthorFiles<'a> {
+    author: &'a str,
+    lines_changed: u64,
+    files: HashSet<(&'a str, &'a str)>,
+}
+
+/// Scores every pair among the `author_limit` most active authors by how much
+/// their changed files overlap, strongest pairs first. Pairs with nothing in
+/// common are left out.
+pub fn collaborator_pairs(
+    contributions_map: &HashMap<String, Vec<Contribution>>,
+    author_limit: usize,
+) -> Vec<CollaboratorPair> {
+    let mut authors: HashMap<&str, AuthorFiles> = HashMap::new();
+
+    for (repo_name, contributions) in contributions_map {
+        for contrib in contributions {
+            let entry = authors
+                .entry(contrib.email.as_str())
+                .or_insert_with(|| AuthorFiles {
+                    author: &contrib.author,
+                    lines_changed: 0,
+                    files: HashSet::new(),
+                });
+            entry.lines_changed += contrib.lines_added as u64 + contrib.lines_deleted as u64;
+            entry.files.extend(
+                contrib
+                    .files_touched
+                    .iter()
+                    .map(|path| (repo_name.as_str(), path.as_str())),
+            );
+        }
+    }
+
+    let top: Vec<(&str, AuthorFiles)> = authors
+        .into_iter()
+        .filter(|(_, author)| !author.files.is_empty())
+        .sorted_by(|a, b| {
+            b.1.lines_changed
+                .cmp(&a.1.lines_changed)
+                .then_with(|| a.0.cmp(b.0))
+        })
+        .take(author_limit)
+        .collect();
+
+    let mut pairs = Vec::new();
+    for (i, (first_email, first)) in top.iter().enumerate() {
+        for (second_email, second) in &top[i + 1..] {
+            let shared = first.files.intersection(&second.files).count();
+            if shared == 0 {
+                continue;
+            }
+            let union = first.files.len() + second.files.len() - shared;
+            pairs.push(CollaboratorPair {
+                first_author: first.author.to_string(),
+                first_email: first_email.to_string(),
+                second_author: second.author.to_string(),
+                second_email: second_email.to_string(),
+                shared_files: shared as u32,
+                affinity: shared as f64 / union as f64,
+            });
+        }
+    }
+
+    pairs.sort_by(|a, b| {
+        b.affinity
+            .total_cmp(&a.affinity)
+            .then_with(|| b.shared_files.cmp(&a.shared_files))
+    });
+    pairs
+}
+
+/// One author's activity in a baseline window next to a current window.
+#[derive(Debug, Clone)]
+pub struct AuthorComparison {
+    pub author: String,
+    pub email: String,
+    pub baseline_commits: u32,
+    pub current_commits: u32,
+    pub baseline_lines: u32,
+    pub current_lines: u32,
+}
+
+impl AuthorComparison {
+    pub fn commits_delta(&self) -> i64 {
+        self.current_commits as i64 - self.baseline_commits as i64
+    }
+
+    pub fn lines_delta(&self) -> i64 {
+        self.current_lines as i64 - self.baseline_lines as i64
+    }
+}
+
+/// Matches authors by email across two analyses (summed over every repository)
+/// and returns their activity in both, largest line increase first. Authors
+/// missing from one window count as zero there.
+pub fn compare_contributions(
+    baseline: &HashMap<String, Vec<Contribution>>,
+    current: &HashMap<String, Vec<Contribution>>,
+) -> Vec<AuthorComparison> {
+    let mut authors: HashMap<String, AuthorComparison> = HashMap::new();
+
+    for (contributions_map, is_current) in [(baseline, false), (current, true)] {
+        for contrib in contributions_map.values().flatten() {
+            let entry = authors
+                .entry(contrib.email.clone())
+                .or_insert_with(|| AuthorComparison {
+                    author: contrib.author.clone(),
+                    email: contrib.email.clone(),
+                    baseline_commits: 0,
+                    current_commits: 0,
+                    baseline_lines: 0,
+                    current_lines: 0,
+                });
+
+            let lines = contrib.lines_added + contrib.lines_deleted;
+            if is_current {
+                entry.current_commits += contrib.commits;
+                entry.current_lines += lines;
+            } else {
+                entry.baseline_commits += contrib.commits;
+                entry.baseline_lines += lines;
+            }
+        }
+    }
+
+    authors
+        .into_values()
+        .sorted_by(|a, b| {
+            b.lines_delta()
+                .cmp(&a.lines_delta())
+                .then_with(|| a.email.cmp(&b.email))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A throwaway repository in the system temp directory, removed on drop.
+    struct TestRepository {
+        path: PathBuf,
+    }
+
+    impl TestRepository {
+        fn new(name: &str) -> TestRepository {
+            let path = std::env::temp_dir()
+                .join(format!("gca-test-{}", std::process::id()))
+                .join(name);
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            let repository = TestRepository { path };
+            repository.git(&["init", "--quiet"], &[]);
+            repository
+        }
+
+        fn git(&self, args: &[&str], env: &[(&str, &str)]) {
+            let status = Command::new("git")
+                .current_dir(&self.path)
+                .args(["-c", "commit.gpgsign=false"])
+                .args(args)
+                .envs(env.iter().copied())
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?} failed", args);
+        }
+
+        /// Writes `contents` to `file` and commits it as `author <email>` at
+        /// the Unix time `timestamp`.
+        fn commit(&self, author: &str, email: &str, timestamp: i64, file: &str, contents: &str) {
+            let path = self.path.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+            self.git(&["add", "--all"], &[]);
+            let date = format!("{} +0000", timestamp);
+            self.git(
+                &["commit", "--quiet", "-m", &format!("Change {}", file)],
+                &[
+                    ("GIT_AUTHOR_NAME", author),
+                    ("GIT_AUTHOR_EMAIL", email),
+                    ("GIT_AUTHOR_DATE", &date),
+                    ("GIT_COMMITTER_NAME", author),
+                    ("GIT_COMMITTER_EMAIL", email),
+                    ("GIT_COMMITTER_DATE", &date),
+                ],
+            );
+        }
+
+        /// Full hash of the latest commit.
+        fn head(&self) -> String {
+            let output = Command::new("git")
+                .current_dir(&self.path)
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        }
+
+        fn analyze(&self, options: &AnalysisOptions) -> (Vec<Contribution>, RepositoryInfo) {
+            let (_, contributions, info) = analyze_repository(&self.path, options).unwrap();
+            (contributions, info)
+        }
+    }
+
+    impl Drop for TestRepository {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+
+    const T0: i64 = 1_700_000_000;
+
+    /// Writes `contents` to a file named `name` in this process's temp
+    /// directory and returns its path.
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gca-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn summary(author: &str, email: &str, percent: f64, rank: u32) -> AuthorSummary {
+        AuthorSummary {
+            author: author.to_string(),
+            email: email.to_string(),
+            overall_contribution_percent: percent,
+            rank,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn median_gap_uses_the_counted_commits_only() {
+        let repository = TestRepository::new("median-gap");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "1\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 100, "a.txt", "1\n2\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 1000, "a.txt", "1\n2\n3\n");
+
+        let (contributions, info) = repository.analyze(&AnalysisOptions {
+            until: Some(format!("@{}", T0 + 500)),
+            ..Default::default()
+        });
+        assert_eq!(contributions[0].commits, 2);
+        assert_eq!(info.median_commit_gap, Some(Duration::from_secs(100)));
+    }
+
+    #[test]
+    fn median_gap_of_a_single_commit_is_none() {
+        let repository = TestRepository::new("single-commit");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "1\n");
+
+        let (contributions, info) = repository.analyze(&AnalysisOptions::default());
+        assert_eq!(contributions[0].commits, 1);
+        assert_eq!(info.median_commit_gap, None);
+        assert_eq!(median_duration(commit_gaps(&mut [T0])), None);
+    }
+
+    #[test]
+    fn reviews_csv_skips_the_header_and_adds_up_repeated_emails() {
+        let path = temp_file(
+            "reviews.csv",
+            "email,review_count\n# exported today\n\n\"Jane@Acme.com\",3,extra\njane@acme.com,2\nmia@int.io,4\n",
+        );
+        let reviews = read_reviews_csv(&path).unwrap();
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews["jane@acme.com"], 5);
+        assert_eq!(reviews["mia@int.io"], 4);
+    }
+
+    #[test]
+    fn reviews_csv_rejects_malformed_rows() {
+        let missing_count = temp_file("reviews-short.csv", "jane@acme.com,3\nbob@other.org\n");
+        let error = read_reviews_csv(&missing_count).unwrap_err().to_string();
+        assert!(
+            error.ends_with(":2: expected email,review_count"),
+            "{}",
+            error
+        );
+
+        let bad_count = temp_file("reviews-bad.csv", "jane@acme.com,3\nbob@other.org,many\n");
+        let error = read_reviews_csv(&bad_count).unwrap_err().to_string();
+        assert!(
+            error.ends_with(":2: 'many' is not a review count"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn reviews_without_commits_get_their_own_row_ranked_last() {
+        let mut summaries = vec![
+            summary("Jane", "Jane@acme.com", 60.0, 1),
+            summary("Bob", "bob@other.org", 40.0, 2),
+        ];
+        let reviews = HashMap::from([
+            ("jane@acme.com".to_string(), 3),
+            ("mia@int.io".to_string(), 4),
+        ]);
+        apply_reviews(&mut summaries, &reviews, GroupBy::Author);
+
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries[0].reviews, 3);
+        assert_eq!(summaries[1].reviews, 0);
+        assert_eq!(summaries[2].email, "mia@int.io");
+        assert_eq!(summaries[2].reviews, 4);
+        assert_eq!(summaries[2].total_commits, 0);
+        assert_eq!(summaries[2].rank, 3);
+    }
+
+    #[test]
+    fn org_map_reads_domains_case_insensitively() {
+        let path = temp_file(
+            "orgs.txt",
+            "# organizations\n\nacme.com=Acme Corp\n @Example.ORG = Example Inc \n",
+        );
+        let org_map = read_org_map(&path).unwrap();
+        assert_eq!(org_map.len(), 2);
+        assert_eq!(org_map["acme.com"], "Acme Corp");
+        assert_eq!(org_map["example.org"], "Example Inc");
+
+        let malformed = temp_file("orgs-bad.txt", "acme.com=Acme Corp\nexample.org\n");
+        let error = read_org_map(&malformed).unwrap_err().to_string();
+        assert!(
+            error.ends_with(":2: expected domain=Organization Name"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn organizations_come_from_the_email_domain() {
+        let org_map = HashMap::from([("acme.com".to_string(), "Acme Corp".to_string())]);
+        // The email's own domain, a subdomain of a listed one, an unlisted
+        // domain and no domain at all.
+        assert_eq!(organization_of("Jane@ACME.com", &org_map), "Acme Corp");
+        assert_eq!(organization_of("bob@eng.acme.com", &org_map), "Acme Corp");
+        assert_eq!(organization_of("mia@int.io", &org_map), "int.io");
+        assert_eq!(organization_of("root", &org_map), UNKNOWN_DOMAIN);
+
+        let mut summaries = vec![
+            summary("Jane", "jane@acme.com", 60.0, 1),
+            summary("Mia", "mia@int.io", 40.0, 2),
+        ];
+        apply_org_map(&mut summaries, &org_map);
+        assert_eq!(summaries[0].organization.as_deref(), Some("Acme Corp"));
+        assert_eq!(summaries[1].organization.as_deref(), Some("int.io"));
+    }
+
+    #[test]
+    fn raw_dumps_of_same_named_repositories_do_not_collide() {
+        let work = TestRepository::new("work/api");
+        work.commit("Jane", "jane@acme.com", T0, "a.txt", "1\n");
+        let personal = TestRepository::new("personal/api");
+        personal.commit("Bob", "bob@other.org", T0, "b.txt", "1\n");
+
+        let dir = std::env::temp_dir()
+            .join(format!("gca-test-{}", std::process::id()))
+            .join("dumps");
+        let options = AnalysisOptions {
+            dump_raw: Some(dir.clone()),
+            ..Default::default()
+        };
+        work.analyze(&options);
+        personal.analyze(&options);
+
+        let work_log = fs::read_to_string(dir.join("work").join("api.log")).unwrap();
+        let personal_log = fs::read_to_string(dir.join("personal").join("api.log")).unwrap();
+        assert!(work_log.contains("jane@acme.com") && !work_log.contains("bob@other.org"));
+        assert!(personal_log.contains("bob@other.org") && !personal_log.contains("jane@acme.com"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn numstat(lines: Option<(u32, u32)>, path: &str) -> Option<NumstatLine> {
+        Some(NumstatLine {
+            lines,
+            path: path.to_string(),
+        })
+    }
+
+    #[test]
+    fn numstat_paths_keep_their_spaces() {
+        assert_eq!(
+            parse_numstat_line("3\t1\tdocs/release notes.md"),
+            numstat(Some((3, 1)), "docs/release notes.md")
+        );
+        assert_eq!(
+            parse_numstat_line("3\t1\tsrc\\main.rs\r"),
+            numstat(Some((3, 1)), "src/main.rs")
+        );
+    }
+
+    #[test]
+    fn binary_numstat_lines_have_no_line_counts() {
+        assert_eq!(
+            parse_numstat_line("-\t-\tassets/logo.png"),
+            numstat(None, "assets/logo.png")
+        );
+        assert_eq!(parse_numstat_line("-\t4\tassets/logo.png"), None);
+    }
+
+    #[test]
+    fn renames_keep_numstat_syntax() {
+        let line = parse_numstat_line("0\t0\tsrc/{a => b}/lib.rs");
+        assert_eq!(line, numstat(Some((0, 0)), "src/{a => b}/lib.rs"));
+        assert_eq!(top_level_directory(&line.unwrap().path), Some("src"));
+        assert_eq!(top_level_directory("old/x.rs => new/x.rs"), Some("new"));
+    }
+
+    #[test]
+    fn non_numstat_lines_are_skipped() {
+        assert_eq!(parse_numstat_line(""), None);
+        assert_eq!(parse_numstat_line("commit\t1700000000"), None);
+        assert_eq!(parse_numstat_line("1\t2\t"), None);
+    }
+
+    #[test]
+    fn quoted_numstat_paths_are_unquoted() {
+        assert_eq!(
+            parse_numstat_line("1\t0\t\"docs/caf\\303\\251.md\""),
+            numstat(Some((1, 0)), "docs/café.md")
+        );
+        assert_eq!(
+            parse_numstat_line("2\t2\t\"say \\\"hi\\\"\\tnow.txt\""),
+            numstat(Some((2, 2)), "say \"hi\"\tnow.txt")
+        );
+    }
+
+    #[test]
+    fn utf8_paths_pass_through_unquoted() {
+        assert_eq!(
+            parse_numstat_line("5\t0\tsrc/日本語/モジュール.rs"),
+            numstat(Some((5, 0)), "src/日本語/モジュール.rs")
+        );
+    }
+
+    #[test]
+    fn non_ascii_file_names_are_counted_in_a_repository() {
+        let repository = TestRepository::new("utf8-paths");
+        repository.commit("Jane", "jane@acme.com", T0, "café.md", "one\ntwo\n");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].lines_added, 2);
+    }
+
+    #[test]
+    fn author_names_with_pipes_survive_the_field_split() {
+        let repository = TestRepository::new("pipe-author");
+        repository.commit("Foo | Bar", "foo@acme.com", T0, "a.txt", "one\n");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].author, "Foo | Bar");
+        assert_eq!(contributions[0].email, "foo@acme.com");
+
+        let map = HashMap::from([("pipe-author".to_string(), contributions)]);
+        let summaries = calculate_author_summaries(&map);
+        assert_eq!(summaries[0].author, "Foo | Bar");
+    }
+
+    #[test]
+    fn commit_records_split_on_unit_separators_only() {
+        let fields = [CommitField::Subject, CommitField::Committer];
+        let record =
+            parse_commit_record("abc123\x1fFix a | b\x1fFoo | Bar <foo@acme.com>\n", &fields)
+                .unwrap();
+        assert_eq!(record.hash, "abc123");
+        assert_eq!(record.subject.as_deref(), Some("Fix a | b"));
+        assert_eq!(
+            record.committer.as_deref(),
+            Some("Foo | Bar <foo@acme.com>")
+        );
+    }
+
+    #[test]
+    fn trailer_keys_match_case_insensitively() {
+        let trailers = parse_trailers("Fix it\n\nCo-Authored-BY: Mia <mia@int.io>\n");
+        assert_eq!(
+            trailers.get("co-authored-by"),
+            Some(&vec!["Mia <mia@int.io>".to_string()])
+        );
+    }
+
+    #[test]
+    fn every_trailer_in_the_block_is_kept_in_order() {
+        let trailers = parse_trailers(
+            "Fix it\n\nBody text.\n\nCo-authored-by: Mia <mia@int.io>\nReviewed-by: Bob\nCo-authored-by: Ann <ann@int.io>\n",
+        );
+        assert_eq!(
+            trailers.get("co-authored-by"),
+            Some(&vec![
+                "Mia <mia@int.io>".to_string(),
+                "Ann <ann@int.io>".to_string()
+            ])
+        );
+        assert_eq!(trailers.get("reviewed-by"), Some(&vec!["Bob".to_string()]));
+    }
+
+    #[test]
+    fn trailer_like_body_lines_are_not_trailers() {
+        let body = "Fix it\n\nCo-authored-by: Mia <mia@int.io>\nwas discussed here.\n\nSigned-off-by: Jane <jane@acme.com>\n";
+        let trailers = parse_trailers(body);
+        assert_eq!(trailers.get("co-authored-by"), None);
+        assert_eq!(
+            trailers.get("signed-off-by"),
+            Some(&vec!["Jane <jane@acme.com>".to_string()])
+        );
+
+        assert!(parse_trailers("Reviewed-by: Bob\n").is_empty());
+        assert!(parse_trailers("Fix it\n\nNote: see below\nnot a trailer\n").is_empty());
+    }
+
+    #[test]
+    fn indented_lines_continue_a_trailer() {
+        let trailers = parse_trailers("Fix it\n\nFixes: the crash when\n  loading big repos\n");
+        assert_eq!(
+            trailers.get("fixes"),
+            Some(&vec!["the crash when loading big repos".to_string()])
+        );
+    }
+
+    #[test]
+    fn tied_values_share_a_competition_rank() {
+        assert_eq!(
+            competition_ranks(&[50.0, 25.0, 25.0, 10.0]),
+            vec![1, 2, 2, 4]
+        );
+        assert_eq!(competition_ranks(&[7.0, 7.0, 7.0]), vec![1, 1, 1]);
+        assert_eq!(competition_ranks(&[]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn relative_globs_are_valid_patterns() {
+        for pattern in ["*", "bwt-*", "team-a/*", "**/service-?", "[ab]*"] {
+            assert!(validate_repository_pattern(pattern).is_ok(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn malformed_or_escaping_patterns_are_rejected() {
+        for pattern in [
+            "",
+            "  ",
+            "bwt-[",
+            "/srv/repos/*",
+            "\\repos",
+            "../*",
+            "a/../../b",
+        ] {
+            assert!(
+                validate_repository_pattern(pattern).is_err(),
+                "{:?}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn patterns_only_find_repositories_under_the_parent() {
+        let parent = TestRepository::new("patterns/parent");
+        let _wanted = TestRepository::new("patterns/parent/bwt-api");
+        let _other = TestRepository::new("patterns/parent/web");
+        let mut errors = Vec::new();
+
+        let found = find_repositories(&parent.path, &["bwt-*".to_string()], &mut errors).unwrap();
+        assert_eq!(found, vec![parent.path.join("bwt-api")]);
+        assert!(find_repositories(&parent.path, &["../*".to_string()], &mut errors).is_err());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn edit_distance_gives_up_past_the_bound() {
+        assert_eq!(bounded_edit_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(bounded_edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(bounded_edit_distance("a", "abcd", 2), None);
+        assert_eq!(bounded_edit_distance("same", "same", 0), Some(0));
+        assert_eq!(bounded_edit_distance("", "", 0), Some(0));
+    }
+
+    #[test]
+    fn identical_full_names_are_similar() {
+        assert!(similar_authors(
+            ("jane@acme.com", "Jane Doe"),
+            ("jdoe@home.net", "Jane Doe")
+        ));
+        // A single word is too common a name to merge on alone.
+        assert!(!similar_authors(
+            ("jane@acme.com", "jane"),
+            ("j@home.net", "jane")
+        ));
+    }
+
+    #[test]
+    fn near_duplicate_authors_are_suggested() {
+        assert!(similar_authors(
+            ("jane@example.com", "Jane"),
+            ("jane@exmaple.com", "J")
+        ));
+        assert!(similar_authors(
+            ("jsmith@acme.com", "J"),
+            ("jsmoth@acme.com", "S")
+        ));
+        assert!(similar_authors(
+            ("a@acme.com", "Jonathan Smith"),
+            ("b@home.net", "Jonathon Smith")
+        ));
+    }
+
+    #[test]
+    fn unrelated_authors_are_not_suggested() {
+        assert!(!similar_authors(
+            ("jane@acme.com", "Jane Doe"),
+            ("bob@acme.com", "Bob Stone")
+        ));
+        // Short mailboxes one edit apart are usually different people.
+        assert!(!similar_authors(
+            ("ann@acme.com", "A"),
+            ("dan@acme.com", "D")
+        ));
+        // Short names need to match exactly.
+        assert!(!similar_authors(
+            ("a@x.com", "Jan Doe"),
+            ("b@y.com", "Jane Doe")
+        ));
+        assert!(!similar_authors(
+            ("jane@acme.com", "Jane"),
+            ("jane@globex.org", "Jane")
+        ));
+    }
+
+    fn contribution(
+        author: &str,
+        email: &str,
+        commits: u32,
+        added: u32,
+        deleted: u32,
+    ) -> Contribution {
+        Contribution {
+            author: author.to_string(),
+            email: email.to_string(),
+            commits,
+            lines_added: added,
+            lines_deleted: deleted,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn email_domains_are_lowercased_and_keep_subdomains() {
+        assert_eq!(email_domain("Jane@ACME.com"), "acme.com");
+        assert_eq!(email_domain("jane@eu.mail.acme.com"), "eu.mail.acme.com");
+        assert_eq!(email_domain("a@b@corp.io"), "corp.io");
+        assert_eq!(email_domain("jane"), UNKNOWN_DOMAIN);
+        assert_eq!(email_domain("jane@"), UNKNOWN_DOMAIN);
+        assert_eq!(email_domain(""), UNKNOWN_DOMAIN);
+    }
+
+    #[test]
+    fn summary_keys_follow_the_grouping() {
+        assert_eq!(
+            GroupBy::Author.summary_key("Jane@Acme.com", "Jane"),
+            ("Jane@Acme.com".to_string(), "Jane".to_string())
+        );
+        assert_eq!(
+            GroupBy::Domain.summary_key("Jane@Acme.com", "Jane"),
+            ("@acme.com".to_string(), "acme.com".to_string())
+        );
+        assert_eq!(
+            GroupBy::Domain.summary_key("bob@eu.acme.com", "Bob"),
+            ("@eu.acme.com".to_string(), "eu.acme.com".to_string())
+        );
+        assert_eq!(
+            GroupBy::Domain.summary_key("root", "Root"),
+            (UNKNOWN_DOMAIN.to_string(), UNKNOWN_DOMAIN.to_string())
+        );
+    }
+
+    #[test]
+    fn domain_summaries_merge_differently_cased_emails() {
+        let map = HashMap::from([(
+            "api".to_string(),
+            vec![
+                contribution("Jane", "jane@Acme.com", 2, 10, 0),
+                contribution("Bob", "bob@acme.COM", 1, 5, 5),
+                contribution("Root", "root", 1, 1, 0),
+            ],
+        )]);
+        let summaries = calculate_summaries(
+            &map,
+            GroupBy::Domain,
+            SummaryBasis::Global,
+            LineWeights::default(),
+        );
+
+        let acme = summaries.iter().find(|s| s.email == "@acme.com").unwrap();
+        assert_eq!(acme.author, "acme.com");
+        assert_eq!(acme.total_commits, 3);
+        assert!(summaries.iter().any(|s| s.email == UNKNOWN_DOMAIN));
+        assert_eq!(summaries.len(), 2);
+    }
+
+    /// A large repository where Jane dominates, a small one that is all Bob's
+    /// and one without line changes, with contribution percentages filled in.
+    fn uneven_repositories() -> HashMap<String, Vec<Contribution>> {
+        let with_percent = |mut contribution: Contribution, percent: f64| {
+            contribution.contribution_percent = percent;
+            contribution
+        };
+        HashMap::from([
+            (
+                "big".to_string(),
+                vec![
+                    with_percent(contribution("Jane", "jane@acme.com", 9, 900, 0), 90.0),
+                    with_percent(contribution("Bob", "bob@acme.com", 1, 100, 0), 10.0),
+                ],
+            ),
+            (
+                "small".to_string(),
+                vec![with_percent(
+                    contribution("Bob", "bob@acme.com", 1, 10, 0),
+                    100.0,
+                )],
+            ),
+            (
+                "empty".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 1, 0, 0)],
+            ),
+        ])
+    }
+
+    fn overall_percent(summaries: &[AuthorSummary], email: &str) -> f64 {
+        summaries
+            .iter()
+            .find(|s| s.email == email)
+            .unwrap()
+            .overall_contribution_percent
+    }
+
+    #[test]
+    fn global_basis_weighs_repositories_by_size() {
+        let summaries = calculate_summaries(
+            &uneven_repositories(),
+            GroupBy::Author,
+            SummaryBasis::Global,
+            LineWeights::default(),
+        );
+        assert!(
+            (overall_percent(&summaries, "jane@acme.com") - 900.0 / 1010.0 * 100.0).abs() < 1e-9
+        );
+        assert!(
+            (overall_percent(&summaries, "bob@acme.com") - 110.0 / 1010.0 * 100.0).abs() < 1e-9
+        );
+    }
+
+    #[test]
+    fn per_repository_basis_averages_over_repositories_with_lines() {
+        let summaries = calculate_summaries(
+            &uneven_repositories(),
+            GroupBy::Author,
+            SummaryBasis::PerRepoAverage,
+            LineWeights::default(),
+        );
+        // `empty` has no line changes, so only two repositories are averaged.
+        assert!((overall_percent(&summaries, "jane@acme.com") - 45.0).abs() < 1e-9);
+        assert!((overall_percent(&summaries, "bob@acme.com") - 55.0).abs() < 1e-9);
+        assert_eq!(SummaryBasis::Global.title_note(), "");
+    }
+
+    #[test]
+    fn per_repository_basis_without_line_changes_is_zero() {
+        let map = HashMap::from([(
+            "empty".to_string(),
+            vec![contribution("Jane", "jane@acme.com", 1, 0, 0)],
+        )]);
+        let summaries = calculate_summaries(
+            &map,
+            GroupBy::Author,
+            SummaryBasis::PerRepoAverage,
+            LineWeights::default(),
+        );
+        assert_eq!(overall_percent(&summaries, "jane@acme.com"), 0.0);
+    }
+
+    #[test]
+    fn line_weights_are_validated() {
+        assert!(LineWeights::new(2.0, 0.5).is_ok());
+        assert!(LineWeights::new(1.0, 0.0).is_ok());
+        assert!(LineWeights::new(0.0, 0.0).is_err());
+        assert!(LineWeights::new(-1.0, 1.0).is_err());
+        assert!(LineWeights::new(f64::NAN, 1.0).is_err());
+        assert!(LineWeights::new(1.0, f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn equal_line_weights_give_plain_shares() {
+        let weights = LineWeights::default();
+        assert_eq!(weights.weigh(3, 2), 5.0);
+        assert_eq!(weights.percent(3, 2, 15, 5), 25.0);
+        assert_eq!(weights.percent(0, 0, 0, 0), 0.0);
+
+        let additions_only = LineWeights::new(1.0, 0.0).unwrap();
+        assert_eq!(additions_only.percent(3, 2, 15, 5), 20.0);
+    }
+
+    #[test]
+    fn line_weights_can_change_the_ranking() {
+        let repository = TestRepository::new("line-weights");
+        repository.commit(
+            "Jane",
+            "jane@acme.com",
+            T0,
+            "a.txt",
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n",
+        );
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "a.txt", "x\n");
+
+        let rank_of = |contributions: &[Contribution], email: &str| {
+            contributions
+                .iter()
+                .find(|c| c.email == email)
+                .unwrap()
+                .rank
+        };
+
+        // Bob changed 11 lines (1 added, 10 deleted) to Jane's 10.
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        assert_eq!(rank_of(&contributions, "bob@acme.com"), 1);
+        assert_eq!(rank_of(&contributions, "jane@acme.com"), 2);
+
+        let options = AnalysisOptions {
+            line_weights: LineWeights::new(1.0, 0.0).unwrap(),
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(rank_of(&contributions, "jane@acme.com"), 1);
+        assert_eq!(rank_of(&contributions, "bob@acme.com"), 2);
+    }
+
+    #[test]
+    fn the_most_committed_email_spelling_wins_across_maps() {
+        let mut current = HashMap::from([
+            (
+                "api".to_string(),
+                vec![contribution("Jane", "Jane@Acme.com", 5, 1, 0)],
+            ),
+            (
+                "web".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 2, 1, 0)],
+            ),
+        ]);
+        let mut baseline = HashMap::from([(
+            "api".to_string(),
+            vec![contribution("Jane", "JANE@ACME.COM", 1, 1, 0)],
+        )]);
+        let options = AnalysisOptions {
+            normalize_emails: true,
+            ..Default::default()
+        };
+
+        unify_email_forms(&mut [&mut current, &mut baseline], &options);
+        for contrib in current.values().chain(baseline.values()).flatten() {
+            assert_eq!(contrib.email, "Jane@Acme.com");
+        }
+    }
+
+    #[test]
+    fn email_spellings_are_left_alone_without_normalization() {
+        let mut map = HashMap::from([(
+            "api".to_string(),
+            vec![
+                contribution("Jane", "Jane@Acme.com", 5, 1, 0),
+                contribution("Jane", "jane@acme.com", 2, 1, 0),
+            ],
+        )]);
+        let options = AnalysisOptions {
+            normalize_emails: false,
+            ..Default::default()
+        };
+
+        unify_email_forms(&mut [&mut map], &options);
+        let emails: Vec<&str> = map["api"].iter().map(|c| c.email.as_str()).collect();
+        assert_eq!(emails, ["Jane@Acme.com", "jane@acme.com"]);
+    }
+
+    #[test]
+    fn equally_common_email_spellings_pick_the_smallest() {
+        let forms = HashMap::from([
+            ("jane@acme.com", 2),
+            ("Jane@Acme.com", 2),
+            ("JANE@acme.com", 1),
+        ]);
+        assert_eq!(most_frequent_form(&forms), "Jane@Acme.com");
+        assert_eq!(most_frequent_form(&HashMap::new()), "");
+    }
+
+    #[test]
+    fn automation_accounts_are_bots() {
+        assert!(is_bot(
+            "dependabot[bot]",
+            "49699333+dependabot[bot]@users.noreply.github.com"
+        ));
+        assert!(is_bot("Renovate", "renovate@whitesourcesoftware.com"));
+        assert!(is_bot("CI", "deploy-bot@acme.com"));
+        assert!(is_bot("Release Bot", "ci@acme.com"));
+        assert!(is_bot("GitHub-Actions", "actions@github.com"));
+    }
+
+    #[test]
+    fn people_with_bot_like_names_are_not_bots() {
+        assert!(!is_bot("Abbott", "abbott@acme.com"));
+        assert!(!is_bot("Jane Doe", "robotics@acme.com"));
+        assert!(!is_bot("Bo Botha", "bo@acme.com"));
+    }
+
+    #[test]
+    fn bots_fold_into_one_automation_row_per_repository() {
+        let mut map = HashMap::from([
+            (
+                "api".to_string(),
+                vec![
+                    contribution("Jane", "jane@acme.com", 3, 30, 0),
+                    contribution(
+                        "dependabot[bot]",
+                        "1+dependabot[bot]@users.noreply.github.com",
+                        4,
+                        8,
+                        8,
+                    ),
+                    contribution("Renovate", "renovate@acme.com", 2, 4, 4),
+                ],
+            ),
+            (
+                "web".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 1, 5, 0)],
+            ),
+        ]);
+
+        let merge = merge_bots(&mut [&mut map]).unwrap();
+        assert_eq!(merge.email, AUTOMATION_EMAIL);
+        assert_eq!(
+            merge.variants,
+            [
+                "1+dependabot[bot]@users.noreply.github.com",
+                "renovate@acme.com"
+            ]
+        );
+
+        let api = &map["api"];
+        assert_eq!(api.len(), 2);
+        let automation = api.iter().find(|c| c.email == AUTOMATION_EMAIL).unwrap();
+        assert_eq!(automation.author, AUTOMATION_AUTHOR);
+        assert_eq!(automation.commits, 6);
+        assert_eq!(automation.lines_added + automation.lines_deleted, 24);
+        assert_eq!(map["web"].len(), 1);
+    }
+
+    #[test]
+    fn merging_bots_without_any_changes_nothing() {
+        let mut map = HashMap::from([(
+            "api".to_string(),
+            vec![contribution("Jane", "jane@acme.com", 3, 30, 0)],
+        )]);
+        assert!(merge_bots(&mut [&mut map]).is_none());
+        assert_eq!(map["api"][0].email, "jane@acme.com");
+    }
+
+    #[test]
+    fn repositories_sort_by_the_chosen_order() {
+        let mut jane = contribution("Jane", "jane@acme.com", 3, 10, 5);
+        jane.last_commit_at = Some(T0);
+        let mut bob = contribution("Bob", "bob@acme.com", 2, 1, 0);
+        bob.last_commit_at = Some(T0 + 60);
+        let contributions = [jane, bob];
+
+        assert_eq!(RepositoryOrder::Name.sort_key(&contributions), 0);
+        assert_eq!(RepositoryOrder::Commits.sort_key(&contributions), 5);
+        assert_eq!(RepositoryOrder::Recent.sort_key(&contributions), T0 + 60);
+        assert_eq!(RepositoryOrder::Size.sort_key(&contributions), 16);
+    }
+
+    #[test]
+    fn repositories_without_commits_sort_last() {
+        for order in [RepositoryOrder::Commits, RepositoryOrder::Size] {
+            assert_eq!(order.sort_key(&[]), 0);
+        }
+        assert_eq!(RepositoryOrder::Recent.sort_key(&[]), i64::MIN);
+        let undated = [contribution("Jane", "jane@acme.com", 1, 1, 0)];
+        assert_eq!(RepositoryOrder::Recent.sort_key(&undated), i64::MIN);
+    }
+
+    #[test]
+    fn repositories_are_analyzed_busiest_first() {
+        let quiet = TestRepository::new("order/quiet");
+        quiet.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        let busy = TestRepository::new("order/busy");
+        busy.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        busy.commit("Jane", "jane@acme.com", T0 + 60, "a.txt", "b\n");
+        let empty = TestRepository::new("order/empty");
+
+        let options = AnalysisOptions::default();
+        let mut paths = vec![empty.path.clone(), quiet.path.clone(), busy.path.clone()];
+        RepositoryOrder::Name.arrange(&mut paths, &options);
+        assert_eq!(
+            paths,
+            [empty.path.clone(), quiet.path.clone(), busy.path.clone()]
+        );
+
+        RepositoryOrder::Commits.arrange(&mut paths, &options);
+        assert_eq!(
+            paths,
+            [busy.path.clone(), quiet.path.clone(), empty.path.clone()]
+        );
+    }
+
+    #[test]
+    fn repository_distribution_shares_sum_to_a_hundred() {
+        let map = HashMap::from([
+            (
+                "api".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 3, 70, 0)],
+            ),
+            (
+                "web".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 1, 20, 7)],
+            ),
+            (
+                "docs".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 1, 2, 1)],
+            ),
+            (
+                "empty".to_string(),
+                vec![contribution("Jane", "jane@acme.com", 1, 0, 0)],
+            ),
+        ]);
+        let summaries = calculate_author_summaries(&map);
+        let distribution = &summaries[0].repo_distribution;
+
+        // Repositories without lines from the author are left out; the rest
+        // are ordered by share.
+        let repos: Vec<&str> = distribution.iter().map(|(repo, _)| repo.as_str()).collect();
+        assert_eq!(repos, ["api", "web", "docs"]);
+        assert!((distribution[0].1 - 70.0).abs() < 1e-9);
+        let total: f64 = distribution.iter().map(|(_, percent)| percent).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn authors_without_line_changes_have_no_distribution() {
+        let map = HashMap::from([(
+            "api".to_string(),
+            vec![contribution("Jane", "jane@acme.com", 2, 0, 0)],
+        )]);
+        assert!(calculate_author_summaries(&map)[0]
+            .repo_distribution
+            .is_empty());
+    }
+
+    /// `count` authors with equal lines whose latest commit was `days_ago`.
+    fn equal_authors(count: usize, days_ago: i64) -> Vec<Contribution> {
+        (0..count)
+            .map(|i| {
+                let mut contrib = contribution("Dev", &format!("dev{}@acme.com", i), 1, 10, 0);
+                contrib.last_commit_at = Some(T0 - days_ago * SECONDS_PER_DAY as i64);
+                contrib
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repositories_without_line_data_have_no_health() {
+        assert!(repository_health(&[], OwnershipBasis::Lines, T0).is_none());
+        let binary_only = [contribution("Jane", "jane@acme.com", 2, 0, 0)];
+        assert!(repository_health(&binary_only, OwnershipBasis::Lines, T0).is_none());
+    }
+
+    #[test]
+    fn a_busy_evenly_shared_repository_scores_full_marks() {
+        let health = repository_health(&equal_authors(10, 0), OwnershipBasis::Lines, T0).unwrap();
+        assert_eq!(health.score, 100);
+        assert_eq!(health.contributors, 10);
+        assert_eq!(health.gini, 0.0);
+        assert_eq!(health.days_since_last_commit, Some(0));
+        // Contributors beyond the healthy count earn nothing more.
+        assert_eq!(
+            repository_health(&equal_authors(25, 0), OwnershipBasis::Lines, T0)
+                .unwrap()
+                .score,
+            100
+        );
+    }
+
+    #[test]
+    fn recency_counts_fully_within_a_month_and_not_after_a_year() {
+        // One author: bus factor 10 points, spread 25 and contributors 2.
+        let score = |days_ago| {
+            repository_health(&equal_authors(1, days_ago), OwnershipBasis::Lines, T0)
+                .unwrap()
+                .score
+        };
+        assert_eq!(score(0), 62);
+        assert_eq!(score(30), 62);
+        assert_eq!(score(31), 62);
+        assert_eq!(score(300), 42);
+        assert_eq!(score(365), 37);
+        assert_eq!(score(2000), 37);
+        // A commit dated after `now` counts as today.
+        assert_eq!(score(-5), 62);
+    }
+
+    #[test]
+    fn health_breakdown_names_each_component() {
+        let health = repository_health(&equal_authors(1, 1), OwnershipBasis::Lines, T0).unwrap();
+        assert_eq!(
+            health.breakdown(),
+            "bus factor 1, Gini 0.00, 1 contributor, last commit 1 day ago"
+        );
+    }
+
+    #[test]
+    fn excludes_files_skip_comments_and_blank_lines() {
+        let path = temp_file(
+            "excludes",
+            "# generated code\n\nvendor/\n   \n*.min.js  \n\\#notes.md\n\\!important.txt\n",
+        );
+        assert_eq!(
+            read_excludes_file(&path).unwrap(),
+            ["vendor/", "*.min.js", "#notes.md", "!important.txt"]
+        );
+    }
+
+    #[test]
+    fn negated_exclude_patterns_are_rejected_with_their_line() {
+        let path = temp_file("negated-excludes", "# keep\nbuild/\n!build/keep.txt\n");
+        let error = read_excludes_file(&path).unwrap_err().to_string();
+        assert!(error.ends_with(":3: negated pattern '!build/keep.txt' is not supported"));
+    }
+
+    #[test]
+    fn exclude_patterns_follow_gitignore_anchoring() {
+        assert_eq!(
+            exclude_pathspecs("*.lock"),
+            [":(exclude,glob)**/*.lock/**", ":(exclude,glob)**/*.lock"]
+        );
+        assert_eq!(
+            exclude_pathspecs("vendor/"),
+            [":(exclude,glob)**/vendor/**"]
+        );
+        assert_eq!(
+            exclude_pathspecs("/docs"),
+            [":(exclude,glob)docs/**", ":(exclude,glob)docs"]
+        );
+        assert_eq!(
+            exclude_pathspecs("src/gen"),
+            [":(exclude,glob)src/gen/**", ":(exclude,glob)src/gen"]
+        );
+        assert_eq!(
+            exclude_pathspecs("**/fixtures"),
+            [
+                ":(exclude,glob)**/fixtures/**",
+                ":(exclude,glob)**/fixtures"
+            ]
+        );
+    }
+
+    #[test]
+    fn excluded_paths_do_not_count() {
+        let repository = TestRepository::new("excludes");
+        repository.commit("Jane", "jane@acme.com", T0, "src/lib.rs", "a\nb\n");
+        repository.commit(
+            "Jane",
+            "jane@acme.com",
+            T0 + 60,
+            "lib/vendor/dep.js",
+            "1\n2\n3\n",
+        );
+        repository.commit("Jane", "jane@acme.com", T0 + 120, "app.min.js", "x\n");
+
+        let options = AnalysisOptions {
+            excludes: vec!["vendor/".to_string(), "*.min.js".to_string()],
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(contributions[0].lines_added, 2);
+    }
+
+    #[test]
+    fn ignored_commits_match_by_hash_prefix() {
+        let hash = "3f2a9c0d1e8b7a6f5e4d3c2b1a0f9e8d7c6b5a49";
+        let ignoring = |ignore: &[&str]| AnalysisOptions {
+            ignore_commits: ignore.iter().map(|h| h.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert!(!AnalysisOptions::default().ignores_commit(hash));
+        assert!(ignoring(&[hash]).ignores_commit(hash));
+        assert!(ignoring(&["3f2a"]).ignores_commit(hash));
+        assert!(!ignoring(&["3f2b"]).ignores_commit(hash));
+        // A prefix of another commit does not cover a shorter hash.
+        assert!(!ignoring(&[&format!("{}0", hash)]).ignores_commit(hash));
+    }
+
+    #[test]
+    fn commits_outside_only_commits_are_ignored() {
+        let options = AnalysisOptions {
+            only_commits: Some(HashSet::from(["aaaa".to_string()])),
+            ..Default::default()
+        };
+        assert!(!options.ignores_commit("aaaa"));
+        assert!(options.ignores_commit("bbbb"));
+
+        let nothing_kept = AnalysisOptions {
+            only_commits: Some(HashSet::new()),
+            ..Default::default()
+        };
+        assert!(nothing_kept.ignores_commit("aaaa"));
+    }
+
+    #[test]
+    fn ignore_commits_files_take_hashes_with_notes() {
+        let path = temp_file(
+            "ignore-revs",
+            "# formatting\n3F2A9C0d reformat everything\n\n  abcd  # bump\n",
+        );
+        assert_eq!(
+            read_ignore_commits_file(&path).unwrap(),
+            ["3f2a9c0d", "abcd"]
+        );
+
+        let path = temp_file("bad-ignore-revs", "abcd\nabc\n");
+        let error = read_ignore_commits_file(&path).unwrap_err().to_string();
+        assert!(error.ends_with(":2: 'abc' is not a commit hash"));
+    }
+
+    #[test]
+    fn ignored_commits_do_not_count() {
+        let repository = TestRepository::new("ignore-commits");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "b.txt", "1\n2\n3\n");
+
+        let options = AnalysisOptions {
+            ignore_commits: vec![repository.head()[..7].to_string()],
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].email, "jane@acme.com");
+    }
+
+    #[test]
+    fn every_signature_status_but_none_is_signed() {
+        for status in ["G", "U", "X", "Y", "R", "B", "E"] {
+            assert!(is_signed(status), "{}", status);
+        }
+        for status in ["N", "", "g", "GG", " "] {
+            assert!(!is_signed(status), "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn signed_percent_of_no_commits_is_zero() {
+        assert_eq!(signed_percent(0, 0), 0.0);
+        assert_eq!(signed_percent(1, 4), 25.0);
+        assert_eq!(signed_percent(4, 4), 100.0);
+    }
+
+    #[test]
+    fn ssh_signed_commits_count_without_an_allowed_signers_file() {
+        let repository = TestRepository::new("signatures");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+
+        let key = repository.path.join(".git/signing-key");
+        let generated = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status();
+        if !generated.is_ok_and(|status| status.success()) {
+            eprintln!("ssh-keygen unavailable, skipping the signed commit");
+            return;
+        }
+        fs::write(repository.path.join("b.txt"), "b\n").unwrap();
+        repository.git(&["add", "--all"], &[]);
+        let date = format!("{} +0000", T0 + 60);
+        repository.git(
+            &[
+                "-c",
+                "gpg.format=ssh",
+                "-c",
+                &format!("user.signingkey={}", key.display()),
+                "commit",
+                "--quiet",
+                "-S",
+                "-m",
+                "Signed",
+            ],
+            &[
+                ("GIT_AUTHOR_NAME", "Jane"),
+                ("GIT_AUTHOR_EMAIL", "jane@acme.com"),
+                ("GIT_AUTHOR_DATE", &date),
+                ("GIT_COMMITTER_NAME", "Jane"),
+                ("GIT_COMMITTER_EMAIL", "jane@acme.com"),
+                ("GIT_COMMITTER_DATE", &date),
+            ],
+        );
+
+        let options = AnalysisOptions {
+            signatures: true,
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(contributions[0].commits, 2);
+        assert_eq!(contributions[0].signed_commits, 1);
+    }
+
+    #[test]
+    fn shallow_clones_are_flagged() {
+        let source = TestRepository::new("shallow/source");
+        source.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        source.commit("Bob", "bob@acme.com", T0 + 60, "b.txt", "b\n");
+        source.commit("Jane", "jane@acme.com", T0 + 120, "a.txt", "c\n");
+
+        let clone = TestRepository::new("shallow/clone");
+        fs::remove_dir_all(&clone.path).unwrap();
+        let url = format!("file://{}", source.path.display());
+        let clone_path = clone.path.to_string_lossy().to_string();
+        source.git(
+            &["clone", "--quiet", "--depth", "1", &url, &clone_path],
+            &[],
+        );
+
+        let (_, info) = source.analyze(&AnalysisOptions::default());
+        assert!(!info.shallow);
+        assert!(!is_shallow_repository(&source.path).unwrap());
+
+        let (contributions, info) = clone.analyze(&AnalysisOptions::default());
+        assert!(info.shallow);
+        assert!(is_shallow_repository(&clone.path).unwrap());
+        // Only the tip commit made it into the clone.
+        assert_eq!(repository_commit_count(&contributions), 1);
+    }
+
+    #[test]
+    fn accented_paths_reach_directories_and_languages_unquoted() {
+        let repository = TestRepository::new("accented-paths");
+        // Quoting stays off even where the repository asks for it.
+        repository.git(&["config", "core.quotepath", "true"], &[]);
+        repository.commit("Jane", "jane@acme.com", T0, "café/ñandú.rs", "a\nb\n");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        let jane = &contributions[0];
+        assert_eq!(jane.directory_lines.get("café"), Some(&2));
+        assert_eq!(jane.language_lines.get("Rust"), Some(&2));
+    }
+
+    /// Jane makes one large change to `core`, Bob and Mia many small ones.
+    fn lopsided_history() -> Vec<Contribution> {
+        let mut jane = contribution("Jane", "jane@acme.com", 1, 100, 0);
+        jane.directory_lines = HashMap::from([("core".to_string(), 100)]);
+        jane.directory_commits = HashMap::from([("core".to_string(), 1)]);
+        let mut bob = contribution("Bob", "bob@acme.com", 5, 10, 0);
+        bob.directory_lines = HashMap::from([("core".to_string(), 10)]);
+        bob.directory_commits = HashMap::from([("core".to_string(), 5)]);
+        let mut mia = contribution("Mia", "mia@int.io", 4, 8, 0);
+        mia.directory_lines = HashMap::from([("core".to_string(), 8)]);
+        mia.directory_commits = HashMap::from([("core".to_string(), 4)]);
+        vec![jane, bob, mia]
+    }
+
+    #[test]
+    fn directory_ownership_flips_with_the_basis() {
+        let contributions = lopsided_history();
+
+        let by_lines = directory_owners(&contributions, OwnershipBasis::Lines);
+        assert_eq!(by_lines[0].email, "jane@acme.com");
+        assert!((by_lines[0].percent - 100.0 / 118.0 * 100.0).abs() < 1e-9);
+
+        let by_commits = directory_owners(&contributions, OwnershipBasis::Commits);
+        assert_eq!(by_commits[0].email, "bob@acme.com");
+        assert!((by_commits[0].percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn orphaned_directories_follow_the_basis() {
+        let map = HashMap::from([("api".to_string(), lopsided_history())]);
+        assert!(orphaned_directories(&map, OwnershipBasis::Lines, 50.0).is_empty());
+
+        let orphaned = orphaned_directories(&map, OwnershipBasis::Commits, 50.0);
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].directory, "core");
+        assert_eq!(
+            orphaned[0].ownership_spread(0),
+            "Bob 50%, Mia 40%, Jane 10%"
+        );
+    }
+
+    #[test]
+    fn bus_factor_flips_with_the_basis() {
+        let contributions = lopsided_history();
+        assert_eq!(bus_factor(&contributions, OwnershipBasis::Lines), 1);
+        assert_eq!(bus_factor(&contributions, OwnershipBasis::Commits), 2);
+        assert_eq!(bus_factor(&[], OwnershipBasis::Commits), 0);
+
+        let health = |basis| repository_health(&contributions, basis, T0).unwrap();
+        assert_eq!(health(OwnershipBasis::Lines).bus_factor, 1);
+        assert_eq!(health(OwnershipBasis::Commits).bus_factor, 2);
+    }
+
+    #[test]
+    fn binary_only_repositories_have_no_line_data() {
+        let repository = TestRepository::new("binary-only");
+        repository.commit("Jane", "jane@acme.com", T0, "logo.png", "\0\u{1}\u{2}PNG\0");
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "icon.png", "\0\u{3}\0ICO\0");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        assert_eq!(repository_commit_count(&contributions), 2);
+        assert!(!has_line_data(&contributions));
+        assert!(contributions.iter().all(|c| c.contribution_percent == 0.0));
+    }
+
+    #[test]
+    fn repositories_with_changed_lines_or_no_commits_have_line_data() {
+        assert!(has_line_data(&[]));
+        assert!(has_line_data(&[contribution(
+            "Jane",
+            "jane@acme.com",
+            0,
+            0,
+            0
+        )]));
+        assert!(has_line_data(&[
+            contribution("Jane", "jane@acme.com", 1, 0, 0),
+            contribution("Bob", "bob@acme.com", 1, 0, 1),
+        ]));
+    }
+
+    #[test]
+    fn committer_identity_credits_whoever_applied_the_patch() {
+        let repository = TestRepository::new("committer-identity");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        fs::write(repository.path.join("b.txt"), "1\n2\n").unwrap();
+        repository.git(&["add", "--all"], &[]);
+        let date = format!("{} +0000", T0 + 60);
+        repository.git(
+            &["commit", "--quiet", "-m", "Apply Bob's patch"],
+            &[
+                ("GIT_AUTHOR_NAME", "Bob"),
+                ("GIT_AUTHOR_EMAIL", "bob@acme.com"),
+                ("GIT_AUTHOR_DATE", &date),
+                ("GIT_COMMITTER_NAME", "Jane"),
+                ("GIT_COMMITTER_EMAIL", "jane@acme.com"),
+                ("GIT_COMMITTER_DATE", &date),
+            ],
+        );
+
+        let (by_author, _) = repository.analyze(&AnalysisOptions::default());
+        let commits = |contributions: &[Contribution], email: &str| {
+            contributions
+                .iter()
+                .find(|c| c.email == email)
+                .map_or(0, |c| c.commits)
+        };
+        assert_eq!(commits(&by_author, "jane@acme.com"), 1);
+        assert_eq!(commits(&by_author, "bob@acme.com"), 1);
+
+        let options = AnalysisOptions {
+            identity: Identity::Committer,
+            ..Default::default()
+        };
+        let (by_committer, _) = repository.analyze(&options);
+        assert_eq!(by_committer.len(), 1);
+        assert_eq!(commits(&by_committer, "jane@acme.com"), 2);
+        assert_eq!(by_committer[0].lines_added, 3);
+    }
+
+    #[test]
+    fn panic_messages_are_recovered_from_their_payload() {
+        let literal = panic::catch_unwind(|| panic!("bad numstat")).unwrap_err();
+        assert_eq!(panic_message(&*literal), "bad numstat");
+        let formatted = panic::catch_unwind(|| panic!("bad line {}", 3)).unwrap_err();
+        assert_eq!(panic_message(&*formatted), "bad line 3");
+        let other = panic::catch_unwind(|| panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(&*other), "unknown panic");
+    }
+
+    #[test]
+    fn a_failing_repository_is_reported_and_skipped() {
+        let first = TestRepository::new("failing/first");
+        first.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        // Removed after discovery, so git cannot even be started in it.
+        let broken = TestRepository::new("failing/broken");
+        fs::remove_dir_all(&broken.path).unwrap();
+        let last = TestRepository::new("failing/last");
+        last.commit("Bob", "bob@acme.com", T0, "b.txt", "b\n");
+
+        let mut analyzed = Vec::new();
+        let mut failed = Vec::new();
+        for_each_repository(
+            vec![first.path.clone(), broken.path.clone(), last.path.clone()],
+            AnalysisOptions::default(),
+            |name, _, _| {
+                analyzed.push(name.to_string());
+                Ok(())
+            },
+            |path, _| failed.push(path.to_path_buf()),
+        )
+        .unwrap();
+
+        assert_eq!(analyzed, ["first", "last"]);
+        assert_eq!(failed, std::slice::from_ref(&broken.path));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hung_commands_are_killed_at_the_timeout() {
+        let options = AnalysisOptions {
+            timeout: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let error = with_git_limits(&options, || git_output(Command::new("sleep").arg("30")))
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(error, "git timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancelling_stops_a_running_command() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let options = AnalysisOptions {
+            cancel: Some(cancel.clone()),
+            ..Default::default()
+        };
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            cancel.store(true, Ordering::Relaxed);
+        });
+
+        let started = Instant::now();
+        let error = with_git_limits(&options, || git_output(Command::new("sleep").arg("30")))
+            .unwrap_err()
+            .to_string();
+        canceller.join().unwrap();
+
+        assert_eq!(error, "analysis cancelled");
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // Later commands do not even start.
+        let error = with_git_limits(&options, || git_output(&mut Command::new("true")))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "analysis cancelled");
+    }
+
+    #[test]
+    fn commands_within_their_limits_finish_normally() {
+        let repository = TestRepository::new("limits");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        let options = AnalysisOptions {
+            timeout: Some(Duration::from_secs(60)),
+            cancel: Some(Arc::new(AtomicBool::new(false))),
+            ..Default::default()
+        };
+
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(contributions[0].commits, 1);
+    }
+
+    #[test]
+    fn kept_commits_add_up_to_the_totals() {
+        let repository = TestRepository::new("keep-commits");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "1\n2\n3\n");
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "b.txt", "x\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 120, "a.txt", "1\n3\n4\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 180, "logo.png", "\0PNG\0");
+
+        let options = AnalysisOptions {
+            keep_commits: true,
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        for contrib in &contributions {
+            assert_eq!(contrib.kept_commits.len() as u32, contrib.commits);
+            let added: u32 = contrib.kept_commits.iter().map(|c| c.lines_added).sum();
+            let deleted: u32 = contrib.kept_commits.iter().map(|c| c.lines_deleted).sum();
+            assert_eq!(
+                (added, deleted),
+                (contrib.lines_added, contrib.lines_deleted)
+            );
+        }
+
+        let jane = contributions.iter().find(|c| c.author == "Jane").unwrap();
+        let timestamps: Vec<i64> = jane.kept_commits.iter().map(|c| c.timestamp).collect();
+        assert_eq!(timestamps, [T0 + 180, T0 + 120, T0]);
+        assert_eq!(jane.kept_commits[1].subject, "Change a.txt");
+        assert_eq!(
+            (
+                jane.kept_commits[1].lines_added,
+                jane.kept_commits[1].lines_deleted
+            ),
+            (1, 1)
+        );
+        assert_eq!(jane.kept_commits[0].hash, repository.head());
+    }
+
+    #[test]
+    fn commits_are_only_kept_when_asked_for() {
+        let repository = TestRepository::new("no-keep-commits");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions::default());
+        assert!(contributions[0].kept_commits.is_empty());
+    }
+
+    #[test]
+    fn blame_looks_through_ignored_reformatting_commits() {
+        let repository = TestRepository::new("blame-ignore-revs");
+        repository.commit(
+            "Jane",
+            "jane@acme.com",
+            T0,
+            "lib.rs",
+            "fn a(){}\nfn b(){}\nfn c(){}\n",
+        );
+        repository.commit(
+            "Fmt",
+            "fmt@acme.com",
+            T0 + 60,
+            "lib.rs",
+            "fn a() {}\nfn b() {}\nfn c() {}\n",
+        );
+        let surviving = |contributions: &[Contribution], email: &str| {
+            contributions
+                .iter()
+                .find(|c| c.email == email)
+                .map_or(0, |c| c.surviving_lines)
+        };
+        let options = AnalysisOptions {
+            blame: true,
+            ..Default::default()
+        };
+
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(surviving(&contributions, "fmt@acme.com"), 3);
+        assert_eq!(surviving(&contributions, "jane@acme.com"), 0);
+
+        let ignore_revs = format!("# rustfmt\n{}\n", repository.head());
+        fs::write(repository.path.join(BLAME_IGNORE_REVS_FILE), ignore_revs).unwrap();
+        let (contributions, _) = repository.analyze(&options);
+        assert_eq!(surviving(&contributions, "jane@acme.com"), 3);
+        assert_eq!(surviving(&contributions, "fmt@acme.com"), 0);
+    }
+
+    #[test]
+    fn file_pathspecs_match_one_literal_path_from_the_root() {
+        assert_eq!(file_pathspec("src/lib.rs"), ":(top,literal)src/lib.rs");
+        assert_eq!(file_pathspec("./src/lib.rs"), ":(top,literal)src/lib.rs");
+        assert_eq!(file_pathspec("/src/[id].rs"), ":(top,literal)src/[id].rs");
+    }
+
+    #[test]
+    fn file_option_counts_only_commits_touching_that_file() {
+        let repository = TestRepository::new("single-file");
+        repository.commit("Jane", "jane@acme.com", T0, "src/lib.rs", "1\n2\n3\n4\n");
+        repository.commit(
+            "Bob",
+            "bob@acme.com",
+            T0 + 60,
+            "src/lib.rs",
+            "1\n2\n3\n4\n5\n",
+        );
+        repository.commit(
+            "Bob",
+            "bob@acme.com",
+            T0 + 120,
+            "README.md",
+            "a\nb\nc\nd\ne\nf\n",
+        );
+        repository.commit("Mia", "mia@int.io", T0 + 180, "src/main.rs", "x\n");
+
+        let options = AnalysisOptions {
+            file: Some("src/lib.rs".to_string()),
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        let row = |email: &str| contributions.iter().find(|c| c.email == email).unwrap();
+
+        assert_eq!(contributions.len(), 2);
+        assert_eq!(row("jane@acme.com").lines_added, 4);
+        assert_eq!(
+            (row("bob@acme.com").commits, row("bob@acme.com").lines_added),
+            (1, 1)
+        );
+        assert!((row("jane@acme.com").contribution_percent - 80.0).abs() < 1e-9);
+        assert!((row("bob@acme.com").contribution_percent - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn file_option_on_a_missing_file_finds_nothing() {
+        let repository = TestRepository::new("single-file-missing");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+
+        let options = AnalysisOptions {
+            file: Some("no/such/file.rs".to_string()),
+            ..Default::default()
+        };
+        let (contributions, _) = repository.analyze(&options);
+        assert!(contributions.is_empty());
+    }
+
+    #[test]
+    fn languages_come_from_the_current_extension() {
+        assert_eq!(language_of("src/main.rs"), "Rust");
+        assert_eq!(language_of("web/App.TSX"), "TypeScript");
+        assert_eq!(language_of("Makefile"), "Makefile");
+        assert_eq!(language_of("docker/Dockerfile"), "Dockerfile");
+        assert_eq!(language_of("lib/{util.js => util.ts}"), "TypeScript");
+        assert_eq!(language_of("old.py => new.go"), "Go");
+        assert_eq!(language_of(".gitignore"), OTHER_LANGUAGE);
+        assert_eq!(language_of("LICENSE"), OTHER_LANGUAGE);
+        assert_eq!(language_of("data.bin"), OTHER_LANGUAGE);
+    }
+
+    #[test]
+    fn language_footprint_totals_every_repository() {
+        let api = TestRepository::new("footprint/api");
+        api.commit("Jane", "jane@acme.com", T0, "src/main.rs", "1\n2\n3\n");
+        api.commit("Bob", "bob@acme.com", T0 + 60, "src/lib.rs", "1\n2\n");
+        api.commit("Bob", "bob@acme.com", T0 + 120, "build.py", "1\n");
+        let web = TestRepository::new("footprint/web");
+        web.commit("Mia", "mia@int.io", T0, "app.ts", "1\n2\n3\n4\n");
+        web.commit("Mia", "mia@int.io", T0 + 60, "ui.tsx", "1\n");
+        web.commit("Mia", "mia@int.io", T0 + 120, "LICENSE", "MIT\n");
+
+        let options = AnalysisOptions::default();
+        let map = HashMap::from([
+            ("api".to_string(), api.analyze(&options).0),
+            ("web".to_string(), web.analyze(&options).0),
+        ]);
+        let footprint: Vec<(String, u32, usize)> = language_footprint(&map)
+            .into_iter()
+            .map(|f| (f.language, f.lines_changed, f.repositories))
+            .collect();
+
+        assert_eq!(
+            footprint,
+            [
+                ("Rust".to_string(), 5, 1),
+                ("TypeScript".to_string(), 5, 1),
+                ("Other".to_string(), 1, 1),
+                ("Python".to_string(), 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn language_footprint_ranks_surviving_lines_first() {
+        let mut jane = contribution("Jane", "jane@acme.com", 1, 50, 0);
+        jane.language_lines = HashMap::from([("Rust".to_string(), 50)]);
+        jane.file_lines = HashMap::from([("src/lib.rs".to_string(), 5)]);
+        let mut bob = contribution("Bob", "bob@acme.com", 1, 10, 0);
+        bob.language_lines = HashMap::from([("Go".to_string(), 10)]);
+        bob.file_lines = HashMap::from([("main.go".to_string(), 10)]);
+        let map = HashMap::from([
+            ("api".to_string(), vec![jane]),
+            ("svc".to_string(), vec![bob]),
+        ]);
+
+        let footprint = language_footprint(&map);
+        assert_eq!(footprint[0].language, "Go");
+        assert_eq!(footprint[0].surviving_lines, 10);
+        assert_eq!(footprint[1].language, "Rust");
+        assert_eq!(footprint[1].lines_changed, 50);
+        assert!(language_footprint(&HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn overlapping_patterns_find_each_repository_once() {
+        let parent = TestRepository::new("multi-pattern");
+        let _api_core = TestRepository::new("multi-pattern/api-core");
+        let _api_web = TestRepository::new("multi-pattern/api-web");
+        let _lib_core = TestRepository::new("multi-pattern/lib-core");
+        let _docs = TestRepository::new("multi-pattern/docs");
+        fs::create_dir_all(parent.path.join("api-notes")).unwrap();
+        let patterns: Vec<String> = ["api-*", "*-core", "api-core", "missing-*"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let mut errors = Vec::new();
+
+        let found = find_repositories(&parent.path, &patterns, &mut errors).unwrap();
+        let names: Vec<String> = found
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["api-core", "api-web", "lib-core"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn last_counts_only_the_most_recent_commits() {
+        let repository = TestRepository::new("last-commits");
+        repository.commit(
+            "Jane",
+            "jane@acme.com",
+            T0,
+            "a.txt",
+            "1\n2\n3\n4\n5\n6\n7\n8\n",
+        );
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "b.txt", "1\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 120, "a.txt", "1\n");
+        repository.commit("Mia", "mia@int.io", T0 + 180, "c.txt", "1\n2\n");
+
+        let options = AnalysisOptions {
+            last: Some(2),
+            ..Default::default()
+        };
+        let (contributions, info) = repository.analyze(&options);
+        let row = |email: &str| contributions.iter().find(|c| c.email == email);
+
+        assert_eq!(repository_commit_count(&contributions), 2);
+        assert!(row("bob@acme.com").is_none());
+        let jane = row("jane@acme.com").unwrap();
+        assert_eq!(
+            (jane.commits, jane.lines_added, jane.lines_deleted),
+            (1, 0, 7)
+        );
+        assert_eq!(row("mia@int.io").unwrap().lines_added, 2);
+        // Percentages are shares of the limited window only.
+        assert!((jane.contribution_percent - 7.0 / 9.0 * 100.0).abs() < 1e-9);
+        assert_eq!(info.last_commits, Some(2));
+        // First commit dates still look past the limit.
+        assert_eq!(jane.first_commit_at, Some(T0));
+    }
+
+    #[test]
+    fn last_beyond_the_history_is_no_limit() {
+        let repository = TestRepository::new("last-commits-all");
+        repository.commit("Jane", "jane@acme.com", T0, "a.txt", "a\n");
+        repository.commit("Bob", "bob@acme.com", T0 + 60, "b.txt", "b\n");
+
+        let options = AnalysisOptions {
+            last: Some(5),
+            ..Default::default()
+        };
+        let (contributions, info) = repository.analyze(&options);
+        assert_eq!(repository_commit_count(&contributions), 2);
+        assert_eq!(info.last_commits, None);
+    }
+
+    #[test]
+    fn same_named_repositories_are_keyed_by_relative_path() {
+        let parent = Path::new("/work");
+        let repositories = [
+            parent.join("team-a/api"),
+            parent.join("team-b/api"),
+            parent.join("team-a/web"),
+        ];
+        assert_eq!(
+            repository_keys(parent, &repositories),
+            ["team-a/api", "team-b/api", "web"]
+        );
+    }
+
+    #[test]
+    fn emails_differing_in_case_are_one_contribution() {
+        let repository = TestRepository::new("email-case");
+        repository.commit("Jane", "Jane@Acme.com", T0, "a.txt", "1\n");
+        repository.commit("Jane", "jane@acme.com", T0 + 100, "a.txt", "1\n2\n");
+
+        let (contributions, _) = repository.analyze(&AnalysisOptions {
+            normalize_emails: true,
+            ..Default::default()
+        });
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].commits, 2);
+        assert_eq!(contributions[0].lines_added, 2);
+    }
 }
@@ -0,0 +1,449 @@
+use crate::git::{
+    commit_gaps, competition_ranks, git_output, median_duration, most_frequent_form, raw_dump_path,
+    subject_terms, with_git_limits, AnalysisOptions, Contribution, Identity, KeptCommit,
+    RepositoryInfo, ISSUE_REFERENCE_PATTERN,
+};
+use crate::vcs::{Analysis, VcsBackend};
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fs,
+    path::Path,
+    process::Command,
+};
+
+/// Separates the fields of one commit in `hg log` output; commits end with
+/// a record separator, as in the git backend.
+const FIELD_SEPARATOR: char = '\x1f';
+const RECORD_SEPARATOR: char = '\x1e';
+
+/// Node, email, name, `unixtime offset` date, `files: +added/-removed`
+/// diffstat and subject of each commit.
+const COMMIT_TEMPLATE: &str = "{node}\x1f{author|email}\x1f{author|person}\x1f{date|hgdate}\x1f{diffstat}\x1f{desc|firstline}\x1e";
+
+/// Node, email and date only, for the first and last commit dates.
+const DATE_TEMPLATE: &str = "{node}\x1f{author|email}\x1f{date|hgdate}\x1e";
+
+/// Mercurial working copies, analyzed through the `hg` command line. Needs
+/// the `mercurial` feature.
+pub struct Mercurial;
+
+impl Mercurial {
+    /// Every option the git backend honors but this one cannot, as
+    /// `--flag` names; analysis refuses to run rather than ignore them.
+    fn unsupported_options(options: &AnalysisOptions) -> Vec<&'static str> {
+        let mut unsupported = Vec::new();
+        if options.identity == Identity::Committer {
+            unsupported.push("--identity committer");
+        }
+        if options.include_merges {
+            unsupported.push("--include-merges");
+        }
+        if options.blame {
+            unsupported.push("--blame");
+        }
+        if options.signatures {
+            unsupported.push("--signatures");
+        }
+        if !options.excludes.is_empty() {
+            unsupported.push("--exclude");
+        }
+        if options.file.is_some() {
+            unsupported.push("--file");
+        }
+        if options.last.is_some() {
+            unsupported.push("--last");
+        }
+        if options.trend_since.is_some() {
+            unsupported.push("--trend-window");
+        }
+        if options.include_working_tree {
+            unsupported.push("--include-working-tree");
+        }
+        if !options.commit_fields.is_empty() {
+            unsupported.push("--commit-fields");
+        }
+        unsupported
+    }
+
+    /// The revset of non-merge commits `options` selects. A git range
+    /// `a..b` is `only(b, a)`: ancestors of `b` that are not ancestors of `a`.
+    fn revset(options: &AnalysisOptions) -> String {
+        let range = match (&options.tag_range, &options.base) {
+            (Some(tag_range), _) => Some((
+                tag_range.from.as_str(),
+                tag_range.until.as_deref().unwrap_or("."),
+            )),
+            (None, Some(base)) => Some((base.as_str(), ".")),
+            (None, None) => None,
+        };
+        match range {
+            Some((from, until)) => format!(
+                "not merge() and only({}, {})",
+                revset_string(until),
+                revset_string(from)
+            ),
+            None => String::from("not merge()"),
+        }
+    }
+
+    /// `--date` for the `--since`/`--until` window, in any date format
+    /// Mercurial understands.
+    fn date_args(options: &AnalysisOptions) -> Vec<String> {
+        let spec = match (&options.since, &options.until) {
+            (Some(since), Some(until)) => format!("{} to {}", since, until),
+            (Some(since), None) => format!(">{}", since),
+            (None, Some(until)) => format!("<{}", until),
+            (None, None) => return Vec::new(),
+        };
+        vec![String::from("--date"), spec]
+    }
+}
+
+/// Quotes a revision name for a revset, so tags and branches with dashes or
+/// dots stay one symbol.
+fn revset_string(name: &str) -> String {
+    format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// An `hg` command run in `repo_dir`, with `HGPLAIN` so user configuration
+/// (aliases, localized messages, pagers) cannot change the output.
+fn hg_command(repo_dir: &Path) -> Command {
+    let mut command = Command::new("hg");
+    command
+        .arg("--repository")
+        .arg(repo_dir)
+        .env("HGPLAIN", "1")
+        .current_dir(repo_dir);
+    command
+}
+
+/// Runs `hg` under the same timeout and cancellation as git commands,
+/// returning its output or its error message.
+fn run_hg(command: &mut Command) -> Result<Vec<u8>, Box<dyn Error>> {
+    let output = git_output(command).map_err(|e| format!("could not run hg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "hg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(output.stdout)
+}
+
+/// Lines added and removed from a `{diffstat}` value, `files: +added/-removed`.
+fn parse_diffstat(diffstat: &str) -> Option<(u32, u32)> {
+    let (_, lines) = diffstat.split_once(": +")?;
+    let (added, deleted) = lines.split_once("/-")?;
+    Some((added.trim().parse().ok()?, deleted.trim().parse().ok()?))
+}
+
+/// Unix time of an `{date|hgdate}` value, `unixtime offset`.
+fn parse_hgdate(date: &str) -> Option<i64> {
+    date.split_whitespace()
+        .next()?
+        .parse::<f64>()
+        .ok()
+        .map(|t| t as i64)
+}
+
+/// One author's commits while they are being counted.
+#[derive(Default)]
+struct AuthorTally {
+    name: String,
+    forms: HashMap<String, u32>,
+    commits: u32,
+    lines_added: u32,
+    lines_deleted: u32,
+    terms: HashMap<String, u32>,
+    issues: HashSet<u32>,
+    commit_days: BTreeMap<chrono::NaiveDate, u32>,
+    kept_commits: Vec<KeptCommit>,
+}
+
+impl VcsBackend for Mercurial {
+    fn metadata_dir(&self) -> &'static str {
+        ".hg"
+    }
+
+    fn analyze(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Analysis, Box<dyn Error>> {
+        let repo_name = repo_path
+            .file_name()
+            .ok_or("Invalid repository path")?
+            .to_string_lossy()
+            .to_string();
+
+        let unsupported = Mercurial::unsupported_options(options);
+        if !unsupported.is_empty() {
+            return Err(format!(
+                "{} not supported for Mercurial repositories, skipping repository",
+                unsupported.join(", ")
+            )
+            .into());
+        }
+
+        let output = run_hg(
+            hg_command(repo_path)
+                .args(["log", "--rev", &Mercurial::revset(options)])
+                .args(Mercurial::date_args(options))
+                .args(["--template", COMMIT_TEMPLATE]),
+        )?;
+        if let Some(dir) = &options.dump_raw {
+            let path = raw_dump_path(dir, repo_path);
+            fs::create_dir_all(path.parent().unwrap_or(dir))?;
+            fs::write(path, &output)?;
+        }
+
+        let issue_pattern = Regex::new(ISSUE_REFERENCE_PATTERN)?;
+        let mut tallies: HashMap<String, AuthorTally> = HashMap::new();
+        let mut total_added = 0;
+        let mut total_deleted = 0;
+        let mut commit_times = Vec::new();
+
+        for record in String::from_utf8_lossy(&output).split(RECORD_SEPARATOR) {
+            let fields: Vec<&str> = record.trim_start().split(FIELD_SEPARATOR).collect();
+            let [node, email, name, date, diffstat, subject] = fields[..] else {
+                continue;
+            };
+            if options.ignores_commit(node) || options.excludes_author(name, email) {
+                continue;
+            }
+
+            let (added, deleted) = parse_diffstat(diffstat).unwrap_or((0, 0));
+            total_added += added;
+            total_deleted += deleted;
+
+            // Newest first, so the name is the one the author uses today.
+            let tally = tallies.entry(options.email_key(email)).or_default();
+            if tally.name.is_empty() {
+                tally.name = name.to_string();
+            }
+            *tally.forms.entry(email.to_string()).or_insert(0) += 1;
+            tally.commits += 1;
+            tally.lines_added += added;
+            tally.lines_deleted += deleted;
+            for term in subject_terms(subject) {
+                *tally.terms.entry(term).or_insert(0) += 1;
+            }
+            for reference in issue_pattern.captures_iter(subject) {
+                if let Ok(issue) = reference[1].parse::<u32>() {
+                    tally.issues.insert(issue);
+                }
+            }
+            if options.keep_commits {
+                tally.kept_commits.push(KeptCommit {
+                    hash: node.to_string(),
+                    timestamp: parse_hgdate(date).unwrap_or(0),
+                    subject: subject.to_string(),
+                    lines_added: added,
+                    lines_deleted: deleted,
+                });
+            }
+            if let Some(timestamp) = parse_hgdate(date) {
+                commit_times.push(timestamp);
+                if let Some(date) = chrono::DateTime::from_timestamp(timestamp, 0) {
+                    let day = date.with_timezone(&chrono::Local).date_naive();
+                    *tally.commit_days.entry(day).or_insert(0) += 1;
+                }
+            }
+        }
+
+        // First and last commits ignore the window, as with git.
+        let dates = run_hg(
+            hg_command(repo_path)
+                .args(["log", "--rev", "not merge()"])
+                .args(["--template", DATE_TEMPLATE]),
+        )?;
+        let mut commit_spans: HashMap<String, (i64, i64)> = HashMap::new();
+        for record in String::from_utf8_lossy(&dates).split(RECORD_SEPARATOR) {
+            let fields: Vec<&str> = record.trim_start().split(FIELD_SEPARATOR).collect();
+            let [node, email, date] = fields[..] else {
+                continue;
+            };
+            if let (false, Some(timestamp)) = (options.ignores_commit(node), parse_hgdate(date)) {
+                let span = commit_spans
+                    .entry(options.email_key(email))
+                    .or_insert((timestamp, timestamp));
+                span.0 = span.0.min(timestamp);
+                span.1 = span.1.max(timestamp);
+            }
+        }
+
+        let mut contributions: Vec<Contribution> = tallies
+            .into_iter()
+            .map(|(key, tally)| {
+                let forms = tally
+                    .forms
+                    .iter()
+                    .map(|(form, count)| (form.as_str(), *count))
+                    .collect();
+                let span = commit_spans.get(&key);
+                Contribution {
+                    author: tally.name,
+                    email: most_frequent_form(&forms),
+                    commits: tally.commits,
+                    lines_added: tally.lines_added,
+                    lines_deleted: tally.lines_deleted,
+                    contribution_percent: options.line_weights.percent(
+                        tally.lines_added,
+                        tally.lines_deleted,
+                        total_added,
+                        total_deleted,
+                    ),
+                    repository: repo_name.clone(),
+                    terms: tally.terms,
+                    issues: tally.issues,
+                    first_commit_at: span.map(|span| span.0),
+                    last_commit_at: span.map(|span| span.1),
+                    commit_days: tally.commit_days,
+                    kept_commits: tally.kept_commits,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        contributions.sort_by(|a, b| b.contribution_percent.total_cmp(&a.contribution_percent));
+        let percents: Vec<f64> = contributions
+            .iter()
+            .map(|c| c.contribution_percent)
+            .collect();
+        for (contrib, rank) in contributions.iter_mut().zip(competition_ranks(&percents)) {
+            contrib.rank = rank;
+        }
+
+        let info = RepositoryInfo {
+            revision_range: options.revision_range(),
+            median_commit_gap: median_duration(commit_gaps(&mut commit_times)),
+            ..Default::default()
+        };
+
+        Ok((repo_name, contributions, info))
+    }
+
+    fn reference_commit_count(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<u32, Box<dyn Error>> {
+        // Counted with a revset instead of reading the log, excluding ignored
+        // commits and excluded authors there as well.
+        let mut revset = Mercurial::revset(options);
+        for hash in &options.ignore_commits {
+            revset.push_str(&format!(" and not id({})", revset_string(hash)));
+        }
+        for author in &options.exclude_authors {
+            revset.push_str(&format!(" and not author({})", revset_string(author)));
+        }
+        let output = with_git_limits(options, || {
+            run_hg(
+                hg_command(repo_path)
+                    .args(["log", "--rev", &revset])
+                    .args(Mercurial::date_args(options))
+                    .args(["--template", "x"]),
+            )
+        })?;
+
+        Ok(output.len() as u32)
+    }
+
+    fn latest_commit_at(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Option<i64>, Box<dyn Error>> {
+        let output = with_git_limits(options, || {
+            run_hg(hg_command(repo_path).args([
+                "log",
+                "--rev",
+                "tip",
+                "--template",
+                "{date|hgdate}",
+            ]))
+        })?;
+        Ok(parse_hgdate(&String::from_utf8_lossy(&output)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::TagRange;
+
+    #[test]
+    fn diffstats_are_parsed() {
+        assert_eq!(parse_diffstat("3: +12/-4"), Some((12, 4)));
+        assert_eq!(parse_diffstat("0: +0/-0"), Some((0, 0)));
+        assert_eq!(parse_diffstat(""), None);
+        assert_eq!(parse_diffstat("1: +x/-2"), None);
+    }
+
+    #[test]
+    fn hgdates_are_parsed() {
+        assert_eq!(parse_hgdate("1700000000 -3600"), Some(1_700_000_000));
+        assert_eq!(parse_hgdate("1700000000.5 0"), Some(1_700_000_000));
+        assert_eq!(parse_hgdate(""), None);
+        assert_eq!(parse_hgdate("yesterday 0"), None);
+    }
+
+    #[test]
+    fn revision_names_are_quoted() {
+        assert_eq!(revset_string("v1.0-rc"), "'v1.0-rc'");
+        assert_eq!(revset_string(r"it's\"), r"'it\'s\\'");
+    }
+
+    #[test]
+    fn revsets_follow_tag_ranges_and_bases() {
+        let mut options = AnalysisOptions::default();
+        assert_eq!(Mercurial::revset(&options), "not merge()");
+
+        options.base = Some("main".to_string());
+        assert_eq!(
+            Mercurial::revset(&options),
+            "not merge() and only('.', 'main')"
+        );
+
+        options.tag_range = Some(TagRange::parse("v1..v2").unwrap());
+        assert_eq!(
+            Mercurial::revset(&options),
+            "not merge() and only('v2', 'v1')"
+        );
+    }
+
+    #[test]
+    fn date_windows_become_date_arguments() {
+        let mut options = AnalysisOptions::default();
+        assert!(Mercurial::date_args(&options).is_empty());
+
+        options.since = Some("2024-01-01".to_string());
+        assert_eq!(Mercurial::date_args(&options), ["--date", ">2024-01-01"]);
+
+        options.until = Some("2024-06-30".to_string());
+        assert_eq!(
+            Mercurial::date_args(&options),
+            ["--date", "2024-01-01 to 2024-06-30"]
+        );
+
+        options.since = None;
+        assert_eq!(Mercurial::date_args(&options), ["--date", "<2024-06-30"]);
+    }
+
+    #[test]
+    fn options_the_backend_cannot_honor_are_listed() {
+        let mut options = AnalysisOptions::default();
+        assert!(Mercurial::unsupported_options(&options).is_empty());
+
+        options.identity = Identity::Committer;
+        options.blame = true;
+        options.last = Some(10);
+        assert_eq!(
+            Mercurial::unsupported_options(&options),
+            ["--identity committer", "--blame", "--last"]
+        );
+    }
+}
@@ -1,5 +1,12 @@
+pub mod anonymize;
 pub mod app;
+pub mod diff;
 pub mod error;
 pub mod export;
 pub mod git;
+#[cfg(feature = "mercurial")]
+pub mod hg;
+pub mod plain;
+pub mod score;
 pub mod ui;
+pub mod vcs;
@@ -4,219 +4,1154 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use itertools::Itertools;
 use std::{
     collections::HashMap,
+    env,
     error::Error,
     io,
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, TryRecvError},
+        Arc,
+    },
     thread,
+    time::Duration,
 };
 use tui::{backend::CrosstermBackend, Terminal};
 
+#[cfg(feature = "rusqlite")]
+use git_contribution_analyzer::export::export_sqlite_report;
 use git_contribution_analyzer::{
-    app::{App, AppState},
+    anonymize::{anonymize_contributions, anonymize_merges, anonymize_reviews, build_pseudonyms},
+    app::{AnalysisReport, App, AppState, ComparisonView, CoverageStats, ProgressEvent},
+    diff::{diff_reports, DiffFormat},
     error::io_err_to_box_err,
-    export::export_html_report,
-    git::{analyze_repository, calculate_author_summaries, find_repositories},
+    export::{
+        export_codeowners, export_html_report, export_report, export_split_report, ExportFormat,
+        TimelineGranularity, YearRange, DEFAULT_TIMELINE_AUTHORS, JSON_SCHEMA,
+    },
+    git::{
+        analyze_repository_guarded, apply_org_map, apply_reviews, apply_trends,
+        calculate_summaries, collaborator_pairs, compare_contributions, find_repositories,
+        find_repositories_by_path, fuzzy_merge_authors, inactive_owner_risk, merge_bots,
+        orphaned_directories, read_excludes_file, read_ignore_commits_file, read_org_map,
+        read_reviews_csv, repository_commit_count, repository_group, repository_keys,
+        set_git_executable, unify_email_forms, validate_repository_pattern, AnalysisOptions,
+        CommitField, CommitReconciliation, GroupBy, HealthThresholds, Identity, LineWeights,
+        OwnershipBasis, RepositoryOrder, SummaryBasis, TagRange, COLLABORATION_AUTHOR_LIMIT,
+        DEFAULT_INACTIVE_DAYS, DEFAULT_ORPHAN_THRESHOLD, DEFAULT_PERCENT_PRECISION,
+        MAX_PERCENT_PRECISION,
+    },
+    plain::{plain_report, reconciliation_report},
+    score::{apply_contribution_score, apply_summary_score, ScoreExpression},
     ui::{render_loading_screen, render_main_view},
+    vcs::backend_for,
 };
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct CliArgs {
+    /// Directory containing the repositories to analyze; defaults to
+    /// $GIT_ANALYZER_PATH, then the current directory
     #[arg(short, long)]
-    path: PathBuf,
+    path: Option<PathBuf>,
 
-    /// Repository pattern to match (e.g., "bwt-*")
-    #[arg(short, long, default_value = "*")]
-    pattern: String,
+    /// Repository pattern to match (e.g., "bwt-*"); comma-separated or
+    /// repeated patterns match any of them (e.g., "api-*,web-*")
+    #[arg(long, default_value = "*", value_delimiter = ',')]
+    pattern: Vec<String>,
+
+    /// Pattern matched against each repository's path relative to --path,
+    /// searched recursively (e.g., "**/service-*")
+    #[arg(long)]
+    path_pattern: Option<String>,
+
+    /// Milliseconds between loading-screen animation frames
+    #[arg(long, default_value_t = 100)]
+    tick_rate: u64,
+
+    /// Only analyze commits more recent than this date (e.g., "2024-01-01", "3 months ago")
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only analyze commits older than this date
+    #[arg(long)]
+    until: Option<String>,
+
+    /// Compare two time windows given as <since>..<until> (e.g., "2024-01-01..2024-03-31");
+    /// the tabs show the second window
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["BASELINE", "CURRENT"],
+        conflicts_with_all = ["since", "until"]
+    )]
+    compare: Option<Vec<String>>,
+
+    /// Only analyze commits between two tags, given as <tag>..<tag> (e.g., "v1.0..v2.0");
+    /// repositories missing either tag are skipped
+    #[arg(long, value_name = "RANGE", conflicts_with = "since_tag")]
+    between: Option<String>,
+
+    /// Only analyze commits made after this tag, up to HEAD
+    #[arg(long, value_name = "TAG")]
+    since_tag: Option<String>,
+
+    /// Only analyze commits on HEAD that are not on this ref (<base>..HEAD), to see
+    /// who built a feature branch; repositories without the ref are skipped
+    #[arg(long, value_name = "REF", conflicts_with_all = ["between", "since_tag"])]
+    base: Option<String>,
+
+    /// Share of a directory's changed lines (in percent) a contributor must exceed
+    /// to be listed as its owner in the CODEOWNERS export
+    #[arg(long, default_value_t = 50.0)]
+    codeowners_threshold: f64,
+
+    /// Replace author names and emails with stable pseudonyms (Contributor A, B, ...)
+    /// everywhere, including exports
+    #[arg(long)]
+    anonymize: bool,
+
+    /// Drop repositories with fewer than this many commits (after analysis)
+    #[arg(long, default_value_t = 0)]
+    min_repo_commits: u32,
+
+    /// Order to analyze repositories in and show their tabs in: by name, most
+    /// commits, most recent commit or most lines changed first
+    #[arg(long, value_enum, default_value_t = RepositoryOrder::Name)]
+    order: RepositoryOrder,
+
+    /// Attribute each commit to its author (who wrote it) or its committer (who applied it)
+    #[arg(long, value_enum, default_value_t = Identity::Author)]
+    identity: Identity,
+
+    /// Summarize per author, or roll authors up by email domain (e.g., everyone @acme.com)
+    #[arg(long, value_enum, default_value_t = GroupBy::Author)]
+    group_by: GroupBy,
+
+    /// Measure summary percentages against all lines changed (global), or
+    /// average each author's per-repository percentages so every repository
+    /// counts equally regardless of size (per-repo-average)
+    #[arg(long, value_enum, default_value_t = SummaryBasis::Global)]
+    summary_basis: SummaryBasis,
+
+    /// Measure directory ownership and bus factors by changed lines or by
+    /// number of commits
+    #[arg(long, value_enum, default_value_t = OwnershipBasis::Lines)]
+    ownership_basis: OwnershipBasis,
+
+    /// Blame every text file at HEAD to show each author's surviving lines (who
+    /// wrote the code that exists today); slow on large repositories
+    #[arg(long)]
+    blame: bool,
+
+    /// Check commit signatures to show the share of each author's commits that
+    /// are GPG/SSH-signed; runs gpg or ssh-keygen for every signed commit
+    #[arg(long)]
+    signatures: bool,
+
+    /// Leave paths matching this gitignore-style pattern out of every count
+    /// (e.g., "vendor/", "*.lock"); may be repeated
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Read exclusion patterns from a gitignore-syntax file, one per line;
+    /// blank lines and # comments are ignored
+    #[arg(long, value_name = "FILE")]
+    excludes_from: Option<PathBuf>,
+
+    /// Only count commits touching this file, given relative to each
+    /// repository's root, to see who knows it best
+    #[arg(long, value_name = "PATH")]
+    file: Option<String>,
+
+    /// Only count each repository's most recent N non-merge commits, a fast
+    /// way to look at very long histories
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    last: Option<u32>,
+
+    /// Leave the commits listed in this file (one hash per line, # comments
+    /// allowed) out of every count, e.g. an accidental vendor import
+    #[arg(long, value_name = "FILE")]
+    ignore_commits: Option<PathBuf>,
+
+    /// CSV of email,review_count rows (e.g. exported from GitHub or GitLab)
+    /// to show as a Reviews column in the summary
+    #[arg(long, value_name = "FILE")]
+    reviews_csv: Option<PathBuf>,
+
+    /// File of domain=Organization Name lines; the summary gets an Org
+    /// column naming each author's organization by email domain, or the
+    /// domain itself when unmapped
+    #[arg(long, value_name = "FILE")]
+    org_map: Option<PathBuf>,
+
+    /// Record these details of every commit in the JSON and JSON Lines
+    /// exports; comma-separated
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELD,...")]
+    commit_fields: Vec<CommitField>,
+
+    /// Keep every commit's hash, date, subject and lines, so the author
+    /// detail popup can list the commits behind a repository percentage
+    #[arg(long)]
+    keep_commits: bool,
+
+    /// Flag top-level directories where no contributor's share (per
+    /// --ownership-basis) exceeds this percentage as orphaned
+    #[arg(long, value_name = "PERCENT", default_value_t = DEFAULT_ORPHAN_THRESHOLD)]
+    orphan_threshold: f64,
+
+    /// Days without a commit after which an author counts as inactive when
+    /// scoring files by how much of them inactive authors own (with --blame)
+    #[arg(long, value_name = "DAYS", default_value_t = DEFAULT_INACTIVE_DAYS)]
+    inactive_days: u32,
+
+    /// Show dates in the TUI relative to now ("3 months ago"); exports keep
+    /// absolute dates
+    #[arg(long)]
+    relative_dates: bool,
+
+    /// High-contrast mode for low vision: white text instead of cyan and
+    /// gray, bold underlined headers, a marker on the selected row and a
+    /// symbol next to every colored badge; also turns off author colors and
+    /// striped rows
+    #[arg(long)]
+    accessible: bool,
+
+    /// Write one report per repository plus an index summary into this
+    /// directory once analysis finishes (works with --plain too)
+    #[arg(long, value_name = "DIR")]
+    split_output: Option<PathBuf>,
+
+    /// Save each repository's raw `git log --numstat` output, exactly as the
+    /// analysis parsed it, to <DIR>/<parent>/<repository>.log for auditing
+    /// the counts
+    #[arg(long, value_name = "DIR")]
+    dump_raw: Option<PathBuf>,
+
+    /// Format of the --split-output reports
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = ExportFormat::Html)]
+    split_format: ExportFormat,
+
+    /// Treat emails differing only in case or surrounding whitespace as one
+    /// author, shown under the most used spelling; pass `false` to keep them apart
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    normalize_emails: bool,
+
+    /// Merge authors whose emails or names look like typos of each other
+    /// (e.g. jane@exmaple.com and jane@example.com); the merges are listed
+    /// with the errors (`x`) and in --plain output for checking
+    #[arg(long)]
+    fuzzy_merge: bool,
+
+    /// Fold bot accounts (dependabot[bot], renovate, *-bot, ...) into a single
+    /// "Automation" author instead of listing each one
+    #[arg(long)]
+    merge_bots: bool,
+
+    /// Decimal places shown for percentages in the TUI and reports (0-6;
+    /// larger values are clamped)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_PERCENT_PRECISION)]
+    percent_precision: usize,
+
+    /// Years the HTML report's activity calendars cover, as <year> or
+    /// <year>..<year> (default: the first to the last year with commits)
+    #[arg(long, value_name = "YEARS")]
+    calendar_years: Option<String>,
+
+    /// Period the HTML report's commits-over-time chart steps by
+    #[arg(long, value_enum, default_value_t = TimelineGranularity::Month)]
+    timeline_granularity: TimelineGranularity,
+
+    /// Authors with the most commits drawn in the HTML report's
+    /// commits-over-time chart; 0 leaves the chart out
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_TIMELINE_AUTHORS)]
+    timeline_authors: usize,
+
+    /// Drop everyone whose name or email contains this text (case-insensitive),
+    /// e.g. a bot account; their lines also leave the percentage totals, so the
+    /// remaining authors still add up to 100%. May be repeated
+    #[arg(long, value_name = "EMAIL")]
+    exclude_author: Vec<String>,
+
+    /// Print the summary and repository tables as plain text to stdout and exit
+    /// instead of opening the TUI
+    #[arg(long)]
+    plain: bool,
+
+    /// With --plain, exit nonzero if any repository's bus factor (fewest authors
+    /// who changed over half the lines) is below N
+    #[arg(long, value_name = "N", requires = "plain")]
+    fail_under_bus_factor: Option<u32>,
+
+    /// With --plain, exit nonzero if any repository's Gini coefficient of lines
+    /// changed per author (0 even, 1 one author did everything) is above X
+    #[arg(long, value_name = "X", requires = "plain")]
+    fail_over_gini: Option<f64>,
+
+    /// Check each repository's analyzed commit total against `git rev-list`
+    /// with the same filters, print the reconciliation and exit, nonzero if
+    /// any repository differs by more than --validate-tolerance
+    #[arg(long)]
+    validate: bool,
+
+    /// Percentage of a repository's commits --validate lets the totals differ by
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    validate_tolerance: f64,
+
+    /// Mark authors trending up or down by comparing their commit rate since this
+    /// date (e.g., "1 month ago") with their overall rate
+    #[arg(long, value_name = "SINCE")]
+    trend_window: Option<String>,
+
+    /// Git binary to run instead of the one on PATH (defaults to $GIT if set)
+    #[arg(long, value_name = "PATH")]
+    git_path: Option<PathBuf>,
+
+    /// How much an added line counts towards contribution percentages
+    /// relative to a deleted one; 1.0 for both counts every changed line alike
+    #[arg(long, value_name = "WEIGHT", default_value_t = 1.0)]
+    add_weight: f64,
+
+    /// How much a deleted line counts towards contribution percentages
+    #[arg(long, value_name = "WEIGHT", default_value_t = 1.0)]
+    delete_weight: f64,
+
+    /// Rank authors by this expression over commits, lines_added,
+    /// lines_deleted, lines, merges, surviving_lines, signed_commits,
+    /// recent_commits, issues and reviews instead of by lines changed
+    /// (e.g., "0.5*commits + 0.3*lines_added + 0.2*reviews")
+    #[arg(long, value_name = "EXPR")]
+    score: Option<String>,
+
+    /// List only authors with commits in every analyzed repository in the
+    /// summary, to find cross-cutting contributors (toggle with `u`)
+    #[arg(long)]
+    ubiquitous_only: bool,
+
+    /// Keep the tabs and the HTML report flat instead of grouping repositories
+    /// by the directory they are in
+    #[arg(long)]
+    no_grouping: bool,
+
+    /// Start in the compact layout: no borders, one-line tabs and no help
+    /// footer, for small terminals and split panes (toggle with `d`)
+    #[arg(long)]
+    compact: bool,
+
+    /// Give up on a repository whose analysis takes longer than this many
+    /// seconds (e.g. git hanging on a network filesystem) and move on
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Drop errors about repositories that could not be analyzed instead of
+    /// listing them in the errors view (x) and the reports
+    #[arg(long)]
+    quiet: bool,
+
+    /// Also credit staged and unstaged changes to the configured git user
+    /// (user.email), as one extra commit marked ✎ in the repository tables
+    #[arg(long)]
+    include_working_tree: bool,
+
+    /// Count merge commits per author in a separate "Merges" column; line and
+    /// commit metrics still exclude merges
+    #[arg(long)]
+    include_merges: bool,
+
+    /// Print the JSON Schema of the JSON report and exit
+    #[arg(long)]
+    schema: bool,
+
+    /// Print how contributions changed between two JSON reports, per author
+    /// and per repository, and exit
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    diff: Option<Vec<PathBuf>>,
+
+    /// Format of the --diff output
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    diff_format: DiffFormat,
+}
+
+/// Names the directory to analyze when `--path` is not given.
+const PATH_ENV_VAR: &str = "GIT_ANALYZER_PATH";
+
+/// The directory to analyze: `--path`, else `GIT_ANALYZER_PATH`, else the
+/// current directory. Whichever applies must be an existing directory.
+fn resolve_parent_path(path: Option<PathBuf>) -> Result<PathBuf, String> {
+    let (path, source) = match path {
+        Some(path) => (path, "--path"),
+        None => match env::var_os(PATH_ENV_VAR).filter(|value| !value.is_empty()) {
+            Some(path) => (PathBuf::from(path), PATH_ENV_VAR),
+            None => (
+                env::current_dir()
+                    .map_err(|e| format!("cannot read the current directory: {}", e))?,
+                "the current directory",
+            ),
+        },
+    };
+
+    if !path.is_dir() {
+        return Err(format!(
+            "{} (from {}) is not an existing directory",
+            path.display(),
+            source
+        ));
+    }
+    Ok(path)
+}
+
+/// Opens `path` with the platform's default application (the file manager
+/// for a directory, the browser for an HTML report) without waiting for it
+/// to close, or explains why it cannot be opened.
+fn open_with_default_app(path: &Path) -> Result<(), String> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else if env::var_os("DISPLAY").is_none() && env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err(String::from("no graphical session to open it in"));
+    } else {
+        "xdg-open"
+    };
+
+    let mut child = Command::new(opener)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("could not run {}: {}", opener, e))?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Writes the `--split-output` reports once loading is done, noting where
+/// they went or why they could not be written.
+fn write_split_reports(app: &mut App, format: ExportFormat, output_dir: &Path) {
+    match export_split_report(app, format, output_dir) {
+        Ok(()) => app.loading_message = format!("Reports written to {}", output_dir.display()),
+        Err(e) => app.error_log.push(format!(
+            "Error writing reports to {}: {}",
+            output_dir.display(),
+            e
+        )),
+    }
+}
+
+/// Keeps panic messages off the alternate screen. Panics in analysis threads
+/// are caught and shown in the errors view, so their messages are dropped; a
+/// panic on the main thread restores the terminal before it is reported.
+fn install_tui_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen);
+            default_hook(info);
+        }
+    }));
 }
 
 fn main() -> Result<(), Box<dyn Error + Send>> {
     let args = CliArgs::parse();
-    let parent_path = args.path.clone();
+    if args.schema {
+        print!("{}", JSON_SCHEMA);
+        return Ok(());
+    }
+    if let Some(reports) = &args.diff {
+        print!(
+            "{}",
+            diff_reports(&reports[0], &reports[1], args.diff_format)?
+        );
+        return Ok(());
+    }
+    let parent_path = match resolve_parent_path(args.path.clone()) {
+        Ok(path) => path,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
+        }
+    };
     let pattern = args.pattern.clone();
+    let path_pattern = args.path_pattern.clone();
+    let tick_rate_ms = args.tick_rate;
+    let min_repo_commits = args.min_repo_commits;
+    let anonymize = args.anonymize;
+    let group_by = args.group_by;
+    let summary_basis = args.summary_basis;
+    let plain = args.plain;
+    let validate = args.validate;
+    let thresholds = HealthThresholds::new(args.fail_under_bus_factor, args.fail_over_gini)?;
+    let mut reviews = match &args.reviews_csv {
+        Some(path) => Some(read_reviews_csv(path)?),
+        None => None,
+    };
+    let org_map = match &args.org_map {
+        Some(path) => Some(read_org_map(path)?),
+        None => None,
+    };
+    let quiet = args.quiet;
+    let ownership_basis = args.ownership_basis;
+    let orphan_threshold = args.orphan_threshold;
+    let inactive_days = args.inactive_days;
+    let split_output = args.split_output.clone();
+    let split_format = args.split_format;
+    let grouping = !args.no_grouping;
+    let fuzzy_merge = args.fuzzy_merge;
+    let fold_bots = args.merge_bots;
+    let order = args.order;
+    let score = args
+        .score
+        .as_deref()
+        .map(ScoreExpression::parse)
+        .transpose()?;
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cancel_ui = Arc::clone(&cancel);
 
-    enable_raw_mode().map_err(io_err_to_box_err)?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen).map_err(io_err_to_box_err)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).map_err(io_err_to_box_err)?;
+    match &path_pattern {
+        Some(path_pattern) => validate_repository_pattern(path_pattern)?,
+        None => {
+            for pattern in &pattern {
+                validate_repository_pattern(pattern)?;
+            }
+        }
+    }
+    set_git_executable(args.git_path.clone())?;
 
-    let app = Arc::new(Mutex::new(App::new()));
-    let app_ui = Arc::clone(&app);
+    let mut excludes = args.exclude.clone();
+    if let Some(path) = &args.excludes_from {
+        excludes.extend(read_excludes_file(path)?);
+    }
 
-    let loading_thread = thread::spawn(move || -> Result<(), Box<dyn Error + Send>> {
-        {
-            let mut guard = app.lock().map_err(|_| {
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to acquire lock".to_string(),
-                )) as Box<dyn Error + Send>
-            })?;
-            guard.loading_message = String::from("Finding Git repositories");
+    let mut options = AnalysisOptions {
+        since: args.since.clone(),
+        until: args.until.clone(),
+        include_merges: args.include_merges,
+        identity: args.identity,
+        blame: args.blame,
+        signatures: args.signatures,
+        excludes,
+        file: args.file.clone(),
+        trend_since: args.trend_window.clone(),
+        exclude_authors: args.exclude_author.clone(),
+        include_working_tree: args.include_working_tree,
+        normalize_emails: args.normalize_emails,
+        commit_fields: args.commit_fields.clone(),
+        keep_commits: args.keep_commits,
+        timeout: args.timeout.map(Duration::from_secs),
+        line_weights: LineWeights::new(args.add_weight, args.delete_weight)?,
+        cancel: Some(Arc::clone(&cancel)),
+        dump_raw: args.dump_raw.clone(),
+        last: args.last,
+        only_commits: None,
+        ignore_commits: match &args.ignore_commits {
+            Some(path) => read_ignore_commits_file(path)?,
+            None => Vec::new(),
+        },
+        tag_range: match (&args.between, &args.since_tag) {
+            (Some(range), _) => Some(TagRange::parse(range)?),
+            (None, Some(tag)) => Some(TagRange {
+                from: tag.clone(),
+                until: None,
+            }),
+            (None, None) => None,
+        },
+        base: args.base.clone(),
+    };
+
+    // With --compare the tabs show the second window, and the first window is
+    // analyzed with otherwise identical options as the baseline.
+    let baseline = match &args.compare {
+        Some(windows) => {
+            let current = AnalysisOptions::from_window(&windows[1])?;
+            let baseline = AnalysisOptions::from_window(&windows[0])?;
+            options.since = current.since;
+            options.until = current.until;
+            Some((
+                windows[0].clone(),
+                windows[1].clone(),
+                AnalysisOptions {
+                    since: baseline.since,
+                    until: baseline.until,
+                    // Blame looks at HEAD only, so the baseline would just repeat it.
+                    blame: false,
+                    signatures: false,
+                    trend_since: None,
+                    // Uncommitted work belongs to the current window only.
+                    include_working_tree: false,
+                    // Only the current window's commits are listed.
+                    keep_commits: false,
+                    ..options.clone()
+                },
+            ))
         }
+        None => None,
+    };
+
+    let mut app = App::new();
+    app.show_merges = args.include_merges;
+    app.show_surviving = args.blame;
+    app.show_signatures = args.signatures;
+    app.show_reviews = args.reviews_csv.is_some();
+    app.org_map = org_map.clone();
+    app.codeowners_threshold = args.codeowners_threshold;
+    app.ownership_basis = args.ownership_basis;
+    app.orphan_threshold = args.orphan_threshold;
+    app.inactive_days = args.inactive_days;
+    app.relative_dates = args.relative_dates;
+    app.compact = args.compact;
+    app.ubiquitous_only = args.ubiquitous_only;
+    app.group_by = args.group_by;
+    app.summary_basis = args.summary_basis;
+    app.line_weights = options.line_weights;
+    app.score = score.clone();
+    app.percent_precision = args.percent_precision.min(MAX_PERCENT_PRECISION);
+    app.timeline_granularity = args.timeline_granularity;
+    app.timeline_authors = args.timeline_authors;
+    app.calendar_years = args
+        .calendar_years
+        .as_deref()
+        .map(YearRange::parse)
+        .transpose()?;
+    app.accessible = args.accessible;
+    app.use_color =
+        !args.accessible && !matches!(env::var_os("NO_COLOR"), Some(value) if !value.is_empty());
+    app.loading_message = String::from("Finding Git repositories");
+
+    if !plain {
+        install_tui_panic_hook();
+    }
+
+    let (progress, progress_ui) = mpsc::channel();
+    let loading_thread = thread::spawn(move || -> Result<(), Box<dyn Error + Send>> {
+        let mut report = AnalysisReport::default();
+        let mut discovery_errors = Vec::new();
+        let mut repositories = match &path_pattern {
+            Some(path_pattern) => {
+                find_repositories_by_path(&parent_path, path_pattern, &mut discovery_errors)?
+            }
+            None => find_repositories(&parent_path, &pattern, &mut discovery_errors)?,
+        };
 
-        let repositories = find_repositories(&parent_path, &pattern)?;
+        if !quiet {
+            report.errors.extend(discovery_errors);
+        }
+        // The UI may already be gone (e.g. it quit while loading), so sends
+        // are allowed to fail.
+        let _ = progress.send(ProgressEvent::Found(repositories.len()));
+        order.arrange(&mut repositories, &options);
 
         if repositories.is_empty() {
-            let mut guard = app.lock().map_err(|_| {
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Failed to acquire lock".to_string(),
-                )) as Box<dyn Error + Send>
-            })?;
-            guard.loading_message = String::from("No Git repositories found!");
             thread::sleep(std::time::Duration::from_secs(2));
-            guard.state = AppState::Main;
+            let _ = progress.send(ProgressEvent::Done(Box::new(report)));
             return Ok(());
         }
 
         let repo_count = repositories.len();
+        let mut coverage = CoverageStats {
+            repositories_found: repo_count,
+            ..CoverageStats::default()
+        };
         let mut repository_names = Vec::new();
         let mut contributions_map = HashMap::new();
+        let mut repository_info = HashMap::new();
+        let mut baseline_map = HashMap::new();
+        let mut reconciliation = Vec::new();
+        let mut repository_paths = HashMap::new();
 
-        for (index, repo_path) in repositories.iter().enumerate() {
-            let repo_name = repo_path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
+        let repository_keys = repository_keys(&parent_path, &repositories);
 
-            {
-                let mut guard = app.lock().map_err(|_| {
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Failed to acquire mutex lock".to_string(),
-                    )) as Box<dyn Error + Send>
-                })?;
-                guard.loading_message = format!(
-                    "Analyzing repository {}/{}: {}",
-                    index + 1,
-                    repo_count,
-                    repo_name
-                );
-                guard.loading_progress = ((index as f32 / repo_count as f32) * 100.0) as u8;
+        for (index, (repo_path, repo_name)) in repositories.iter().zip(repository_keys).enumerate()
+        {
+            if cancel.load(Ordering::Relaxed) {
+                break;
             }
 
-            match analyze_repository(repo_path) {
-                Ok((name, contributions)) => {
-                    repository_names.push(name.clone());
-                    contributions_map.insert(name, contributions);
+            let _ = progress.send(ProgressEvent::Analyzing {
+                index,
+                total: repo_count,
+                name: repo_name.clone(),
+            });
+
+            let analysis = analyze_repository_guarded(repo_path, &options);
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            match analysis {
+                Ok((_, mut contributions, info)) => {
+                    for contribution in &mut contributions {
+                        contribution.repository = repo_name.clone();
+                    }
+                    if validate {
+                        let reference_commits = match backend_for(repo_path)
+                            .reference_commit_count(repo_path, &options)
+                        {
+                            Ok(count) => Some(count),
+                            Err(e) => {
+                                report.errors.push(format!(
+                                    "Error counting commits of repository {}: {}",
+                                    repo_name, e
+                                ));
+                                None
+                            }
+                        };
+                        reconciliation.push(CommitReconciliation {
+                            repository: repo_name.clone(),
+                            reference_commits,
+                            analyzed_commits: contributions
+                                .iter()
+                                .map(|c| c.commits - c.working_tree as u32)
+                                .sum(),
+                        });
+                    }
+                    coverage.repositories_analyzed += 1;
+                    coverage.commits_processed += contributions
+                        .iter()
+                        .map(|c| c.commits + c.merges)
+                        .sum::<u32>();
+                    repository_names.push(repo_name.clone());
+                    repository_paths.insert(repo_name.clone(), repo_path.clone());
+                    repository_info.insert(repo_name.clone(), info);
+                    contributions_map.insert(repo_name.clone(), contributions);
                 }
                 Err(e) => {
-                    eprintln!("Error analyzing repository {}: {}", repo_name, e);
+                    coverage.repositories_failed += 1;
+                    if !quiet {
+                        report
+                            .errors
+                            .push(format!("Error analyzing repository {}: {}", repo_name, e));
+                    }
+                }
+            }
+
+            if let Some((_, _, baseline_options)) = &baseline {
+                let baseline_analysis = analyze_repository_guarded(repo_path, baseline_options);
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                match baseline_analysis {
+                    Ok((_, mut contributions, _)) => {
+                        for contribution in &mut contributions {
+                            contribution.repository = repo_name.clone();
+                        }
+                        baseline_map.insert(repo_name.clone(), contributions);
+                    }
+                    Err(e) => {
+                        if !quiet {
+                            report.errors.push(format!(
+                                "Error analyzing baseline of repository {}: {}",
+                                repo_name, e
+                            ));
+                        }
+                    }
                 }
             }
         }
 
-        repository_names.sort();
+        if cancel.load(Ordering::Relaxed) {
+            coverage.repositories_skipped =
+                repo_count - coverage.repositories_analyzed - coverage.repositories_failed;
+        }
 
-        let author_summaries = calculate_author_summaries(&contributions_map);
+        if min_repo_commits > 0 {
+            repository_names.retain(|name| {
+                contributions_map
+                    .get(name)
+                    .map(|contributions| repository_commit_count(contributions) >= min_repo_commits)
+                    .unwrap_or(false)
+            });
+            contributions_map.retain(|name, _| repository_names.contains(name));
+            repository_info.retain(|name, _| repository_names.contains(name));
+            repository_paths.retain(|name, _| repository_names.contains(name));
+            baseline_map.retain(|name, _| repository_names.contains(name));
+            coverage.repositories_hidden = coverage.repositories_analyzed - repository_names.len();
+        }
 
-        {
-            let mut guard = app.lock().map_err(|e| {
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Failed to acquire mutex lock: {}", e),
-                )) as Box<dyn Error + Send>
-            })?;
-            guard.repositories = repository_names;
-            guard.contributions = contributions_map;
-            guard.author_summaries = author_summaries;
-            guard.selected_in_tab = vec![None; guard.repositories.len() + 1];
-            guard.state = AppState::Main;
+        if options.normalize_emails {
+            unify_email_forms(&mut [&mut contributions_map, &mut baseline_map], &options);
         }
 
+        let mut author_merges = Vec::new();
+        if fold_bots {
+            author_merges.extend(merge_bots(&mut [&mut contributions_map, &mut baseline_map]));
+        }
+        if fuzzy_merge {
+            match fuzzy_merge_authors(&mut [&mut contributions_map, &mut baseline_map]) {
+                Ok(merges) => author_merges.extend(merges),
+                Err(e) => report.errors.push(e),
+            }
+        }
+
+        if let Some(score) = &score {
+            apply_contribution_score(&mut contributions_map, score);
+        }
+
+        // Repositories in different directories are grouped by directory,
+        // unless they all share one or grouping is turned off.
+        let mut repository_groups: HashMap<String, String> = HashMap::new();
+        if grouping {
+            repository_groups = repository_paths
+                .iter()
+                .map(|(name, path)| (name.clone(), repository_group(&parent_path, path)))
+                .collect();
+            if repository_groups.values().all_equal() {
+                repository_groups.clear();
+            }
+        }
+        repository_names.sort_by(|a, b| {
+            repository_groups
+                .get(a)
+                .cmp(&repository_groups.get(b))
+                .then_with(|| {
+                    let key = |name| {
+                        contributions_map
+                            .get(name)
+                            .map_or(0, |contributions| order.sort_key(contributions))
+                    };
+                    key(b).cmp(&key(a))
+                })
+                .then_with(|| a.cmp(b))
+        });
+
+        if anonymize {
+            let pseudonyms = build_pseudonyms(&[&contributions_map, &baseline_map]);
+            anonymize_contributions(&mut contributions_map, &pseudonyms);
+            anonymize_contributions(&mut baseline_map, &pseudonyms);
+            anonymize_merges(&mut author_merges, &pseudonyms);
+            if let Some(reviews) = &mut reviews {
+                anonymize_reviews(reviews, &pseudonyms);
+            }
+        }
+
+        let mut author_summaries = calculate_summaries(
+            &contributions_map,
+            group_by,
+            summary_basis,
+            options.line_weights,
+        );
+        if let Some(trend_start) = repository_info.values().filter_map(|i| i.trend_start).min() {
+            let history_start = repository_info
+                .values()
+                .filter_map(|i| i.window_start)
+                .min();
+            apply_trends(
+                &mut author_summaries,
+                history_start,
+                trend_start,
+                chrono::Utc::now().timestamp(),
+            );
+        }
+        if let Some(reviews) = &reviews {
+            apply_reviews(&mut author_summaries, reviews, group_by);
+        }
+        if let Some(org_map) = &org_map {
+            apply_org_map(&mut author_summaries, org_map);
+        }
+        // Averaged summaries already average the scored repository shares.
+        if let (Some(score), SummaryBasis::Global) = (&score, summary_basis) {
+            apply_summary_score(&mut author_summaries, score);
+        }
+        let collaborators = collaborator_pairs(&contributions_map, COLLABORATION_AUTHOR_LIMIT);
+        let orphaned = orphaned_directories(&contributions_map, ownership_basis, orphan_threshold);
+        let at_risk_files = inactive_owner_risk(
+            &contributions_map,
+            inactive_days,
+            chrono::Utc::now().timestamp(),
+        );
+        let comparison = baseline.map(|(baseline_label, current_label, _)| ComparisonView {
+            baseline_label,
+            current_label,
+            authors: compare_contributions(&baseline_map, &contributions_map),
+        });
+
+        report.repositories = repository_names;
+        report.repository_paths = repository_paths;
+        report.repository_groups = repository_groups;
+        report.contributions = contributions_map;
+        report.repository_info = repository_info;
+        report.author_summaries = author_summaries;
+        report.collaborators = collaborators;
+        report.orphaned = orphaned;
+        report.at_risk_files = at_risk_files;
+        report.coverage = coverage;
+        report.comparison = comparison;
+        report.reconciliation = reconciliation;
+        report.author_merges = author_merges;
+        let _ = progress.send(ProgressEvent::Done(Box::new(report)));
+
         Ok(())
     });
 
+    if plain || validate {
+        for event in progress_ui {
+            app.apply_progress(event);
+        }
+        loading_thread
+            .join()
+            .map_err(|_| io_err_to_box_err(std::io::Error::other("Loading thread panicked")))??;
+        if let Some(output_dir) = &split_output {
+            write_split_reports(&mut app, split_format, output_dir);
+        }
+        for error in &app.error_log {
+            eprintln!("{}", error);
+        }
+        if validate {
+            print!(
+                "{}",
+                reconciliation_report(&app.reconciliation, args.validate_tolerance)
+            );
+            if !app
+                .reconciliation
+                .iter()
+                .all(|row| row.is_within(args.validate_tolerance))
+            {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        print!("{}", plain_report(&app));
+        let violations = app.threshold_violations(&thresholds);
+        for violation in &violations {
+            eprintln!("Threshold failed: {}", violation);
+        }
+        if !violations.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    enable_raw_mode().map_err(io_err_to_box_err)?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).map_err(io_err_to_box_err)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).map_err(io_err_to_box_err)?;
+
     let mut last_tick = std::time::Instant::now();
-    let tick_rate = std::time::Duration::from_millis(100);
+    let tick_rate = std::time::Duration::from_millis(tick_rate_ms);
+    // Once loading is done nothing animates, so only wake up for input.
+    let idle_poll_rate = tick_rate.max(std::time::Duration::from_millis(250));
     let mut loading_thread = Some(loading_thread);
-    let mut loading_thread_complete = false;
 
     loop {
-        terminal
-            .draw(|f| {
-                if let Ok(guard) = app_ui.lock() {
-                    match guard.state {
-                        AppState::Loading => render_loading_screen(f, &guard),
-                        AppState::Main => render_main_view(f, &guard),
+        if loading_thread.is_some() {
+            let finished = loop {
+                match progress_ui.try_recv() {
+                    Ok(event) => {
+                        let done = matches!(event, ProgressEvent::Done(_));
+                        app.apply_progress(event);
+                        if done {
+                            break true;
+                        }
                     }
+                    Err(TryRecvError::Empty) => break false,
+                    // The thread ended without a report, so it failed.
+                    Err(TryRecvError::Disconnected) => break true,
                 }
-            })
-            .map_err(io_err_to_box_err)?;
-
-        if !loading_thread_complete {
-            if let Ok(guard) = app_ui.lock() {
-                if guard.state == AppState::Main {
-                    if let Some(thread) = loading_thread.take() {
-                        if let Err(e) = thread.join() {
-                            eprintln!("Loading thread error: {:?}", e);
-                        }
+            };
+            if finished {
+                if let Some(thread) = loading_thread.take() {
+                    let error = match thread.join() {
+                        Ok(Ok(())) => None,
+                        Ok(Err(e)) => Some(format!("Loading failed: {}", e)),
+                        Err(_) => Some(String::from("Loading thread panicked")),
+                    };
+                    if let Some(error) = error {
+                        app.error_log.push(error);
                     }
-                    loading_thread_complete = true;
+                }
+                if app.state == AppState::Loading {
+                    app.apply_progress(ProgressEvent::Done(Box::default()));
+                }
+                if let Some(output_dir) = &split_output {
+                    write_split_reports(&mut app, split_format, output_dir);
                 }
             }
         }
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| std::time::Duration::from_secs(0));
+        if std::mem::take(&mut app.dirty) {
+            terminal
+                .draw(|f| match app.state {
+                    AppState::Loading => render_loading_screen(f, &app),
+                    AppState::Main => render_main_view(f, &app),
+                })
+                .map_err(io_err_to_box_err)?;
+        }
 
-        if event::poll(timeout).map_err(io_err_to_box_err)? {
-            if let Event::Key(key) = event::read().map_err(io_err_to_box_err)? {
-                if let Ok(mut guard) = app_ui.lock() {
-                    if guard.state == AppState::Main {
-                        match key.code {
-                            KeyCode::Char('q') => {
-                                guard.quit = true;
-                            }
-                            KeyCode::Char('?') => guard.toggle_help(),
-                            KeyCode::Char('h') => {
-                                let output_path = PathBuf::from("git_contribution_report.html");
-                                match export_html_report(&guard, &output_path) {
+        let mut timeout = if app.state == AppState::Loading {
+            tick_rate
+                .checked_sub(last_tick.elapsed())
+                .unwrap_or_else(|| std::time::Duration::from_secs(0))
+        } else {
+            idle_poll_rate
+        };
+
+        // Handle every event already queued before drawing again, so a held
+        // key or a paste costs one frame instead of one per event.
+        while !app.quit && event::poll(timeout).map_err(io_err_to_box_err)? {
+            timeout = std::time::Duration::ZERO;
+            let event = event::read().map_err(io_err_to_box_err)?;
+
+            if let Event::Resize(_, _) = event {
+                app.dirty = true;
+            }
+
+            if let Event::Key(key) = event {
+                app.dirty = true;
+                app.status_message = None;
+                if app.state == AppState::Loading {
+                    if key.code == KeyCode::Char('q') {
+                        app.quit = true;
+                    } else if key.code == KeyCode::Esc && !cancel_ui.swap(true, Ordering::Relaxed) {
+                        app.loading_message =
+                            String::from("Cancelling; showing the repositories analyzed so far");
+                    }
+                } else if app.state == AppState::Main && app.command_line.is_some() {
+                    match key.code {
+                        KeyCode::Esc => app.close_command_line(),
+                        KeyCode::Enter => app.command_line_submit(),
+                        KeyCode::Backspace => app.command_line_pop(),
+                        KeyCode::Char(c) => app.command_line_push(c),
+                        _ => {}
+                    }
+                } else if app.state == AppState::Main && app.picker.is_some() {
+                    match key.code {
+                        KeyCode::Esc => app.close_picker(),
+                        KeyCode::Enter => app.picker_confirm(),
+                        KeyCode::Backspace => app.picker_pop(),
+                        KeyCode::Down => app.picker_next(),
+                        KeyCode::Up => app.picker_previous(),
+                        KeyCode::Char(c) => app.picker_push(c),
+                        _ => {}
+                    }
+                } else if app.state == AppState::Main && app.export_menu.is_some() {
+                    match key.code {
+                        KeyCode::Esc => app.close_export_menu(),
+                        KeyCode::Down => app.export_menu_next(),
+                        KeyCode::Up => app.export_menu_previous(),
+                        KeyCode::Enter => {
+                            if let Some(format) = app.export_menu_confirm() {
+                                let output_path = format.default_path();
+                                let message = match export_report(&app, format, &output_path) {
                                     Ok(_) => {
-                                        guard.loading_message =
-                                            format!("Report exported to {}", output_path.display());
-                                    }
-                                    Err(e) => {
-                                        guard.loading_message =
-                                            format!("Error exporting report: {}", e);
+                                        format!("Report exported to {}", output_path.display())
                                     }
-                                }
+                                    Err(e) => format!("Error exporting report: {}", e),
+                                };
+                                app.status_message = Some(message);
                             }
-                            KeyCode::Down => guard.next(),
-                            KeyCode::Up => guard.previous(),
-                            KeyCode::Tab => {
-                                if key.modifiers.contains(KeyModifiers::SHIFT) {
-                                    guard.previous_tab();
-                                } else {
-                                    guard.next_tab();
+                        }
+                        _ => {}
+                    }
+                } else if app.state == AppState::Main {
+                    match key.code {
+                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.open_picker()
+                        }
+                        KeyCode::Char('q') => {
+                            app.quit = true;
+                        }
+                        KeyCode::Char('?') => app.toggle_help(),
+                        KeyCode::Char('d') => app.toggle_compact(),
+                        KeyCode::Char('u') => app.toggle_ubiquitous_only(),
+                        KeyCode::Char(':') => app.open_command_line(),
+                        KeyCode::Char('e') => app.open_export_menu(),
+                        KeyCode::Char('h') => {
+                            let output_path = PathBuf::from("git_contribution_report.html");
+                            match export_html_report(&app, &output_path) {
+                                Ok(_) => {
+                                    app.loading_message =
+                                        format!("Report exported to {}", output_path.display());
+                                }
+                                Err(e) => {
+                                    app.loading_message = format!("Error exporting report: {}", e);
                                 }
                             }
-                            _ => {}
                         }
+                        KeyCode::Char('H') => {
+                            let output_path = PathBuf::from("git_contribution_report.html");
+                            let message = match export_html_report(&app, &output_path) {
+                                Ok(_) => match open_with_default_app(&output_path) {
+                                    Ok(()) => format!("Opened {}", output_path.display()),
+                                    Err(e) => format!(
+                                        "Report exported to {}, not opened: {}",
+                                        output_path.display(),
+                                        e
+                                    ),
+                                },
+                                Err(e) => format!("Error exporting report: {}", e),
+                            };
+                            app.status_message = Some(message);
+                        }
+                        #[cfg(feature = "rusqlite")]
+                        KeyCode::Char('s') => {
+                            let output_path = PathBuf::from("git_contribution_report.sqlite");
+                            let message = match export_sqlite_report(&app, &output_path) {
+                                Ok(_) => format!("Report exported to {}", output_path.display()),
+                                Err(e) => format!("Error exporting report: {}", e),
+                            };
+                            app.status_message = Some(message);
+                        }
+                        KeyCode::Char('c') => app.toggle_comparison(),
+                        KeyCode::Char('a') => app.toggle_collaborators(),
+                        KeyCode::Char('o') => app.toggle_orphaned(),
+                        KeyCode::Char('i') => app.toggle_at_risk(),
+                        KeyCode::Char('r') => {
+                            let message = match app.current_repository_path() {
+                                Some(path) => match open_with_default_app(path) {
+                                    Ok(()) => format!("Opened {}", path.display()),
+                                    Err(e) => format!("Cannot open {}: {}", path.display(), e),
+                                },
+                                None => String::from("Switch to a repository tab to open it"),
+                            };
+                            app.status_message = Some(message);
+                        }
+                        KeyCode::Char('x') => app.toggle_errors(),
+                        KeyCode::Char('m') => app.cycle_display_metric(),
+                        KeyCode::Char('p') => app.toggle_line_shares(),
+                        KeyCode::Enter => app.toggle_author_detail(),
+                        KeyCode::Esc => app.clear_selection(),
+                        KeyCode::Char('w') => {
+                            let output_dir = PathBuf::from("codeowners");
+                            let message = match export_codeowners(
+                                &app,
+                                &output_dir,
+                                app.codeowners_threshold,
+                            ) {
+                                Ok(_) => format!(
+                                    "CODEOWNERS suggestions exported to {}",
+                                    output_dir.display()
+                                ),
+                                Err(e) => format!("Error exporting CODEOWNERS: {}", e),
+                            };
+                            app.status_message = Some(message);
+                        }
+                        KeyCode::Down => app.next(),
+                        KeyCode::Up => app.previous(),
+                        KeyCode::Backspace | KeyCode::Char('[') => app.go_back(),
+                        KeyCode::Char(']') => app.go_forward(),
+                        KeyCode::Tab => {
+                            if key.modifiers.contains(KeyModifiers::SHIFT) {
+                                app.previous_tab();
+                            } else {
+                                app.next_tab();
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
         }
 
-        // Check quit condition
-        if let Ok(guard) = app_ui.lock() {
-            if guard.quit {
-                break;
-            }
+        if app.quit {
+            break;
         }
 
         if last_tick.elapsed() >= tick_rate {
-            if let Ok(mut guard) = app_ui.lock() {
-                if guard.state == AppState::Loading {
-                    guard.loading_progress = (guard.loading_progress + 1) % 100;
-                }
-            }
+            app.tick();
             last_tick = std::time::Instant::now();
         }
     }
@@ -225,5 +1160,40 @@ fn main() -> Result<(), Box<dyn Error + Send>> {
     execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(io_err_to_box_err)?;
     terminal.show_cursor().map_err(io_err_to_box_err)?;
 
+    // Quit while still loading: cancelling kills the running git command
+    // within `GIT_POLL_INTERVAL` and skips the remaining repositories, so the
+    // thread ends promptly and leaves no git processes behind.
+    if let Some(thread) = loading_thread.take() {
+        cancel_ui.store(true, Ordering::Relaxed);
+        let _ = thread.join();
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn arguments_are_consistent() {
+        CliArgs::command().debug_assert();
+    }
+
+    #[test]
+    fn patterns_split_on_commas_and_repeat() {
+        let args = CliArgs::try_parse_from([
+            "git-contribution-analyzer",
+            "--pattern",
+            "api-*,web-*",
+            "--pattern",
+            "lib-*",
+        ])
+        .unwrap();
+        assert_eq!(args.pattern, ["api-*", "web-*", "lib-*"]);
+
+        let args = CliArgs::try_parse_from(["git-contribution-analyzer"]).unwrap();
+        assert_eq!(args.pattern, ["*"]);
+    }
+}
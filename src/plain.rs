@@ -0,0 +1,243 @@
+use crate::{
+    app::App,
+    git::{format_gap, format_percent, signed_percent, CommitReconciliation},
+};
+
+/// Lays out rows under their headers as left-aligned text columns, each as
+/// wide as its longest cell, with a dashed rule under the header.
+pub fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let mut table = format_row(headers.to_vec());
+    table.push_str(&format_row(rule.iter().map(String::as_str).collect()));
+    for row in rows {
+        table.push_str(&format_row(row.iter().map(String::as_str).collect()));
+    }
+
+    table
+}
+
+/// Renders the summary and every repository as plain text tables for `--plain`.
+pub fn plain_report(app: &App) -> String {
+    let overview = app.overview();
+    let mut report = format!(
+        "Repositories: {} | Authors: {} | Commits: {} | Line churn: {} (+{} / -{})\n{}\n",
+        overview.repositories,
+        overview.authors,
+        overview.commits,
+        overview.line_churn(),
+        overview.lines_added,
+        overview.lines_deleted,
+        app.coverage.summary()
+    );
+    if let Some((average, lowest, lowest_score)) = app.health_overview() {
+        report.push_str(&format!(
+            "Health: average {}/100 | lowest: {} ({}/100)\n",
+            average, lowest, lowest_score
+        ));
+    }
+    for merge in &app.author_merges {
+        report.push_str(&format!("Merged: {}\n", merge.describe()));
+    }
+    report.push('\n');
+
+    let mut headers = vec!["Rank", app.group_by.name_header(), "Email"];
+    if app.org_map.is_some() {
+        headers.push("Org");
+    }
+    headers.push("Commits");
+    if app.show_merges {
+        headers.push("Merges");
+    }
+    headers.extend([
+        "Lines Added",
+        "Lines Deleted",
+        "Overall %",
+        "Preferred Repo",
+        "Preferred %",
+    ]);
+    if app.show_surviving {
+        headers.push("Surviving");
+    }
+    if app.show_signatures {
+        headers.push("Signed %");
+    }
+    if app.show_reviews {
+        headers.push("Reviews");
+    }
+
+    let rows: Vec<Vec<String>> = app
+        .summary_rows()
+        .into_iter()
+        .map(|s| {
+            let mut row = vec![format!("#{}", s.rank), s.author.clone(), s.email.clone()];
+            if app.org_map.is_some() {
+                row.push(s.organization.clone().unwrap_or_default());
+            }
+            row.push(s.total_commits.to_string());
+            if app.show_merges {
+                row.push(s.total_merges.to_string());
+            }
+            row.extend([
+                s.total_lines_added.to_string(),
+                s.total_lines_deleted.to_string(),
+                format_percent(s.overall_contribution_percent, app.percent_precision),
+                s.preferred_repo.clone(),
+                format_percent(s.preferred_repo_percent, app.percent_precision),
+            ]);
+            if app.show_surviving {
+                row.push(s.total_surviving_lines.to_string());
+            }
+            if app.show_signatures {
+                row.push(format_percent(
+                    signed_percent(s.total_signed_commits, s.total_commits),
+                    app.percent_precision,
+                ));
+            }
+            if app.show_reviews {
+                row.push(s.reviews.to_string());
+            }
+            row
+        })
+        .collect();
+
+    report.push_str(&format!(
+        "{}{}{}\n\n",
+        app.group_by.summary_title(),
+        app.summary_basis.title_note(),
+        app.summary_filter_note()
+    ));
+    report.push_str(&format_table(&headers, &rows));
+
+    for repo_name in &app.repositories {
+        let contributions = match app.contributions.get(repo_name) {
+            Some(contributions) => contributions,
+            None => continue,
+        };
+
+        let mut headers = vec!["Rank", "Author", "Email", "Commits"];
+        if app.show_merges {
+            headers.push("Merges");
+        }
+        headers.extend(["Lines Added", "Lines Deleted", "Contribution %"]);
+        if app.show_surviving {
+            headers.push("Surviving");
+        }
+        if app.show_signatures {
+            headers.push("Signed %");
+        }
+
+        let rows: Vec<Vec<String>> = contributions
+            .iter()
+            .map(|c| {
+                let mut row = vec![
+                    format!("#{}", c.rank),
+                    c.display_author(),
+                    c.email.clone(),
+                    c.commits.to_string(),
+                ];
+                if app.show_merges {
+                    row.push(c.merges.to_string());
+                }
+                row.extend([
+                    c.lines_added.to_string(),
+                    c.lines_deleted.to_string(),
+                    format_percent(c.contribution_percent, app.percent_precision),
+                ]);
+                if app.show_surviving {
+                    row.push(c.surviving_lines.to_string());
+                }
+                if app.show_signatures {
+                    row.push(format_percent(
+                        signed_percent(c.signed_commits, c.commits),
+                        app.percent_precision,
+                    ));
+                }
+                row
+            })
+            .collect();
+
+        report.push_str(&format!("\nRepository: {}", repo_name));
+        if let Some(range) = app.history_limits(repo_name) {
+            report.push_str(&format!(" ({})", range));
+        }
+        if let Some(gap) = app
+            .repository_info
+            .get(repo_name)
+            .and_then(|info| info.median_commit_gap)
+        {
+            report.push_str(&format!(" | median gap: {}", format_gap(gap)));
+        }
+        if app.is_shallow(repo_name) {
+            report.push_str(" (shallow clone, history is incomplete)");
+        }
+        if let Some(health) = app.repository_health(repo_name) {
+            report.push_str(&format!(
+                "\nHealth: {}/100 ({})",
+                health.score,
+                health.breakdown()
+            ));
+        }
+        report.push_str("\n\n");
+        report.push_str(&format_table(&headers, &rows));
+    }
+
+    report
+}
+
+/// Lists each repository's analyzed commit total next to the `git rev-list`
+/// count for `--validate`, marking those that differ by more than `tolerance`
+/// percent.
+pub fn reconciliation_report(rows: &[CommitReconciliation], tolerance: f64) -> String {
+    let headers = [
+        "Repository",
+        "git rev-list",
+        "Analyzed",
+        "Difference",
+        "Status",
+    ];
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.repository.clone(),
+                row.reference_commits
+                    .map_or_else(|| String::from("-"), |count| count.to_string()),
+                row.analyzed_commits.to_string(),
+                match row.difference() {
+                    Some(0) => String::from("0"),
+                    Some(difference) => format!("{:+}", difference),
+                    None => String::from("-"),
+                },
+                String::from(if row.is_within(tolerance) {
+                    "ok"
+                } else {
+                    "MISMATCH"
+                }),
+            ]
+        })
+        .collect();
+
+    format!(
+        "Commit reconciliation (tolerance {}%)\n\n{}",
+        tolerance,
+        format_table(&headers, &rows)
+    )
+}
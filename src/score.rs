@@ -0,0 +1,458 @@
+use crate::app::AuthorSummary;
+use crate::git::{competition_ranks, Contribution};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// A per-author figure a `--score` expression can refer to by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreMetric {
+    Commits,
+    LinesAdded,
+    LinesDeleted,
+    /// Lines added plus lines deleted.
+    Lines,
+    Merges,
+    SurvivingLines,
+    SignedCommits,
+    RecentCommits,
+    /// Distinct issue/PR numbers referenced in commit subjects.
+    Issues,
+    /// Reviews from `--reviews-csv`, only known across all repositories, so
+    /// zero in the repository tables.
+    Reviews,
+}
+
+impl ScoreMetric {
+    pub const ALL: [ScoreMetric; 10] = [
+        ScoreMetric::Commits,
+        ScoreMetric::LinesAdded,
+        ScoreMetric::LinesDeleted,
+        ScoreMetric::Lines,
+        ScoreMetric::Merges,
+        ScoreMetric::SurvivingLines,
+        ScoreMetric::SignedCommits,
+        ScoreMetric::RecentCommits,
+        ScoreMetric::Issues,
+        ScoreMetric::Reviews,
+    ];
+
+    /// The identifier used in expressions.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoreMetric::Commits => "commits",
+            ScoreMetric::LinesAdded => "lines_added",
+            ScoreMetric::LinesDeleted => "lines_deleted",
+            ScoreMetric::Lines => "lines",
+            ScoreMetric::Merges => "merges",
+            ScoreMetric::SurvivingLines => "surviving_lines",
+            ScoreMetric::SignedCommits => "signed_commits",
+            ScoreMetric::RecentCommits => "recent_commits",
+            ScoreMetric::Issues => "issues",
+            ScoreMetric::Reviews => "reviews",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<ScoreMetric> {
+        ScoreMetric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == name)
+    }
+}
+
+/// Values of every `ScoreMetric` for one table row.
+pub trait ScoreInputs {
+    fn metric(&self, metric: ScoreMetric) -> f64;
+}
+
+impl ScoreInputs for Contribution {
+    fn metric(&self, metric: ScoreMetric) -> f64 {
+        match metric {
+            ScoreMetric::Commits => self.commits as f64,
+            ScoreMetric::LinesAdded => self.lines_added as f64,
+            ScoreMetric::LinesDeleted => self.lines_deleted as f64,
+            ScoreMetric::Lines => self.lines_added as f64 + self.lines_deleted as f64,
+            ScoreMetric::Merges => self.merges as f64,
+            ScoreMetric::SurvivingLines => self.surviving_lines as f64,
+            ScoreMetric::SignedCommits => self.signed_commits as f64,
+            ScoreMetric::RecentCommits => self.recent_commits as f64,
+            ScoreMetric::Issues => self.issues.len() as f64,
+            ScoreMetric::Reviews => 0.0,
+        }
+    }
+}
+
+impl ScoreInputs for AuthorSummary {
+    fn metric(&self, metric: ScoreMetric) -> f64 {
+        match metric {
+            ScoreMetric::Commits => self.total_commits as f64,
+            ScoreMetric::LinesAdded => self.total_lines_added as f64,
+            ScoreMetric::LinesDeleted => self.total_lines_deleted as f64,
+            ScoreMetric::Lines => self.total_lines_added as f64 + self.total_lines_deleted as f64,
+            ScoreMetric::Merges => self.total_merges as f64,
+            ScoreMetric::SurvivingLines => self.total_surviving_lines as f64,
+            ScoreMetric::SignedCommits => self.total_signed_commits as f64,
+            ScoreMetric::RecentCommits => self.recent_commits as f64,
+            ScoreMetric::Issues => self.issues_touched as f64,
+            ScoreMetric::Reviews => self.reviews as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    /// Dividing by zero gives zero rather than infinity, so one author with
+    /// no merges cannot make every share meaningless.
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide if right == 0.0 => 0.0,
+            Operator::Divide => left / right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Metric(ScoreMetric),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, inputs: &dyn ScoreInputs) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Metric(metric) => inputs.metric(*metric),
+            Node::Negate(node) => -node.evaluate(inputs),
+            Node::Binary(operator, left, right) => {
+                operator.apply(left.evaluate(inputs), right.evaluate(inputs))
+            }
+        }
+    }
+
+    /// The node's value when it uses no metric, such as `(1 - 1)`.
+    fn constant(&self) -> Option<f64> {
+        match self {
+            Node::Number(value) => Some(*value),
+            Node::Metric(_) => None,
+            Node::Negate(node) => node.constant().map(|value| -value),
+            Node::Binary(operator, left, right) => {
+                Some(operator.apply(left.constant()?, right.constant()?))
+            }
+        }
+    }
+}
+
+/// A parsed `--score` expression: numbers, metric names, `+ - * /` and
+/// parentheses, with the usual precedence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreExpression {
+    source: String,
+    root: Node,
+}
+
+impl ScoreExpression {
+    pub fn parse(source: &str) -> Result<ScoreExpression, Box<dyn Error + Send>> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+        let root = parser.expression().and_then(|root| match parser.peek() {
+            None => Ok(root),
+            Some(c) => Err(format!("unexpected '{}'", c)),
+        });
+
+        match root {
+            Ok(root) => Ok(ScoreExpression {
+                source: source.trim().to_string(),
+                root,
+            }),
+            Err(message) => Err(Box::new(std::io::Error::other(format!(
+                "Invalid score expression '{}' at column {}: {}",
+                source,
+                parser.position + 1,
+                message
+            )))),
+        }
+    }
+
+    /// The row's score; anything below zero or not finite counts as zero,
+    /// since scores are turned into shares.
+    pub fn evaluate(&self, inputs: &dyn ScoreInputs) -> f64 {
+        let score = self.root.evaluate(inputs);
+        if score.is_finite() && score > 0.0 {
+            score
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for ScoreExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Recursive descent over `expression := term (('+' | '-') term)*`,
+/// `term := factor (('*' | '/') factor)*` and
+/// `factor := '-' factor | number | metric | '(' expression ')'`.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.source[self.position..].chars().next()
+    }
+
+    fn expression(&mut self) -> Result<Node, String> {
+        let mut node = self.term()?;
+        while let Some(operator @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let operator = if operator == '+' {
+                Operator::Add
+            } else {
+                Operator::Subtract
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    fn term(&mut self) -> Result<Node, String> {
+        let mut node = self.factor()?;
+        while let Some(operator @ ('*' | '/')) = self.peek() {
+            self.position += 1;
+            self.peek();
+            let divisor_at = self.position;
+            let right = self.factor()?;
+            let operator = if operator == '*' {
+                Operator::Multiply
+            } else if right.constant() == Some(0.0) {
+                self.position = divisor_at;
+                return Err(String::from("division by zero"));
+            } else {
+                Operator::Divide
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(right));
+        }
+        Ok(node)
+    }
+
+    fn factor(&mut self) -> Result<Node, String> {
+        let next = self.peek();
+        let start = self.position;
+        match next {
+            Some('-') => {
+                self.position += 1;
+                Ok(Node::Negate(Box::new(self.factor()?)))
+            }
+            Some('(') => {
+                self.position += 1;
+                let node = self.expression()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.position += 1;
+                        Ok(node)
+                    }
+                    _ => Err(String::from("missing ')'")),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let token = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                token.parse().map(Node::Number).map_err(|_| {
+                    self.position = start;
+                    format!("invalid number '{}'", token)
+                })
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let token = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                ScoreMetric::from_name(token)
+                    .map(Node::Metric)
+                    .ok_or_else(|| {
+                        self.position = start;
+                        format!(
+                            "unknown metric '{}', expected one of {}",
+                            token,
+                            ScoreMetric::ALL.map(ScoreMetric::name).join(", ")
+                        )
+                    })
+            }
+            Some(c) => Err(format!("unexpected '{}'", c)),
+            None => Err(String::from("expression ends too early")),
+        }
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position;
+        let rest = &self.source[start..];
+        self.position += rest.find(|c| !accept(c)).unwrap_or(rest.len());
+        &self.source[start..self.position]
+    }
+}
+
+/// Each row's share of the total score, or zero when nobody scores.
+fn score_shares<T: ScoreInputs>(rows: &[T], score: &ScoreExpression) -> Vec<f64> {
+    let scores: Vec<f64> = rows.iter().map(|row| score.evaluate(row)).collect();
+    let total: f64 = scores.iter().sum();
+    scores
+        .into_iter()
+        .map(|score| {
+            if total > 0.0 {
+                score / total * 100.0
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Replaces every contribution percentage with the contribution's share of
+/// its repository's total score, then re-sorts and re-ranks each repository.
+pub fn apply_contribution_score(
+    contributions_map: &mut HashMap<String, Vec<Contribution>>,
+    score: &ScoreExpression,
+) {
+    for contributions in contributions_map.values_mut() {
+        let shares = score_shares(contributions, score);
+        for (contrib, share) in contributions.iter_mut().zip(shares) {
+            contrib.contribution_percent = share;
+        }
+        contributions.sort_by(|a, b| b.contribution_percent.total_cmp(&a.contribution_percent));
+
+        let percents: Vec<f64> = contributions
+            .iter()
+            .map(|c| c.contribution_percent)
+            .collect();
+        for (contrib, rank) in contributions.iter_mut().zip(competition_ranks(&percents)) {
+            contrib.rank = rank;
+        }
+    }
+}
+
+/// Replaces every overall percentage with the row's share of the total score
+/// over the summary's own totals, then re-sorts and re-ranks.
+pub fn apply_summary_score(summaries: &mut [AuthorSummary], score: &ScoreExpression) {
+    let shares = score_shares(summaries, score);
+    for (summary, share) in summaries.iter_mut().zip(shares) {
+        summary.overall_contribution_percent = share;
+    }
+    summaries.sort_by(|a, b| {
+        b.overall_contribution_percent
+            .total_cmp(&a.overall_contribution_percent)
+    });
+
+    let percents: Vec<f64> = summaries
+        .iter()
+        .map(|s| s.overall_contribution_percent)
+        .collect();
+    for (summary, rank) in summaries.iter_mut().zip(competition_ranks(&percents)) {
+        summary.rank = rank;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3 commits, 4 lines added, 1 deleted and no merges.
+    fn contribution() -> Contribution {
+        Contribution {
+            commits: 3,
+            lines_added: 4,
+            lines_deleted: 1,
+            ..Default::default()
+        }
+    }
+
+    fn score(source: &str) -> f64 {
+        ScoreExpression::parse(source)
+            .unwrap()
+            .evaluate(&contribution())
+    }
+
+    fn parse_error(source: &str) -> String {
+        ScoreExpression::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(score("commits + lines * 2"), 13.0);
+        assert_eq!(score("lines * 2 + commits"), 13.0);
+        assert_eq!(score("lines_added - lines_deleted - commits"), 0.0);
+        assert_eq!(score("12 / commits / 2"), 2.0);
+    }
+
+    #[test]
+    fn parentheses_and_unary_minus() {
+        assert_eq!(score("(commits + lines) * 2"), 16.0);
+        assert_eq!(score("-commits + 10"), 7.0);
+        assert_eq!(score("--commits"), 3.0);
+        assert_eq!(score("10 * -(lines_deleted - commits)"), 20.0);
+    }
+
+    #[test]
+    fn negative_and_divided_by_zero_scores_count_as_zero() {
+        assert_eq!(score("-commits"), 0.0);
+        assert_eq!(score("commits / merges"), 0.0);
+        assert_eq!(score("commits + lines / merges"), 3.0);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_column() {
+        assert_eq!(
+            parse_error("commits + bogus"),
+            format!(
+                "Invalid score expression 'commits + bogus' at column 11: unknown metric 'bogus', expected one of {}",
+                ScoreMetric::ALL.map(ScoreMetric::name).join(", ")
+            )
+        );
+        assert!(parse_error("(commits + 1").ends_with("at column 13: missing ')'"));
+        assert!(parse_error("commits +").ends_with("at column 10: expression ends too early"));
+        assert!(parse_error("commits 2").ends_with("at column 9: unexpected '2'"));
+    }
+
+    #[test]
+    fn constant_zero_divisors_are_rejected() {
+        assert!(parse_error("commits / 0").ends_with("at column 11: division by zero"));
+        assert!(parse_error("1/(0)").ends_with("at column 3: division by zero"));
+        assert!(parse_error("commits / (1 - 1)").ends_with("at column 11: division by zero"));
+        assert!(ScoreExpression::parse("commits / (1 - merges)").is_ok());
+    }
+
+    #[test]
+    fn scores_become_shares_and_ranks() {
+        let mut summaries = vec![
+            AuthorSummary {
+                total_commits: 1,
+                reviews: 3,
+                ..Default::default()
+            },
+            AuthorSummary {
+                total_commits: 3,
+                ..Default::default()
+            },
+        ];
+        let score = ScoreExpression::parse("commits + reviews").unwrap();
+        apply_summary_score(&mut summaries, &score);
+        assert!((summaries[0].overall_contribution_percent - 400.0 / 7.0).abs() < 1e-9);
+        assert_eq!(summaries[0].rank, 1);
+        assert!((summaries[1].overall_contribution_percent - 300.0 / 7.0).abs() < 1e-9);
+        assert_eq!(summaries[1].rank, 2);
+    }
+}
@@ -1,18 +1,67 @@
 use crate::{
-    app::{App, AuthorSummary},
-    git::Contribution,
+    anonymize::stable_hash,
+    app::{App, AuthorSummary, ComparisonView, CoverageStats, DisplayMetric, OverviewStats},
+    export::ExportFormat,
+    git::{
+        format_gap, format_percent, format_timestamp, has_line_data, signed_percent,
+        top_percentile, top_terms, AuthorMerge, CollaboratorPair, Contribution, GroupBy,
+        SummaryBasis, COLLABORATION_AUTHOR_LIMIT, NO_LINE_DATA_MESSAGE, WORKING_TREE_MARKER,
+    },
 };
 use std::io;
 use tui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Spans,
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs},
+    text::{Span, Spans},
+    widgets::{
+        Block, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table, Tabs,
+        Wrap,
+    },
     Frame,
 };
 
+/// Smallest terminal the views are laid out for: room for the summary's
+/// columns, the tabs, a few rows and the footer. Anything smaller gets a note
+/// instead of zero-height panes and clipped columns.
+pub const MIN_TERMINAL_WIDTH: u16 = 60;
+pub const MIN_TERMINAL_HEIGHT: u16 = 15;
+
+/// Draws a centered "too small" note in place of a view when the terminal is
+/// below the minimum size, and says whether it did.
+fn render_too_small(f: &mut Frame<CrosstermBackend<io::Stdout>>) -> bool {
+    let size = f.size();
+    if size.width >= MIN_TERMINAL_WIDTH && size.height >= MIN_TERMINAL_HEIGHT {
+        return false;
+    }
+    let message = format!(
+        "Terminal too small (need at least {}x{})",
+        MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT
+    );
+    let lines = (message.len() as u16)
+        .div_ceil(size.width.max(1))
+        .min(size.height);
+    // Word wrapping may take more lines than that, so leave it the rest.
+    let top = (size.height - lines) / 2;
+    let area = Rect {
+        y: size.y + top,
+        height: size.height - top,
+        ..size
+    };
+    f.render_widget(
+        Paragraph::new(message)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(tui::layout::Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+    true
+}
+
 pub fn render_loading_screen(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+    if render_too_small(f) {
+        return;
+    }
     let size = f.size();
 
     let block = Block::default()
@@ -23,222 +72,1380 @@ pub fn render_loading_screen(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &
     let loading_text = format!(
         "{} {}",
         app.loading_message,
-        ".".repeat(((app.loading_progress % 4) + 1) as usize)
+        ".".repeat(((app.loading_tick % 4) + 1) as usize)
     );
 
     let loading_paragraph = Paragraph::new(loading_text)
-        .style(Style::default().fg(Color::Cyan))
+        .style(Style::default().fg(palette(app.accessible).info))
         .block(Block::default())
         .alignment(tui::layout::Alignment::Center);
 
     let loading_area = centered_rect(60, 20, size);
-    f.render_widget(loading_paragraph, loading_area);
+    let loading_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(1)])
+        .split(loading_area);
+
+    f.render_widget(loading_paragraph, loading_chunks[0]);
+
+    if let Some(progress) = app.loading_progress {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(palette(app.accessible).info))
+            .percent(progress as u16);
+        f.render_widget(gauge, loading_chunks[1]);
+    }
 }
 
 pub fn render_main_view(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+    if render_too_small(f) {
+        return;
+    }
     let size = f.size();
 
-    let main_block = Block::default()
-        .title("Git Contribution Analyzer")
-        .borders(Borders::ALL);
-    f.render_widget(main_block, size);
+    if !app.compact {
+        let main_block = Block::default()
+            .title("Git Contribution Analyzer")
+            .borders(Borders::ALL);
+        f.render_widget(main_block, size);
+    }
+
+    // Compact mode keeps the footer only while it has more to say than "?".
+    let footer_has_news = app.status_message.is_some() || !app.error_log.is_empty();
+    let help_height = if app.show_help {
+        HELP_LINES.len() as u16 + 2
+    } else if app.compact {
+        footer_has_news as u16
+    } else {
+        3
+    };
+    let tabs_height = if app.compact { 1 } else { 3 };
+    // Likewise the command bar, while the tables are in plain rank order.
+    let command_bar_height =
+        (!app.compact || app.command_line.is_some() || app.is_sorted_or_filtered()) as u16;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Tabs
-            Constraint::Min(10),   // Content
-            Constraint::Length(3), // Help
+            Constraint::Length(tabs_height),        // Tabs
+            Constraint::Min(10),                    // Content
+            Constraint::Length(command_bar_height), // Sort and filter
+            Constraint::Length(help_height),        // Help
         ])
         .split(size);
 
     let mut tab_titles = app
         .repositories
         .iter()
-        .map(|repo| Spans::from(repo.clone()))
+        .enumerate()
+        .map(|(index, repo)| {
+            let name = if app.is_shallow(repo) {
+                format!("{} ⚠", repo)
+            } else {
+                repo.clone()
+            };
+            match app.group_label(index) {
+                Some(group) => Spans::from(vec![
+                    Span::styled(
+                        format!("{} ▸ ", group),
+                        Style::default().fg(palette(app.accessible).muted),
+                    ),
+                    Span::raw(name),
+                ]),
+                None => Spans::from(name),
+            }
+        })
         .collect::<Vec<Spans>>();
 
     tab_titles.push(Spans::from("Summary"));
 
-    let tabs = Tabs::new(tab_titles)
-        .block(Block::default().borders(Borders::ALL).title("Repositories"))
+    let mut tabs = Tabs::new(tab_titles);
+    if !app.compact {
+        tabs = tabs.block(Block::default().borders(Borders::ALL).title("Repositories"));
+    }
+    let tabs = tabs
         .select(app.current_tab)
         .style(Style::default())
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
+                .add_modifier(if app.accessible {
+                    Modifier::BOLD | Modifier::UNDERLINED
+                } else {
+                    Modifier::BOLD
+                }),
         );
 
     f.render_widget(tabs, chunks[0]);
 
-    if app.current_tab < app.repositories.len() {
-        let repo_name = &app.repositories[app.current_tab];
-        if let Some(contributions) = app.contributions.get(repo_name) {
-            render_repository_tab(
-                f,
-                chunks[1],
-                repo_name,
-                contributions,
-                app.selected_in_tab[app.current_tab],
-            );
-        }
+    if let (true, Some(comparison)) = (app.show_comparison, &app.comparison) {
+        render_comparison_view(f, chunks[1], comparison, app.use_color, app.accessible);
+    } else if app.show_collaborators {
+        render_collaborators_view(
+            f,
+            chunks[1],
+            &app.collaborators,
+            app.use_color,
+            app.accessible,
+        );
+    } else if app.show_orphaned {
+        render_orphaned_view(f, chunks[1], app);
+    } else if app.show_at_risk {
+        render_at_risk_view(f, chunks[1], app);
+    } else if app.show_errors {
+        render_errors_view(f, chunks[1], &app.error_log, &app.author_merges);
+    } else if app.current_tab < app.repositories.len() {
+        render_repository_tab(f, chunks[1], app, &app.repositories[app.current_tab]);
     } else {
+        let summary_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(
+                    3 + app.window_start().is_some() as u16
+                        + app.health_overview().is_some() as u16,
+                ),
+                Constraint::Min(5),
+            ])
+            .split(chunks[1]);
+
+        render_summary_overview(
+            f,
+            summary_chunks[0],
+            &app.overview(),
+            &app.coverage,
+            app.health_overview(),
+            app.new_and_returning_counts(),
+            app.accessible,
+        );
         render_summary_tab(
             f,
-            chunks[1],
-            &app.author_summaries,
+            summary_chunks[1],
+            &app.summary_rows(),
             app.selected_in_tab[app.current_tab],
+            &TableOptions::from_app(app),
         );
     }
 
+    if command_bar_height > 0 {
+        render_command_bar(f, chunks[2], app);
+    }
+
     if app.show_help {
-        render_help(f, chunks[2]);
+        render_help(f, chunks[3]);
+    } else if help_height > 0 {
+        render_help_shortcut(
+            f,
+            chunks[3],
+            app.error_log.len(),
+            app.status_message.as_deref(),
+            panel_borders(app.compact),
+            app.accessible,
+        );
+    }
+
+    if app.show_author_detail {
+        render_author_detail(f, app);
+    }
+
+    if app.picker.is_some() {
+        render_repository_picker(f, app);
+    }
+
+    if app.export_menu.is_some() {
+        render_export_menu(f, app);
+    }
+}
+
+pub fn render_repository_picker(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+    let picker = match &app.picker {
+        Some(picker) => picker,
+        None => return,
+    };
+
+    let area = centered_rect(50, 60, f.size());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let input = Paragraph::new(format!("> {}", picker.query)).block(
+        Block::default()
+            .title("Go to repository (Enter to jump, Esc to cancel)")
+            .borders(Borders::ALL),
+    );
+
+    let items = picker
+        .matches
+        .iter()
+        .map(|&tab| ListItem::new(app.tab_name(tab).to_string()))
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    if !picker.matches.is_empty() {
+        state.select(Some(picker.selected));
+    }
+
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+pub fn render_export_menu(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+    let selected = match app.export_menu {
+        Some(selected) => selected,
+        None => return,
+    };
+
+    let items = ExportFormat::ALL
+        .iter()
+        .map(|format| {
+            ListItem::new(format!(
+                "{:<10} {}",
+                format.label(),
+                format.default_path().display()
+            ))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Export report (Enter to write, Esc to cancel)")
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+
+    let area = centered_rect(50, 30, f.size());
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+const DETAIL_TERM_LIMIT: usize = 8;
+
+fn format_terms(terms: &std::collections::HashMap<String, u32>) -> String {
+    let top = top_terms(terms, DETAIL_TERM_LIMIT);
+    if top.is_empty() {
+        return String::from("-");
+    }
+    top.iter()
+        .map(|(term, count)| format!("{} ({})", term, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn render_author_detail(f: &mut Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+    let selected = match app.selected_in_tab.get(app.current_tab).copied().flatten() {
+        Some(selected) => selected,
+        None => return,
+    };
+    if let (Some(offset), Some(contribution)) = (app.commit_list, app.selected_contribution()) {
+        render_commit_list(f, app, contribution, offset);
+        return;
+    }
+    let mut title = "Author Detail (Enter/Esc to close)";
+
+    let lines = if app.current_tab < app.repositories.len() {
+        let repo_name = &app.repositories[app.current_tab];
+        let contributions_len = app.contributions.get(repo_name).map_or(0, Vec::len);
+        match app.repository_rows(repo_name).get(selected) {
+            Some(c) => {
+                if !c.kept_commits.is_empty() {
+                    title = "Author Detail (Enter: list commits, Esc: close)";
+                }
+                vec![
+                    Spans::from(format!("{} <{}>", c.author, c.email)),
+                    Spans::from(format!("Repository: {}", c.repository)),
+                    Spans::from(format!(
+                        "Rank: #{} of {} (top {:.0}%)",
+                        c.rank,
+                        contributions_len,
+                        top_percentile(c.rank, contributions_len)
+                    )),
+                    Spans::from(format!(
+                        "Commits: {} | Lines: +{} / -{} | Contribution: {}",
+                        c.commits,
+                        c.lines_added,
+                        c.lines_deleted,
+                        format_percent(c.contribution_percent, app.percent_precision)
+                    )),
+                    Spans::from(app.active_dates(c.first_commit_at, c.last_commit_at)),
+                    Spans::from(format!("Issues touched: {}", c.issues.len())),
+                    Spans::from(""),
+                    Spans::from(format!("Top terms: {}", format_terms(&c.terms))),
+                ]
+            }
+            None => return,
+        }
     } else {
-        render_help_shortcut(f, chunks[2]);
+        match app.summary_rows().get(selected) {
+            Some(s) => {
+                let mut lines = vec![
+                    Spans::from(format!("{} <{}>", s.author, s.email)),
+                    Spans::from(format!(
+                        "Preferred repository: {} ({})",
+                        s.preferred_repo,
+                        format_percent(s.preferred_repo_percent, app.percent_precision)
+                    )),
+                    Spans::from(format!(
+                        "Rank: #{} of {} (top {:.0}%)",
+                        s.rank,
+                        app.author_summaries.len(),
+                        top_percentile(s.rank, app.author_summaries.len())
+                    )),
+                    Spans::from(format!(
+                        "Commits: {} | Lines: +{} / -{} | Overall: {}",
+                        s.total_commits,
+                        s.total_lines_added,
+                        s.total_lines_deleted,
+                        format_percent(s.overall_contribution_percent, app.percent_precision)
+                    )),
+                    Spans::from(app.active_dates(s.first_commit_at, s.last_commit_at)),
+                    Spans::from(format!("Issues touched: {}", s.issues_touched)),
+                    Spans::from(match s.trend {
+                        Some(trend) => format!(
+                            "Trend: {} ({} commits in the trend window)",
+                            trend.arrow(),
+                            s.recent_commits
+                        ),
+                        None => String::from("Trend: - (pass --trend-window)"),
+                    }),
+                ];
+                if !s.repo_distribution.is_empty() {
+                    lines.push(Spans::from(""));
+                    lines.push(Spans::from("Own lines by repository:"));
+                    lines.extend(s.repo_distribution.iter().map(|(repo, percent)| {
+                        Spans::from(format!(
+                            "- {}: {}",
+                            repo,
+                            format_percent(*percent, app.percent_precision)
+                        ))
+                    }));
+                }
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(format!(
+                    "Top terms: {}",
+                    format_terms(&s.terms)
+                )));
+                lines
+            }
+            None => return,
+        }
+    };
+
+    let area = centered_rect(70, 50, f.size());
+    let detail = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(detail, area);
+}
+
+/// The commits behind the selected repository row (`--keep-commits`), from
+/// `offset` on, under their totals so they can be checked against the row.
+fn render_commit_list(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    app: &App,
+    contribution: &Contribution,
+    offset: usize,
+) {
+    let commits = &contribution.kept_commits;
+    let now = chrono::Utc::now().timestamp();
+    let mut lines = vec![
+        Spans::from(format!(
+            "{} commits | Lines: +{} / -{}",
+            commits.len(),
+            commits.iter().map(|c| c.lines_added).sum::<u32>(),
+            commits.iter().map(|c| c.lines_deleted).sum::<u32>()
+        )),
+        Spans::from(""),
+    ];
+    lines.extend(commits.iter().skip(offset).map(|commit| {
+        Spans::from(vec![
+            Span::styled(
+                format!("{:.8}", commit.hash),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!(
+                "  {}  +{}/-{}  {}",
+                format_timestamp(commit.timestamp, now, app.relative_dates),
+                commit.lines_added,
+                commit.lines_deleted,
+                commit.subject
+            )),
+        ])
+    }));
+
+    let area = centered_rect(70, 50, f.size());
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(
+                "Commits of {} in {} (↑/↓ to scroll, Esc to go back)",
+                contribution.author, contribution.repository
+            ))
+            .borders(Borders::ALL),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
+
+fn metric_header(metric: DisplayMetric, lines_header: &'static str) -> &'static str {
+    match metric {
+        DisplayMetric::Lines => lines_header,
+        DisplayMetric::Commits => "Commits %",
+        DisplayMetric::NetLines => "Net Lines",
     }
 }
 
+fn metric_value(
+    metric: DisplayMetric,
+    lines_percent: f64,
+    commits: u32,
+    total_commits: u32,
+    lines_added: u32,
+    lines_deleted: u32,
+    precision: usize,
+) -> String {
+    match metric {
+        DisplayMetric::Lines => format_percent(lines_percent, precision),
+        DisplayMetric::Commits => {
+            let percent = if total_commits > 0 {
+                (commits as f64 / total_commits as f64) * 100.0
+            } else {
+                0.0
+            };
+            format_percent(percent, precision)
+        }
+        DisplayMetric::NetLines => format!("{:+}", lines_added as i64 - lines_deleted as i64),
+    }
+}
+
+fn line_headers(line_shares: bool) -> (&'static str, &'static str) {
+    if line_shares {
+        ("Added %", "Deleted %")
+    } else {
+        ("Lines Added", "Lines Deleted")
+    }
+}
+
+/// Lines added or deleted, or with `line_shares` their share of the table's total.
+fn line_count_cell(lines: u32, total: u32, options: &TableOptions) -> Cell<'static> {
+    if !options.line_shares {
+        return Cell::from(lines.to_string());
+    }
+    let percent = if total > 0 {
+        lines as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    };
+    Cell::from(format_percent(percent, options.percent_precision))
+}
+
+/// View settings shared by the repository and summary tables.
+pub struct TableOptions {
+    pub metric: DisplayMetric,
+    pub show_merges: bool,
+    pub show_surviving: bool,
+    pub show_signatures: bool,
+    pub show_reviews: bool,
+    /// Show the Org column (`--org-map`).
+    pub show_organizations: bool,
+    pub group_by: GroupBy,
+    pub percent_precision: usize,
+    /// Show lines added/deleted as shares of the table's totals.
+    pub line_shares: bool,
+    pub summary_basis: SummaryBasis,
+    pub use_color: bool,
+    /// High-contrast tables with a marked, bold selection (`--accessible`).
+    pub accessible: bool,
+    pub compact: bool,
+    /// Appended to the summary title; see `App::summary_filter_note`.
+    pub filter_note: &'static str,
+}
+
+impl TableOptions {
+    pub fn from_app(app: &App) -> TableOptions {
+        TableOptions {
+            metric: app.display_metric,
+            show_merges: app.show_merges,
+            show_surviving: app.show_surviving,
+            show_signatures: app.show_signatures,
+            show_reviews: app.show_reviews,
+            show_organizations: app.org_map.is_some(),
+            group_by: app.group_by,
+            percent_precision: app.percent_precision,
+            line_shares: app.show_line_shares,
+            summary_basis: app.summary_basis,
+            use_color: app.use_color,
+            accessible: app.accessible,
+            compact: app.compact,
+            filter_note: app.summary_filter_note(),
+        }
+    }
+}
+
+/// Borders of the main panels, dropped in compact mode to free up rows.
+fn panel_borders(compact: bool) -> Borders {
+    if compact {
+        Borders::NONE
+    } else {
+        Borders::ALL
+    }
+}
+
+/// Width up to which the contribution tables spread their columns over the
+/// whole panel; wider panels get the column widths of this one and leave the
+/// rest empty, so names and numbers stay close enough to read across.
+const MAX_TABLE_WIDTH: u16 = 200;
+
+/// Turns the percentage column widths of a table `width` cells wide into fixed
+/// widths once it is wider than `MAX_TABLE_WIDTH`.
+fn capped_widths(widths: &[Constraint], width: u16) -> Vec<Constraint> {
+    if width <= MAX_TABLE_WIDTH {
+        return widths.to_vec();
+    }
+    widths
+        .iter()
+        .map(|constraint| match *constraint {
+            Constraint::Percentage(percent) => Constraint::Length(MAX_TABLE_WIDTH * percent / 100),
+            other => other,
+        })
+        .collect()
+}
+
+/// Colors of the text around the tables.
+pub struct Palette {
+    /// Loading text and gauge, the summary overview and status messages.
+    pub info: Color,
+    /// The help shortcut line and notices such as missing line data.
+    pub hint: Color,
+    /// Tab group labels and the command bar while nothing is sorted.
+    pub muted: Color,
+}
+
+const STANDARD_PALETTE: Palette = Palette {
+    info: Color::Cyan,
+    hint: Color::Gray,
+    muted: Color::DarkGray,
+};
+
+/// `--accessible` keeps everything outside the tables white: cyan and the
+/// grays are hard to read on many terminal backgrounds, while white stays at
+/// full contrast on dark ones. Headers and highlights keep yellow, which is
+/// bright on dark backgrounds, and gain bold and underline.
+const HIGH_CONTRAST_PALETTE: Palette = Palette {
+    info: Color::White,
+    hint: Color::White,
+    muted: Color::White,
+};
+
+pub fn palette(accessible: bool) -> &'static Palette {
+    if accessible {
+        &HIGH_CONTRAST_PALETTE
+    } else {
+        &STANDARD_PALETTE
+    }
+}
+
+/// Background of every other row in striped tables.
+const STRIPE_COLOR: Color = Color::Indexed(236);
+
+/// Reversed for the selected row, and bold too when `accessible`; otherwise
+/// odd rows get the stripe background when `striped`, so the selection always
+/// stands out over the stripe.
+fn row_style(index: usize, selected: Option<usize>, striped: bool, accessible: bool) -> Style {
+    if Some(index) == selected && accessible {
+        Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    } else if Some(index) == selected {
+        Style::default().add_modifier(Modifier::REVERSED)
+    } else if striped && index % 2 == 1 {
+        Style::default().bg(STRIPE_COLOR)
+    } else {
+        Style::default()
+    }
+}
+
+/// Colors authors are told apart by, leaving out the yellow of headers and
+/// highlights and the grays of stripes and hints.
+const AUTHOR_PALETTE: [Color; 8] = [
+    Color::Green,
+    Color::Blue,
+    Color::Magenta,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+];
+
+/// An author's color, picked by a stable hash of their email so they keep it
+/// in every tab and view and across runs.
+pub fn author_color(email: &str) -> Color {
+    AUTHOR_PALETTE[(stable_hash(email) % AUTHOR_PALETTE.len() as u64) as usize]
+}
+
+/// Author name cell, in the author's color when `use_color`.
+fn author_cell(name: String, email: &str, use_color: bool) -> Cell<'static> {
+    let cell = Cell::from(name);
+    if use_color {
+        cell.style(Style::default().fg(author_color(email)))
+    } else {
+        cell
+    }
+}
+
+/// Marks the selected row's rank with `--accessible`, so the selection does
+/// not rest on reversed colors alone.
+const SELECTED_MARKER: &str = "▶ ";
+
+/// Rank cell, with `SELECTED_MARKER` in front on the selected row when
+/// `accessible`.
+fn rank_cell(rank: u32, selected: bool, accessible: bool) -> Cell<'static> {
+    if selected && accessible {
+        Cell::from(format!("{}#{}", SELECTED_MARKER, rank))
+    } else {
+        Cell::from(format!("#{}", rank))
+    }
+}
+
+/// Yellow column headers, also bold and underlined when `accessible`.
+fn header_row(headers: Vec<&str>, accessible: bool) -> Row<'_> {
+    let mut style = Style::default().fg(Color::Yellow);
+    if accessible {
+        style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    }
+    let cells = headers.into_iter().map(|h| Cell::from(h).style(style));
+
+    Row::new(cells).style(Style::default()).height(1)
+}
+
 pub fn render_repository_tab(
     f: &mut Frame<CrosstermBackend<io::Stdout>>,
     area: Rect,
+    app: &App,
     repo_name: &str,
-    contributions: &[Contribution],
-    selected: Option<usize>,
 ) {
-    let header_cells = [
-        "Author",
-        "Email",
-        "Commits",
-        "Lines Added",
-        "Lines Deleted",
-        "Contribution %",
-    ]
-    .iter()
-    .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
-
-    let header = Row::new(header_cells).style(Style::default()).height(1);
-
-    let rows = contributions.iter().enumerate().map(|(i, c)| {
-        let style = if Some(i) == selected {
-            Style::default().add_modifier(Modifier::REVERSED)
-        } else {
-            Style::default()
-        };
+    let contributions = match app.contributions.get(repo_name) {
+        Some(contributions) => contributions,
+        None => return,
+    };
+    let selected = app.selected_in_tab.get(app.current_tab).copied().flatten();
+    let info = app.repository_info.get(repo_name);
+    let options = &TableOptions::from_app(app);
 
-        let cells = [
-            Cell::from(c.author.clone()),
+    let mut title = format!("Repository: {}", repo_name);
+    if let Some(limits) = app.history_limits(repo_name) {
+        title.push_str(&format!(" ({})", limits));
+    }
+    if let Some(gap) = info.and_then(|info| info.median_commit_gap) {
+        title.push_str(&format!(" | median gap: {}", format_gap(gap)));
+    }
+    if contributions.iter().any(|c| c.working_tree) {
+        title.push_str(&format!(
+            " | {} includes uncommitted changes",
+            WORKING_TREE_MARKER
+        ));
+    }
+    if info.map(|info| info.shallow).unwrap_or(false) {
+        title.push_str(" ⚠ shallow clone, history is incomplete");
+    }
+    let mut title = vec![Span::raw(title)];
+    if let Some(health) = app.repository_health(repo_name) {
+        title.extend([
+            Span::raw(" | Health "),
+            health_badge(health.score, options.accessible),
+            Span::raw(format!(" ({})", health.breakdown())),
+        ]);
+    }
+    let title = Spans::from(title);
+
+    if !has_line_data(contributions) {
+        let message = Paragraph::new(NO_LINE_DATA_MESSAGE)
+            .style(Style::default().fg(palette(options.accessible).hint))
+            .alignment(tui::layout::Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(panel_borders(options.compact)),
+            );
+        f.render_widget(message, area);
+        return;
+    }
+
+    let total_commits: u32 = contributions.iter().map(|c| c.commits).sum();
+    let total_added: u32 = contributions.iter().map(|c| c.lines_added).sum();
+    let total_deleted: u32 = contributions.iter().map(|c| c.lines_deleted).sum();
+
+    let total_surviving: u32 = contributions.iter().map(|c| c.surviving_lines).sum();
+    let surviving_width = if options.show_surviving { 2 } else { 0 };
+    let signed_width = if options.show_signatures { 1 } else { 0 };
+
+    let mut headers = vec!["Rank", "Author", "Email", "Commits"];
+    let mut widths = vec![
+        Constraint::Percentage(6),
+        Constraint::Percentage(17 - surviving_width),
+        Constraint::Percentage(27 - 3 * surviving_width),
+        Constraint::Percentage(10 - 3 * signed_width),
+    ];
+    if options.show_merges {
+        headers.push("Merges");
+        widths[2] = Constraint::Percentage(19 - 3 * surviving_width);
+        widths.push(Constraint::Percentage(8));
+    }
+    let (added_header, deleted_header) = line_headers(options.line_shares);
+    headers.extend([
+        added_header,
+        deleted_header,
+        metric_header(options.metric, "Contribution %"),
+    ]);
+    widths.extend([
+        Constraint::Percentage(13 - surviving_width - 3 * signed_width),
+        Constraint::Percentage(13 - surviving_width - 3 * signed_width),
+        Constraint::Percentage(14 - 3 * signed_width),
+    ]);
+    if options.show_surviving {
+        headers.push("Surviving");
+        widths.push(Constraint::Percentage(12));
+    }
+    if options.show_signatures {
+        headers.push("Signed %");
+        widths.push(Constraint::Percentage(8));
+    }
+
+    let header = header_row(headers, options.accessible);
+
+    let rows = app.repository_rows(repo_name);
+    let rows = rows.iter().enumerate().map(|(i, c)| {
+        let style = row_style(i, selected, options.use_color, options.accessible);
+
+        let mut cells = vec![
+            rank_cell(c.rank, Some(i) == selected, options.accessible),
+            author_cell(c.display_author(), &c.email, options.use_color),
             Cell::from(c.email.clone()),
             Cell::from(c.commits.to_string()),
-            Cell::from(c.lines_added.to_string()),
-            Cell::from(c.lines_deleted.to_string()),
-            Cell::from(format!("{:.2}%", c.contribution_percent)),
         ];
+        if options.show_merges {
+            cells.push(Cell::from(c.merges.to_string()));
+        }
+        cells.extend([
+            line_count_cell(c.lines_added, total_added, options),
+            line_count_cell(c.lines_deleted, total_deleted, options),
+            Cell::from(metric_value(
+                options.metric,
+                c.contribution_percent,
+                c.commits,
+                total_commits,
+                c.lines_added,
+                c.lines_deleted,
+                options.percent_precision,
+            )),
+        ]);
+        if options.show_surviving {
+            cells.push(surviving_cell(
+                c.surviving_lines,
+                total_surviving,
+                options.percent_precision,
+            ));
+        }
+        if options.show_signatures {
+            cells.push(Cell::from(format_percent(
+                signed_percent(c.signed_commits, c.commits),
+                options.percent_precision,
+            )));
+        }
 
         Row::new(cells).style(style).height(1)
     });
 
+    let widths = capped_widths(&widths, area.width);
     let table = Table::new(rows)
         .header(header)
         .block(
             Block::default()
-                .title(format!("Repository: {}", repo_name))
-                .borders(Borders::ALL),
+                .title(title)
+                .borders(panel_borders(options.compact)),
         )
-        .widths(&[
-            Constraint::Percentage(20),
-            Constraint::Percentage(30),
-            Constraint::Percentage(10),
-            Constraint::Percentage(13),
-            Constraint::Percentage(13),
-            Constraint::Percentage(14),
-        ])
-        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-        .highlight_symbol("> ");
+        .widths(&widths);
 
     f.render_widget(table, area);
 }
 
+/// Health score as a badge colored green (70+), yellow (40+) or red; when
+/// `accessible` the band is also spelled out as ✓, ⚠ or ✗.
+fn health_badge(score: u32, accessible: bool) -> Span<'static> {
+    let (color, symbol) = match score {
+        70.. => (Color::Green, "✓"),
+        40..=69 => (Color::Yellow, "⚠"),
+        _ => (Color::Red, "✗"),
+    };
+    Span::styled(
+        if accessible {
+            format!(" {} {} ", symbol, score)
+        } else {
+            format!(" {} ", score)
+        },
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+pub fn render_summary_overview(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    overview: &OverviewStats,
+    coverage: &CoverageStats,
+    health: Option<(u32, &str, u32)>,
+    new_and_returning: Option<(usize, usize)>,
+    accessible: bool,
+) {
+    let mut lines = vec![
+        Spans::from(format!(
+            "Repositories: {} | Authors: {} | Commits: {}",
+            overview.repositories, overview.authors, overview.commits
+        )),
+        Spans::from(format!(
+            "Line churn: {} (+{} / -{})",
+            overview.line_churn(),
+            overview.lines_added,
+            overview.lines_deleted
+        )),
+        Spans::from(Span::styled(
+            coverage.summary(),
+            if coverage.is_complete() {
+                Style::default()
+            } else {
+                Style::default().fg(Color::Yellow)
+            },
+        )),
+    ];
+
+    if let Some((average, lowest, lowest_score)) = health {
+        lines.push(Spans::from(vec![
+            Span::raw("Average health "),
+            health_badge(average, accessible),
+            Span::raw(format!(" | Lowest: {} ", lowest)),
+            health_badge(lowest_score, accessible),
+        ]));
+    }
+
+    if let Some((new, returning)) = new_and_returning {
+        lines.push(Spans::from(format!(
+            "New contributors this period: {} | Returning: {}",
+            new, returning
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(palette(accessible).info))
+        .alignment(tui::layout::Alignment::Center);
+
+    f.render_widget(paragraph, area);
+}
+
 pub fn render_summary_tab(
     f: &mut Frame<CrosstermBackend<io::Stdout>>,
     area: Rect,
-    summaries: &[AuthorSummary],
+    summaries: &[&AuthorSummary],
     selected: Option<usize>,
+    options: &TableOptions,
 ) {
-    let header_cells = [
-        "Author",
+    let total_commits: u32 = summaries.iter().map(|s| s.total_commits).sum();
+    let total_added: u32 = summaries.iter().map(|s| s.total_lines_added).sum();
+    let total_deleted: u32 = summaries.iter().map(|s| s.total_lines_deleted).sum();
+    let total_surviving: u32 = summaries.iter().map(|s| s.total_surviving_lines).sum();
+    let surviving_width = if options.show_surviving { 1 } else { 0 };
+    let signed_width = if options.show_signatures { 1 } else { 0 };
+    let reviews_width = if options.show_reviews { 1 } else { 0 };
+    let org_width = if options.show_organizations { 1 } else { 0 };
+
+    let mut headers = vec![
+        "Rank",
+        options.group_by.name_header(),
         "Email",
         "Total Commits",
-        "Lines Added",
-        "Lines Deleted",
-        "Overall %",
+    ];
+    let mut widths = vec![
+        Constraint::Percentage(5),
+        Constraint::Percentage(13 - 3 * surviving_width),
+        Constraint::Percentage(17 - 4 * surviving_width - 3 * reviews_width),
+        Constraint::Percentage(10 - 3 * signed_width - 2 * org_width),
+    ];
+    if options.show_organizations {
+        headers.insert(3, "Org");
+        widths.insert(3, Constraint::Percentage(8));
+    }
+    if options.show_merges {
+        headers.push("Merges");
+        widths[2] = Constraint::Percentage(9 - 4 * surviving_width - 3 * reviews_width);
+        widths.push(Constraint::Percentage(8));
+    }
+    let (added_header, deleted_header) = line_headers(options.line_shares);
+    headers.extend([
+        added_header,
+        deleted_header,
+        metric_header(options.metric, "Overall %"),
         "Preferred Repo",
         "Preferred %",
-    ]
-    .iter()
-    .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+    ]);
+    widths.extend([
+        Constraint::Percentage(10 - 3 * signed_width - 2 * org_width),
+        Constraint::Percentage(10 - 3 * signed_width - 2 * org_width),
+        Constraint::Percentage(10 - 2 * signed_width),
+        Constraint::Percentage(15 - 5 * surviving_width - 3 * signed_width - 3 * reviews_width),
+        Constraint::Percentage(10 - 2 * surviving_width - 2 * org_width),
+    ]);
+    if options.show_surviving {
+        headers.push("Surviving");
+        widths.push(Constraint::Percentage(14));
+    }
+    if options.show_signatures {
+        headers.push("Signed %");
+        widths.push(Constraint::Percentage(8));
+    }
+    if options.show_reviews {
+        headers.push("Reviews");
+        widths.push(Constraint::Percentage(6));
+    }
 
-    let header = Row::new(header_cells).style(Style::default()).height(1);
+    let header = header_row(headers, options.accessible);
 
     let rows = summaries.iter().enumerate().map(|(i, s)| {
-        let style = if Some(i) == selected {
-            Style::default().add_modifier(Modifier::REVERSED)
-        } else {
-            Style::default()
-        };
+        let style = row_style(i, selected, options.use_color, options.accessible);
 
-        let cells = [
-            Cell::from(s.author.clone()),
+        let name = match s.trend {
+            Some(trend) => format!("{} {}", trend.arrow(), s.author),
+            None => s.author.clone(),
+        };
+        let mut cells = vec![
+            rank_cell(s.rank, Some(i) == selected, options.accessible),
+            author_cell(name, &s.email, options.use_color),
             Cell::from(s.email.clone()),
             Cell::from(s.total_commits.to_string()),
-            Cell::from(s.total_lines_added.to_string()),
-            Cell::from(s.total_lines_deleted.to_string()),
-            Cell::from(format!("{:.2}%", s.overall_contribution_percent)),
-            Cell::from(s.preferred_repo.clone()),
-            Cell::from(format!("{:.2}%", s.preferred_repo_percent)),
         ];
+        if options.show_organizations {
+            cells.insert(3, Cell::from(s.organization.clone().unwrap_or_default()));
+        }
+        if options.show_merges {
+            cells.push(Cell::from(s.total_merges.to_string()));
+        }
+        cells.extend([
+            line_count_cell(s.total_lines_added, total_added, options),
+            line_count_cell(s.total_lines_deleted, total_deleted, options),
+            Cell::from(metric_value(
+                options.metric,
+                s.overall_contribution_percent,
+                s.total_commits,
+                total_commits,
+                s.total_lines_added,
+                s.total_lines_deleted,
+                options.percent_precision,
+            )),
+            Cell::from(s.preferred_repo.clone()),
+            Cell::from(format_percent(
+                s.preferred_repo_percent,
+                options.percent_precision,
+            )),
+        ]);
+        if options.show_surviving {
+            cells.push(surviving_cell(
+                s.total_surviving_lines,
+                total_surviving,
+                options.percent_precision,
+            ));
+        }
+        if options.show_signatures {
+            cells.push(Cell::from(format_percent(
+                signed_percent(s.total_signed_commits, s.total_commits),
+                options.percent_precision,
+            )));
+        }
+        if options.show_reviews {
+            cells.push(Cell::from(s.reviews.to_string()));
+        }
 
         Row::new(cells).style(style).height(1)
     });
 
+    let widths = capped_widths(&widths, area.width);
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!(
+                    "{}{}{}",
+                    options.group_by.summary_title(),
+                    options.summary_basis.title_note(),
+                    options.filter_note
+                ))
+                .borders(panel_borders(options.compact)),
+        )
+        .widths(&widths);
+
+    f.render_widget(table, area);
+}
+
+/// Blamed lines at `HEAD` with their share of all surviving lines.
+fn surviving_cell(lines: u32, total: u32, precision: usize) -> Cell<'static> {
+    let percent = if total > 0 {
+        lines as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    };
+    Cell::from(format!(
+        "{} ({})",
+        lines,
+        format_percent(percent, precision)
+    ))
+}
+
+fn delta_cell(delta: i64) -> Cell<'static> {
+    let color = match delta {
+        d if d > 0 => Color::Green,
+        d if d < 0 => Color::Red,
+        _ => Color::Gray,
+    };
+    Cell::from(format!("{:+}", delta)).style(Style::default().fg(color))
+}
+
+pub fn render_comparison_view(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    comparison: &ComparisonView,
+    use_color: bool,
+    accessible: bool,
+) {
+    let header = header_row(
+        vec![
+            "Author",
+            "Email",
+            "Commits (before)",
+            "Commits (after)",
+            "Δ Commits",
+            "Lines (before)",
+            "Lines (after)",
+            "Δ Lines",
+        ],
+        accessible,
+    );
+
+    let rows = comparison.authors.iter().map(|a| {
+        let cells = [
+            author_cell(a.author.clone(), &a.email, use_color),
+            Cell::from(a.email.clone()),
+            Cell::from(a.baseline_commits.to_string()),
+            Cell::from(a.current_commits.to_string()),
+            delta_cell(a.commits_delta()),
+            Cell::from(a.baseline_lines.to_string()),
+            Cell::from(a.current_lines.to_string()),
+            delta_cell(a.lines_delta()),
+        ];
+
+        Row::new(cells).height(1)
+    });
+
     let table = Table::new(rows)
         .header(header)
         .block(
             Block::default()
-                .title("Summary Across All Repositories")
+                .title(format!(
+                    "Comparison: {} → {} (press 'c' to return)",
+                    comparison.baseline_label, comparison.current_label
+                ))
                 .borders(Borders::ALL),
         )
         .widths(&[
             Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(10),
+            Constraint::Percentage(21),
             Constraint::Percentage(10),
             Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(15),
-            Constraint::Percentage(10),
+            Constraint::Percentage(9),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(11),
+        ]);
+
+    f.render_widget(table, area);
+}
+
+pub fn render_collaborators_view(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    pairs: &[CollaboratorPair],
+    use_color: bool,
+    accessible: bool,
+) {
+    let header = header_row(
+        vec![
+            "Author",
+            "Email",
+            "Collaborator",
+            "Email",
+            "Shared Files",
+            "Affinity",
+        ],
+        accessible,
+    );
+
+    let rows = pairs.iter().map(|pair| {
+        Row::new(vec![
+            author_cell(pair.first_author.clone(), &pair.first_email, use_color),
+            Cell::from(pair.first_email.clone()),
+            author_cell(pair.second_author.clone(), &pair.second_email, use_color),
+            Cell::from(pair.second_email.clone()),
+            Cell::from(pair.shared_files.to_string()),
+            Cell::from(format!("{:.0}%", pair.affinity * 100.0)),
         ])
-        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-        .highlight_symbol("> ");
+        .height(1)
+    });
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Who works together: shared files among the top {} authors (press 'a' to return)",
+                    COLLABORATION_AUTHOR_LIMIT
+                ))
+                .borders(Borders::ALL),
+        )
+        .widths(&[
+            Constraint::Percentage(16),
+            Constraint::Percentage(22),
+            Constraint::Percentage(16),
+            Constraint::Percentage(22),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+        ]);
+
+    f.render_widget(table, area);
+}
+
+pub fn render_orphaned_view(f: &mut Frame<CrosstermBackend<io::Stdout>>, area: Rect, app: &App) {
+    let header = header_row(
+        vec!["Repository", "Directory", "Contributors", "Largest Shares"],
+        app.accessible,
+    );
+
+    let rows = app.orphaned.iter().map(|orphan| {
+        Row::new(vec![
+            Cell::from(orphan.repository.clone()),
+            Cell::from(format!("{}/", orphan.directory)),
+            Cell::from(orphan.contributors.to_string()),
+            Cell::from(orphan.ownership_spread(app.percent_precision)),
+        ])
+        .height(1)
+    });
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Orphaned directories: nobody owns more than {} (press 'o' to return)",
+                    format_percent(app.orphan_threshold, 0)
+                ))
+                .borders(Borders::ALL),
+        )
+        .widths(&[
+            Constraint::Percentage(18),
+            Constraint::Percentage(22),
+            Constraint::Percentage(12),
+            Constraint::Percentage(48),
+        ]);
+
+    f.render_widget(table, area);
+}
+
+pub fn render_at_risk_view(f: &mut Frame<CrosstermBackend<io::Stdout>>, area: Rect, app: &App) {
+    let header = header_row(
+        vec![
+            "Repository",
+            "File",
+            "Lines",
+            "Risk",
+            "Main Inactive Owner",
+            "Last Commit",
+        ],
+        app.accessible,
+    );
+
+    let rows = app.at_risk_files.iter().map(|file| {
+        Row::new(vec![
+            Cell::from(file.repository.clone()),
+            Cell::from(file.path.clone()),
+            Cell::from(file.lines.to_string()),
+            Cell::from(format_percent(file.risk_percent, app.percent_precision)),
+            Cell::from(file.inactive_owner.clone()),
+            Cell::from(file.inactivity()),
+        ])
+        .height(1)
+    });
+
+    let title = if app.show_surviving {
+        format!(
+            "Files owned by authors inactive for over {} days (press 'i' to return)",
+            app.inactive_days
+        )
+    } else {
+        String::from("Files owned by inactive authors: run with --blame (press 'i' to return)")
+    };
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .widths(&[
+            Constraint::Percentage(14),
+            Constraint::Percentage(36),
+            Constraint::Percentage(8),
+            Constraint::Percentage(9),
+            Constraint::Percentage(19),
+            Constraint::Percentage(14),
+        ]);
 
     f.render_widget(table, area);
 }
 
-pub fn render_help_shortcut(f: &mut Frame<CrosstermBackend<io::Stdout>>, area: Rect) {
-    let help_text = "Press '?' to show help";
+/// Errors, preceded by the `--merge-bots` and `--fuzzy-merge` merges so they
+/// can be checked.
+pub fn render_errors_view(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    errors: &[String],
+    merges: &[AuthorMerge],
+) {
+    let mut lines: Vec<Spans> = merges
+        .iter()
+        .map(|merge| {
+            Spans::from(Span::styled(
+                format!("Merged: {}", merge.describe()),
+                Style::default().fg(Color::Yellow),
+            ))
+        })
+        .collect();
+    if !merges.is_empty() {
+        lines.push(Spans::from(""));
+    }
+    if errors.is_empty() {
+        lines.push(Spans::from("No errors: every repository was analyzed."));
+    } else {
+        lines.extend(errors.iter().map(|error| Spans::from(error.as_str())));
+    }
+
+    let title = if merges.is_empty() {
+        format!("Errors ({}) (press 'x' to return)", errors.len())
+    } else {
+        format!(
+            "Errors ({}) and merged authors ({}) (press 'x' to return)",
+            errors.len(),
+            merges.len()
+        )
+    };
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Red))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().title(title).borders(Borders::ALL));
+
+    f.render_widget(paragraph, area);
+}
+
+/// One line below the tables: the active sort and filter, or while `:` has
+/// focus the command being typed.
+pub fn render_command_bar(f: &mut Frame<CrosstermBackend<io::Stdout>>, area: Rect, app: &App) {
+    // Side borders only, continuing the main block's frame.
+    let block = Block::default().borders(if app.compact {
+        Borders::NONE
+    } else {
+        Borders::LEFT | Borders::RIGHT
+    });
+    let inner = block.inner(area);
+
+    let bar = match &app.command_line {
+        Some(command) => {
+            let prompt = format!(":{}", command);
+            f.set_cursor(
+                inner.x + (prompt.chars().count() as u16).min(inner.width.saturating_sub(1)),
+                inner.y,
+            );
+            Paragraph::new(prompt).style(Style::default().fg(Color::Yellow))
+        }
+        None => Paragraph::new(format!(
+            " {} | ':' sort <column> [asc|desc], filter <text>",
+            app.sort_filter_status()
+        ))
+        .style(Style::default().fg(if app.is_sorted_or_filtered() {
+            palette(app.accessible).info
+        } else {
+            palette(app.accessible).muted
+        })),
+    };
+
+    f.render_widget(bar.block(block), area);
+}
+
+pub fn render_help_shortcut(
+    f: &mut Frame<CrosstermBackend<io::Stdout>>,
+    area: Rect,
+    error_count: usize,
+    status: Option<&str>,
+    borders: Borders,
+    accessible: bool,
+) {
+    let palette = palette(accessible);
+    let (help_text, color) = if let Some(status) = status {
+        (format!("{} | Press '?' to show help", status), palette.info)
+    } else if error_count > 0 {
+        (
+            format!(
+                "⚠ {} error(s) during analysis, press 'x' to view | Press '?' to show help",
+                error_count
+            ),
+            Color::Yellow,
+        )
+    } else {
+        (String::from("Press '?' to show help"), palette.hint)
+    };
     let help_paragraph = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(color))
         .alignment(tui::layout::Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        .block(Block::default().borders(borders));
 
     f.render_widget(help_paragraph, area);
 }
 
+const HELP_LINES: &[&str] = &[
+    "↑/↓: Navigate entries | Tab/Shift+Tab: Switch repositories | Backspace/[, ]: Back, forward | Enter: Author detail | Esc: Clear selection",
+    "m: Cycle metric (lines % / commits % / net lines) | p: Line counts / shares",
+    "c: Window comparison | a: Who works together | o: Orphaned directories | i: Files owned by inactive authors | x: Errors",
+    "Ctrl+P: Go to repository | r: Open repository folder (o is orphaned directories) | ?: Toggle help | q: Quit | e: Export menu | h: Export HTML report (H: and open it) | w: Export CODEOWNERS suggestions",
+    "d: Compact layout (no borders, one-line tabs, footer only for messages) | u: Summary of authors in every repository",
+    ":: Command bar (sort <column> [asc|desc], filter <text>; Enter runs, Esc cancels)",
+];
+
 pub fn render_help(f: &mut Frame<CrosstermBackend<io::Stdout>>, area: Rect) {
-    let help_text = vec![
-        Spans::from("↑/↓: Navigate entries | Tab/Shift+Tab: Switch repositories"),
-        Spans::from("?: Toggle help | q: Quit | h: Export HTML report"),
-    ];
+    let help_text = HELP_LINES
+        .iter()
+        .map(|line| Spans::from(*line))
+        .collect::<Vec<Spans>>();
 
     let help_paragraph = Paragraph::new(help_text)
         .style(Style::default())
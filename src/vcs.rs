@@ -0,0 +1,111 @@
+use crate::git::{
+    analyze_repository, is_git_repository, latest_commit_at, reference_commit_count,
+    AnalysisOptions, Contribution, RepositoryInfo,
+};
+use std::{error::Error, path::Path};
+
+/// A repository's name, its authors' contributions and what was analyzed.
+pub type Analysis = (String, Vec<Contribution>, RepositoryInfo);
+
+/// A version control system repositories can be analyzed with. Each backend
+/// turns a working copy's history into the same `Contribution`s, so
+/// summaries, the TUI and the exports work the same whichever one produced
+/// them.
+pub trait VcsBackend: Sync {
+    /// Directory in a working copy holding the backend's metadata; repository
+    /// discovery never descends into it.
+    fn metadata_dir(&self) -> &'static str;
+
+    /// Whether `path` is the root of a working copy of this backend.
+    fn detect(&self, path: &Path) -> bool {
+        path.join(self.metadata_dir()).is_dir()
+    }
+
+    /// Lists the repository's authors and counts each one's commits and lines
+    /// under `options`.
+    fn analyze(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Analysis, Box<dyn Error>>;
+
+    /// Counts the commits `analyze` should credit by separate means, as a
+    /// check on it (`--validate`).
+    fn reference_commit_count(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<u32, Box<dyn Error>>;
+
+    /// Time of the newest commit, without a full analysis; `None` when there
+    /// are no commits (`--order recent`).
+    fn latest_commit_at(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Option<i64>, Box<dyn Error>>;
+}
+
+pub struct Git;
+
+impl VcsBackend for Git {
+    fn metadata_dir(&self) -> &'static str {
+        ".git"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        is_git_repository(path)
+    }
+
+    fn analyze(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Analysis, Box<dyn Error>> {
+        analyze_repository(repo_path, options)
+    }
+
+    fn reference_commit_count(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<u32, Box<dyn Error>> {
+        reference_commit_count(repo_path, options)
+    }
+
+    fn latest_commit_at(
+        &self,
+        repo_path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Option<i64>, Box<dyn Error>> {
+        latest_commit_at(repo_path, options)
+    }
+}
+
+/// Every backend compiled in, in the order they are tried.
+pub const BACKENDS: &[&dyn VcsBackend] = &[
+    &Git,
+    #[cfg(feature = "mercurial")]
+    &crate::hg::Mercurial,
+];
+
+/// The backend whose working copy `path` is, if any.
+pub fn detect_backend(path: &Path) -> Option<&'static dyn VcsBackend> {
+    BACKENDS
+        .iter()
+        .copied()
+        .find(|backend| backend.detect(path))
+}
+
+/// The backend to analyze `path` with: the detected one, else git, which
+/// also handles paths inside a repository and bare repositories.
+pub fn backend_for(path: &Path) -> &'static dyn VcsBackend {
+    detect_backend(path).unwrap_or(&Git)
+}
+
+/// Whether `name` is a backend's metadata directory, e.g. `.git`.
+pub fn is_metadata_dir(name: &str) -> bool {
+    BACKENDS
+        .iter()
+        .any(|backend| backend.metadata_dir() == name)
+}